@@ -0,0 +1,88 @@
+// Parse benchmark.
+// Parses a synthetic log of 1M events (override with SCRAPER_BENCH_EVENTS),
+// comparing the decoder registry against compiling the sub-data regex
+// for every event line, as the parser used to.
+// When run by 'cargo test' rather than 'cargo bench', only a few events
+// are parsed, to check that the benchmark still works.
+
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use scraper::LogParser;
+
+const DEFAULT_EVENTS: usize = 1_000_000;
+const TEST_EVENTS: usize = 100;
+
+// Event types, sub-data and sub-data patterns used in the synthetic log.
+const EVENTS: &[(&str, &str, &str)] = &[
+    ("SIGNON", "123 0ab12c OK 26 0 1 v:245", r"([-\*\+0-9]+) ([0-9a-fA-F]+) (.+?) ([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$"),
+    ("IMPACT", "120 3 1 2 3 4 5 -Hi v:244", r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([\-a-zA-Z]+) v:(.+?)$"),
+    ("INPUT", "2 1 30 v:243", r"([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$"),
+    ("ZONECHANGE", "1 2 10 20 v:242", r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$"),
+    ("TRIP", "1234 600 120 60 30 540 v:241", r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+)(.*) v:(.+?)$"),
+];
+
+// Make the i'th line of the synthetic log.
+fn synthetic_line(i: usize) -> String {
+    let (event_type, sub_data, _) = EVENTS[i % EVENTS.len()];
+    format!(
+        "15/03/2024 08:{:02}:{:02} EVENT 1234 {} -274700000/1530200000/0/45/32 {} {}",
+        (i / 60) % 60,
+        i % 60,
+        1_710_489_600 + i,
+        event_type,
+        sub_data
+    )
+}
+
+// Parse with the decoder registry.
+fn parse_with_registry(num_events: usize) -> Duration {
+    let start = Instant::now();
+    let mut parser = LogParser::new();
+    for i in 0..num_events {
+        parser.parse_line(&synthetic_line(i));
+    }
+    black_box(parser.finish());
+    start.elapsed()
+}
+
+// Parse as before the registry, by also compiling the
+// sub-data pattern for every event line.
+fn parse_with_per_line_compile(num_events: usize) -> Duration {
+    let start = Instant::now();
+    let mut parser = LogParser::new();
+    for i in 0..num_events {
+        let line = synthetic_line(i);
+        let (_, sub_data, pattern) = EVENTS[i % EVENTS.len()];
+        let regex = Regex::new(pattern).expect("Invalid benchmark regex pattern");
+        black_box(regex.captures(sub_data));
+        parser.parse_line(&line);
+    }
+    black_box(parser.finish());
+    start.elapsed()
+}
+
+fn main() {
+    // 'cargo bench' passes --bench, 'cargo test' doesn't.
+    let benchmarking = env::args().any(|arg| arg == "--bench");
+    let num_events = if benchmarking {
+        env::var("SCRAPER_BENCH_EVENTS")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(DEFAULT_EVENTS)
+    } else {
+        TEST_EVENTS
+    };
+
+    println!("Parsing synthetic log of {} events.", num_events);
+
+    let registry = parse_with_registry(num_events);
+    println!("Decoder registry:      {:>10.3?} ({:.0} events/s)", registry, num_events as f64 / registry.as_secs_f64());
+
+    let per_line = parse_with_per_line_compile(num_events);
+    println!("Per-line regex compile: {:>10.3?} ({:.0} events/s)", per_line, num_events as f64 / per_line.as_secs_f64());
+
+    println!("Speed up: {:.1}x", per_line.as_secs_f64() / registry.as_secs_f64());
+}
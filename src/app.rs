@@ -2,6 +2,8 @@
 
 use log::info;
 
+use std::path::PathBuf;
+
 use eframe::{egui, App};
 use egui::epaint::{CornerRadius};
 use walkers::{MapMemory};
@@ -51,13 +53,22 @@ pub struct MyApp {
     pub show_report_events: bool,
     pub show_debug_events: bool,
     pub show_gps_events: bool,
+    pub show_raw_lines: bool,
     pub show_time_series: bool,
     pub plot_speed: bool,
     pub plot_battery: bool,
+    pub hide_poor_gps: bool,
     pub show_about: bool,
     pub show_help: bool,
     pub about_icon: Option<egui::TextureHandle>,
     pub show_changelog: bool,
+    pub show_parse_issues: bool,
+    pub show_controller_history: bool,
+    pub show_connect: bool,
+    pub connect_address: String,
+    pub connect_baud_rate: u32,
+    pub connect_capture: bool,
+    pub connect_capture_path: String,
     pub ui_state: UiState,
     pub selected_id: Option<String>,
     pub dark_mode: bool,
@@ -73,6 +84,7 @@ pub struct MyApp {
     pub satellite_tiles: Option<walkers::HttpTiles>,
     pub map_state: Option<MapState>,
     pub plot_state: PlotState,
+    pub image_scale: f32,
     _runtime: tokio::runtime::Runtime,
     
     // Help images.
@@ -115,13 +127,22 @@ impl Default for MyApp {
             show_report_events: false,
             show_debug_events: false,
             show_gps_events: false,
+            show_raw_lines: false,
             show_time_series: false,
             plot_battery: false,
             plot_speed: true,
+            hide_poor_gps: false,
             show_about: false,
             show_help: false,
             about_icon: None,
             show_changelog: false,
+            show_parse_issues: false,
+            show_controller_history: false,
+            show_connect: false,
+            connect_address: "".to_string(),
+            connect_baud_rate: 115200,
+            connect_capture: false,
+            connect_capture_path: "capture.log".to_string(),
             ui_state: UiState::default(),
             selected_id: Some("".to_string()),
             dark_mode: true,
@@ -137,6 +158,7 @@ impl Default for MyApp {
             satellite_tiles: None,
             map_state: None,
             plot_state: PlotState::default(),
+            image_scale: 2.0,
             _runtime: runtime,
 
             // Help images.
@@ -563,18 +585,24 @@ impl App for MyApp {
         // Add border to the main window.
         self.draw_main_window_border(ctx, frame);
 
+        // Pick up any results from a file being loaded.
+        self.scraper.poll_loading();
+
         // Check for dropped file first.
         // Then check for file dialog file.
         if !ctx.input(|i| i.raw.dropped_files.is_empty()) {
             info!("File dropped - reinitializing data");
             self.scraper.reinitialize_data();
             
+            // Several files can be dropped, and are merged.
             let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
-            if let Some(file) = dropped_files.first() {
-                if let Some(path) = &file.path {
-                    info!("Processing dropped file: {:?}", path);
-                    self.scraper.load_file_from_path(path);
-                }
+            let paths: Vec<PathBuf> = dropped_files.iter()
+                .filter_map(|file| file.path.clone())
+                .collect();
+            if !paths.is_empty() {
+                info!("Processing dropped files: {:?}", paths);
+                self.selected_id = None;
+                self.scraper.load_files_from_paths(ctx, &paths);
             }
         }
         
@@ -589,6 +617,10 @@ impl App for MyApp {
         ui::draw_about_dialog(self, ctx);
         ui::draw_help_panel(self, ctx);
         ui::draw_changelog(self, ctx);
+        ui::draw_parse_issues(self, ctx);
+        ui::draw_controller_history(self, ctx);
+        ui::draw_zip_picker(self, ctx);
+        ui::draw_connect_dialog(self, ctx);
 
         // Check if we need to plot gps data.
         if self.show_gps_plot {
@@ -0,0 +1,109 @@
+// Compressed log archives.
+// Logs from the field can be gzip files, or zip bundles
+// holding the current log and rolled over .bak files.
+
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use zip::ZipArchive;
+
+// Kind of log file, from the first bytes of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFileKind {
+    Plain,
+    Gzip,
+    Zip,
+}
+
+// A file in a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub size: u64,
+}
+
+// Magic numbers at the start of compressed files.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZIP_MAGIC: [u8; 4] = [b'P', b'K', 0x03, 0x04];
+
+// Work out the kind of log file from its contents,
+// so that renamed files still open.
+pub fn log_file_kind(path: &Path) -> io::Result<LogFileKind> {
+    let mut magic = [0u8; 4];
+    let mut file = File::open(path)?;
+    let num_bytes = file.read(&mut magic)?;
+
+    if num_bytes >= 2 && magic[..2] == GZIP_MAGIC {
+        Ok(LogFileKind::Gzip)
+    } else if num_bytes == 4 && magic == ZIP_MAGIC {
+        Ok(LogFileKind::Zip)
+    } else {
+        Ok(LogFileKind::Plain)
+    }
+}
+
+// List the files in a zip archive, in archive order.
+// Directories are left out.
+pub fn zip_members(path: &Path) -> io::Result<Vec<ArchiveMember>> {
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    let mut members = Vec::new();
+    for index in 0..archive.len() {
+        let member = archive.by_index(index).map_err(io::Error::other)?;
+        if member.is_file() {
+            members.push(ArchiveMember {
+                name: member.name().to_string(),
+                size: member.size(),
+            });
+        }
+    }
+    Ok(members)
+}
+
+// Read a log file, decompressing it if needed.
+// The reader is passed to the given function, along with the
+// uncompressed size of the log (used for progress).
+// For gzip files the size is only an estimate, so reading can go past it.
+// Member is the file to read from a zip archive, and is
+// required for zip archives.
+pub fn read_log_file<T>(
+    path: &Path,
+    member: Option<&str>,
+    read: impl FnOnce(&mut dyn BufRead, u64) -> io::Result<T>,
+) -> io::Result<T> {
+    match log_file_kind(path)? {
+        LogFileKind::Plain => {
+            let file = File::open(path)?;
+            let total_bytes = file.metadata()?.len();
+            read(&mut BufReader::new(file), total_bytes)
+        }
+        LogFileKind::Gzip => {
+            let mut file = File::open(path)?;
+            let total_bytes = gzip_size(&mut file)?;
+            read(&mut BufReader::new(MultiGzDecoder::new(file)), total_bytes)
+        }
+        LogFileKind::Zip => {
+            let Some(member) = member else {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "No file selected from zip archive."));
+            };
+            let mut archive = ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+            let file = archive.by_name(member).map_err(io::Error::other)?;
+            let total_bytes = file.size();
+            read(&mut BufReader::new(file), total_bytes)
+        }
+    }
+}
+
+// Uncompressed size of a gzip file, from the size in the trailer.
+// This is only a hint for progress. The trailer only holds the size of
+// the last member, modulo 4GB, so it can be well short of the real size.
+// It is never taken as less than the compressed size.
+// Leaves the file at the start.
+fn gzip_size(file: &mut File) -> io::Result<u64> {
+    let mut trailer = [0u8; 4];
+    let compressed_size = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut trailer)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok((u32::from_le_bytes(trailer) as u64).max(compressed_size))
+}
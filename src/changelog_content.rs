@@ -10,6 +10,34 @@ pub fn draw_changelog_content(ui: &mut egui::Ui, _app: &mut MyApp) {
     ui.heading("Scraper Changelog");
     ui.separator();
 
+    ui.collapsing("0.7.0 - update release", |ui| {
+        ui.label("Split log parsing into a library with no GUI dependencies.");
+        ui.label("Log files are now read in a single pass.");
+        ui.label("Log files are now loaded in the background with a progress bar and cancel.");
+        ui.label("Event sub-data patterns are now compiled once, speeding up loading of large files.");
+        ui.label("Events are now defined in events.yml, so new events can be added without a new release.");
+        ui.label("Event data is now typed, with units, so plots no longer depend on display labels.");
+        ui.label("Bad log lines no longer crash the program, and are listed in a Parse issues window.");
+        ui.label("OOS PM and OOS UPM events are now recognised.");
+        ui.label("Timestamps can be day, month or year first, with 12 or 24 hour times, and in any time zone.");
+        ui.label("Gzip and zip compressed log files can now be opened directly, or dropped onto the window.");
+        ui.label("Several log files, such as a log and its .bak files, can be loaded together and merged into one timeline.");
+        ui.label("Added File / Follow, to watch events being added to a log file as it is written.");
+        ui.label("Added File / Connect, to read the log live from a controller over a serial port or TCP.");
+        ui.label("Every controller ID and firmware version in the log is now recorded, and shown in a Controller history window.");
+        ui.label("The GPS aux field is now decoded, and each GPS fix is flagged as good, missing, weak or a jump. Poor fixes can be hidden from the plots.");
+        ui.label("Events now keep their line as logged, with the file and line number, shown with Show / Raw log lines. Right click an event to copy its raw line.");
+        ui.label("CSV exports from the telematics portal can now be opened, with the columns set in settings.yml.");
+        ui.label("Log formats are now detected from the first lines of each file, and can be chosen from File / Format.");
+        ui.label("Other log lines, such as modem or CAN bus lines, can be scraped into events with custom rules in rules.yml.");
+        ui.label("Trips with the same number, such as after the controller is power cycled, are no longer mixed together in the plots.");
+        ui.label("Added File / Export, to save the shown events, or a summary of the trips, to a CSV file.");
+        ui.label("The parsed log can be exported to JSON or NDJSON, and opened again without the original log.");
+        ui.label("The GPS track of the selected trip, or all trips, can be exported to GPX, with events as waypoints.");
+        ui.label("Trips can also be exported to GeoJSON and KML, with event points coloured by speed.");
+        ui.label("Time series and GPS plots can be saved as PNG or SVG images from the Export image menu.");
+    });
+
     ui.collapsing("0.6.0 - update release", |ui| {
         ui.label("Added menu options to plot speed and batter voltage.");
         ui.label("Added delta time measurement to time-series plots.");
@@ -0,0 +1,73 @@
+// Place for controller history content.
+// Refer to ui.rs for associated ui definitions.
+
+use eframe::egui;
+use egui::RichText;
+
+use scraper::{format_timestamp, ControllerDetail};
+
+use crate::app::MyApp;
+use crate::colours;
+use crate::setting_up::SETTINGS;
+
+// Render every controller id and firmware version found in the log,
+// with the line number and time it was found.
+// Changes from the previous id or version are highlighted.
+pub fn draw_controller_history_content(ui: &mut egui::Ui, app: &mut MyApp) {
+    let history = &app.scraper.controller_history;
+
+    if history.is_empty() {
+        ui.label("No controller ids or firmware versions found.");
+        return;
+    }
+
+    let settings = SETTINGS.lock().unwrap().clone();
+    let mut last_id: Option<&str> = None;
+    let mut last_firmware: Option<&str> = None;
+
+    egui::ScrollArea::both()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("controller_history_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Line");
+                    ui.strong("Date/time");
+                    ui.strong("Controller");
+                    ui.strong("Firmware");
+                    ui.end_row();
+
+                    for change in history {
+                        ui.label(RichText::new(format!("{:>8}", change.line_num))
+                            .family(egui::FontFamily::Monospace));
+                        let date_time = match &change.timestamp {
+                            Some(timestamp) => format_timestamp(timestamp, settings.display_tz(), settings.date_order),
+                            None => change.date_time.clone(),
+                        };
+                        ui.label(RichText::new(date_time)
+                            .family(egui::FontFamily::Monospace));
+
+                        // The first of each is not a change.
+                        let (value, last) = match &change.detail {
+                            ControllerDetail::Id(id) => (id.as_str(), &mut last_id),
+                            ControllerDetail::Firmware(firmware) => (firmware.as_str(), &mut last_firmware),
+                        };
+                        let changed = last.is_some_and(|last| last != value);
+                        *last = Some(value);
+
+                        let mut text = RichText::new(value).family(egui::FontFamily::Monospace);
+                        if changed {
+                            text = text.color(colours::trip_colour(app.dark_mode)).strong();
+                        }
+                        if let ControllerDetail::Firmware(_) = &change.detail {
+                            ui.label("");
+                        }
+                        ui.label(text);
+                        if let ControllerDetail::Id(_) = &change.detail {
+                            ui.label("");
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
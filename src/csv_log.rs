@@ -0,0 +1,262 @@
+// CSV log input.
+// CSV exports from the telematics portal have one event per row,
+// with columns mapped to the same fields as an EVENT line.
+
+use serde::{Deserialize, Serialize};
+
+use crate::format::{parse_field, EventRecord, LineEvent, LineMetadata, LogFormat};
+use crate::parser::GpsLocation;
+use crate::timestamp::TimestampFormat;
+
+// Columns of a CSV log.
+// Each column is a header name (not case sensitive), or a 1 based column number
+// for files without a header. Optional columns are None if not in the file.
+// Time is None if the date column has the date and time.
+// Unix time is None to work it out from the date and time.
+// Latitude and longitude are in degrees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvMapping {
+    pub delimiter: char,
+    pub date: String,
+    pub time: Option<String>,
+    pub trip: String,
+    pub unix_time: Option<String>,
+    pub latitude: String,
+    pub longitude: String,
+    pub gps_aux: Option<String>,
+    pub rssi: Option<String>,
+    pub speed: Option<String>,
+    pub event_type: String,
+    pub detail: Option<String>,
+}
+
+// Columns as exported from the telematics portal.
+impl Default for CsvMapping {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            date: "Date".to_string(),
+            time: Some("Time".to_string()),
+            trip: "Trip".to_string(),
+            unix_time: Some("Unix Time".to_string()),
+            latitude: "Latitude".to_string(),
+            longitude: "Longitude".to_string(),
+            gps_aux: None,
+            rssi: Some("RSSI".to_string()),
+            speed: Some("Speed".to_string()),
+            event_type: "Event".to_string(),
+            detail: Some("Detail".to_string()),
+        }
+    }
+}
+
+// Column indexes, once the mapping has been matched to the file.
+#[derive(Debug, Clone)]
+struct CsvColumns {
+    date: usize,
+    time: Option<usize>,
+    trip: usize,
+    unix_time: Option<usize>,
+    latitude: usize,
+    longitude: usize,
+    gps_aux: Option<usize>,
+    rssi: Option<usize>,
+    speed: Option<usize>,
+    event_type: usize,
+    detail: Option<usize>,
+}
+
+// CSV log format.
+// The columns are matched on the first line, which is
+// taken to be a header if it has any of the column names,
+// or if there is no date in the date column.
+#[derive(Debug, Clone)]
+pub struct CsvFormat {
+    mapping: CsvMapping,
+    columns: Option<Result<CsvColumns, ()>>,
+}
+
+impl CsvFormat {
+    pub fn new(mapping: CsvMapping) -> Self {
+        Self {
+            mapping,
+            columns: None,
+        }
+    }
+}
+
+impl LogFormat for CsvFormat {
+    fn name(&self) -> &'static str {
+        "CSV"
+    }
+
+    // The first line must have the mapped columns.
+    fn detect(&self, lines: &[&str]) -> bool {
+        let Some(first) = lines.iter().find(|line| !line.trim().is_empty()) else {
+            return false;
+        };
+        let cells = split_csv_line(first, self.mapping.delimiter);
+        cells.len() > 1 && self.mapping.resolve(&cells).is_ok_and(|(columns, _)| columns.max_index() < cells.len())
+    }
+
+    fn parse_line(&mut self, line: &str, timestamp_format: &TimestampFormat) -> LineEvent {
+        let cells = split_csv_line(line, self.mapping.delimiter);
+        if cells.iter().all(|cell| cell.is_empty()) {
+            return LineEvent::None;
+        }
+
+        if self.columns.is_none() {
+            match self.mapping.resolve(&cells) {
+                Ok((columns, is_header)) => {
+                    self.columns = Some(Ok(columns));
+                    if is_header {
+                        return LineEvent::None;
+                    }
+                }
+                Err(reason) => {
+                    self.columns = Some(Err(()));
+                    return LineEvent::Invalid { reason, event_type: None };
+                }
+            }
+        }
+
+        // Columns not in the file were noted on the first line.
+        let Some(Ok(columns)) = &self.columns else {
+            return LineEvent::None;
+        };
+
+        match columns.record(&cells, timestamp_format) {
+            Ok(record) => LineEvent::Event(record),
+            Err(reason) => LineEvent::Invalid {
+                reason,
+                event_type: cells.get(columns.event_type).cloned(),
+            },
+        }
+    }
+
+    // CSV exports don't have controller details.
+    fn metadata(&self, _line: &str) -> Option<LineMetadata> {
+        None
+    }
+
+    // Columns are matched again for the next log.
+    fn boxed_clone(&self) -> Box<dyn LogFormat> {
+        Box::new(CsvFormat::new(self.mapping.clone()))
+    }
+}
+
+impl CsvMapping {
+    // Match the columns to the first line of the file.
+    // Also says if the first line is a header.
+    fn resolve(&self, first: &[String]) -> Result<(CsvColumns, bool), String> {
+        let mut named = false;
+        let mut find = |column: &str| -> Result<usize, String> {
+            if let Ok(number) = column.parse::<usize>()
+                && number > 0
+            {
+                return Ok(number - 1);
+            }
+            let index = first.iter()
+                .position(|cell| cell.eq_ignore_ascii_case(column))
+                .ok_or_else(|| format!("CSV column '{}' not found.", column))?;
+            named = true;
+            Ok(index)
+        };
+
+        let columns = CsvColumns {
+            date: find(&self.date)?,
+            time: self.time.as_deref().map(&mut find).transpose()?,
+            trip: find(&self.trip)?,
+            unix_time: self.unix_time.as_deref().map(&mut find).transpose()?,
+            latitude: find(&self.latitude)?,
+            longitude: find(&self.longitude)?,
+            gps_aux: self.gps_aux.as_deref().map(&mut find).transpose()?,
+            rssi: self.rssi.as_deref().map(&mut find).transpose()?,
+            speed: self.speed.as_deref().map(&mut find).transpose()?,
+            event_type: find(&self.event_type)?,
+            detail: self.detail.as_deref().map(&mut find).transpose()?,
+        };
+
+        let has_date = first.get(columns.date)
+            .is_some_and(|cell| cell.chars().any(|c| c.is_ascii_digit()));
+        Ok((columns, named || !has_date))
+    }
+}
+
+impl CsvColumns {
+    // Highest column index used.
+    fn max_index(&self) -> usize {
+        [self.date, self.trip, self.latitude, self.longitude, self.event_type].into_iter()
+            .chain([self.time, self.unix_time, self.gps_aux, self.rssi, self.speed, self.detail].into_iter().flatten())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Read the event fields from the cells of a line.
+    // Errors give the reason the line couldn't be read.
+    fn record(&self, cells: &[String], timestamp_format: &TimestampFormat) -> Result<EventRecord, String> {
+        let cell = |index: usize| cells.get(index).map(|cell| cell.as_str()).unwrap_or("");
+        let optional = |index: Option<usize>, name: &str| -> Result<u32, String> {
+            match index.map(cell) {
+                Some(value) if !value.is_empty() => parse_field::<u32>(value, name),
+                _ => Ok(0),
+            }
+        };
+
+        // The date column can have the date and time.
+        let (date, time) = match self.time {
+            Some(time) => (cell(self.date), cell(time)),
+            None => cell(self.date).split_once(' ').unwrap_or((cell(self.date), "")),
+        };
+
+        let unix_time = match self.unix_time {
+            Some(unix_time) => parse_field::<u64>(cell(unix_time), "unix time")?,
+            None => timestamp_format.parse_utc(date, time)
+                .map(|timestamp| timestamp.timestamp().max(0) as u64)
+                .ok_or_else(|| format!("Invalid date time '{} {}'.", date, time))?,
+        };
+
+        Ok(EventRecord {
+            date: date.to_string(),
+            time: time.to_string(),
+            trip_id: cell(self.trip).to_string(),
+            unix_time,
+            gps_locn: GpsLocation {
+                lat: parse_field::<f64>(cell(self.latitude), "latitude")?,
+                lon: parse_field::<f64>(cell(self.longitude), "longitude")?,
+            },
+            gps_aux: optional(self.gps_aux, "gps aux")?,
+            gps_rssi: optional(self.rssi, "gps rssi")?,
+            gps_speed: optional(self.speed, "gps speed")?,
+            event_type: cell(self.event_type).to_string(),
+            detail: self.detail.map(cell).unwrap_or("").to_string(),
+        })
+    }
+}
+
+// Split a CSV line into cells.
+// Cells can be quoted, with doubled quotes inside quotes.
+fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => {
+                cells.push(cell.trim().to_string());
+                cell.clear();
+            }
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
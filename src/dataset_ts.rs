@@ -1,23 +1,27 @@
 // Function to create dataset for time series plots.
 
-use crate::scraper::{Scraper, ScrapedData};
+use scraper::{EventKind, FieldKey, ScrapedData, Seconds};
+
+use crate::scraper::Scraper;
 use crate::time_series_plot::TimeSeriesData;
 use crate::time_series_plot::SinglePoint;
 use crate::helpers_ts;
 
 // Function to create the data sets to plot.
-// Flags to optionally plot (or not) machine speed or battery voltage.
+// Flags to optionally plot (or not) machine speed or battery voltage,
+// and to leave out speeds from poor GPS fixes.
 pub fn create_time_series_datasets(scraper: &Scraper,
         selected_trip: &str,
         plot_battery: bool,
-        plot_speed: bool) -> Vec<TimeSeriesData> {
+        plot_speed: bool,
+        hide_poor_gps: bool) -> Vec<TimeSeriesData> {
 
     // Create datasets of plots.
     let mut datasets = Vec::new();
 
     // Get all points for the selected trip.
     let trip_data: Vec<&ScrapedData> = scraper.scrapings.iter()
-        .filter(|scraped| scraped.trip_num == *selected_trip)
+        .filter(|scraped| scraped.trip_key == *selected_trip)
         .collect();
     
     // Abort if no data in the trip.
@@ -41,17 +45,11 @@ pub fn create_time_series_datasets(scraper: &Scraper,
     if plot_battery {
         let battery_points: Vec<SinglePoint> = trip_data.iter()
         .filter_map(|data| {
-            // Look for "Battery voltage" in the ev_detail vector.
-            // This is in all events, even the logic ones.
-            data.ev_detail.iter()
-                .find(|(tag, _)| tag == "Battery voltage")
-                .and_then(|(_, value)| {
-                    // Parse the f16 string value to f32.
-                    value.parse::<f32>().ok()
-                })
+            // Battery voltage is in all events, even the logic ones.
+            data.event.battery()
                 .map(|voltage| SinglePoint {
                     unix_time: data.unix_time,
-                    point_value: voltage,
+                    point_value: voltage.0,
                 })
         })
         .collect();
@@ -74,6 +72,8 @@ pub fn create_time_series_datasets(scraper: &Scraper,
     // Optionally plot speed accoroding to menu.
     if plot_speed {
         let speed_points: Vec<SinglePoint> = trip_data.iter()
+            .filter(|data| !matches!(data.event.kind, EventKind::Custom(_)))
+            .filter(|data| !hide_poor_gps || data.gps_fix.is_good())
             .map(|data| SinglePoint {
                 unix_time: data.unix_time,
                 point_value: data.gps_speed as f32,
@@ -95,7 +95,7 @@ pub fn create_time_series_datasets(scraper: &Scraper,
 
     // The impulse is an instantaneous event marker.
     let xsidlestart_points: Vec<SinglePoint> = trip_data.iter()
-        .filter(|data| data.event_type == "XSIDLESTART")
+        .filter(|data| data.event.kind == EventKind::XsIdleStart)
         .map(|data| SinglePoint {
             unix_time: data.unix_time,
             point_value: 1.0,
@@ -106,11 +106,8 @@ pub fn create_time_series_datasets(scraper: &Scraper,
     let mut xsidle_pulse_points: Vec<SinglePoint> = Vec::new();
     
     // Process XSIDLE events (similar to UNBUCKLED pulse creation)
-    for data in trip_data.iter().filter(|d| d.event_type == "XSIDLE") {
-        if let Some(duration) = data.ev_detail.iter()
-            .find(|(tag, _)| tag == "Max idle")
-            .and_then(|(_, value)| value.parse::<u64>().ok())
-        {
+    for data in trip_data.iter().filter(|d| d.event.kind == EventKind::XsIdle) {
+        if let Some(Seconds(duration)) = data.event.seconds(FieldKey::MaxIdle) {
             let event_end_time = data.unix_time;
             let event_start_time = if event_end_time >= duration {
                 event_end_time - duration
@@ -166,7 +163,7 @@ pub fn create_time_series_datasets(scraper: &Scraper,
     // Process each unique event type once to create combined datasets.
     // That is a combined dataset for each type of event.
     let unique_event_types: std::collections::BTreeSet<String> = trip_data.iter()
-        .map(|data| data.event_type.clone())
+        .map(|data| data.event_type().to_string())
         .collect();
 
     for event_type in unique_event_types {
@@ -175,18 +172,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -211,18 +203,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -247,18 +234,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -283,18 +265,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -319,18 +296,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -355,14 +327,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event severity in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Severity")
-                            .and_then(|(_, value)| {
+                        // Look for the event severity.
+                        data.event.text(FieldKey::Severity)
+                            .map(|value| {
                                 // Translate severity strings to numeric levels.
-                                let numeric_level = match value.as_str() {
+                                match value {
                                     "1" => 1.0,
                                     "2" => 2.0,
                                     "3" => 3.0,
@@ -375,8 +346,7 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                                         // Try to parse as number (fallback).
                                         value.parse::<f32>().unwrap_or(1.0)
                                     }
-                                };
-                                Some(numeric_level)
+                                }
                             })
                             .map(|event_point| SinglePoint {
                                 unix_time: data.unix_time,
@@ -405,17 +375,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event zone output in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Zone output")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                // Note that we want the no zone 0 value to
-                                // be above the baseline so add 1 to the zone output value.
-                                value.parse::<f32>().ok()
-                            })
+                        // Look for the event zone output.
+                        // Note that we want the no zone 0 value to
+                        // be above the baseline so add 1 to the zone output value.
+                        data.event.count(FieldKey::ZoneOutput)
+                            .map(|zone_output| zone_output as f32)
                             .map(|event_point| SinglePoint {
                                 unix_time: data.unix_time,
                                 point_value: event_point + 1.0,
@@ -445,17 +411,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event zone output in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Zone output")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                // Note that we want the no zone 0 value to
-                                // be above the baseline so add 1 to the zone output value.
-                                value.parse::<f32>().ok()
-                            })
+                        // Look for the event zone output.
+                        // Note that we want the no zone 0 value to
+                        // be above the baseline so add 1 to the zone output value.
+                        data.event.count(FieldKey::ZoneOutput)
+                            .map(|zone_output| zone_output as f32)
                             .map(|event_point| SinglePoint {
                                 unix_time: data.unix_time,
                                 point_value: event_point + 1.0,
@@ -485,18 +447,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -521,18 +478,13 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 // Get all points for this event type in the selected trip.
                 let ev_points: Vec<SinglePoint> = trip_data.iter()
                     // Filter by event type.
-                    .filter(|data| data.event_type == event_type)
+                    .filter(|data| data.event_type() == event_type)
                     .filter_map(|data| {
-                        // Look for event duration in the ev_detail vector.
-                        data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| {
-                                // Parse the integer string value to f32.
-                                value.parse::<f32>().ok()
-                            })
-                            .map(|event_point| SinglePoint {
+                        // Plot the event duration.
+                        data.event.duration()
+                            .map(|duration| SinglePoint {
                                 unix_time: data.unix_time,
-                                point_value: event_point,
+                                point_value: duration.0 as f32,
                             })
                     })
                     .collect();
@@ -564,42 +516,36 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 });
                 
                 // Process Driver unbuckled events (pulses at level 2.0).
-                for data in trip_data.iter().filter(|d| d.event_type == event_type) {
-                    let is_driver = data.ev_detail.iter()
-                        .find(|(tag, _)| tag == "Seat owner")
-                        .map(|(_, value)| value == "D")
-                        .unwrap_or(false);
+                for data in trip_data.iter().filter(|d| d.event_type() == event_type) {
+                    let is_driver = data.event.text(FieldKey::SeatOwner) == Some("D");
                     
-                    if is_driver {
-                        if let Some(duration) = data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| value.parse::<u64>().ok())
-                        {
-                            let event_end_time = data.unix_time;
-                            let event_start_time = if event_end_time >= duration {
-                                event_end_time - duration
-                            } else {
-                                trip_start_time
-                            };
-                            
-                            // Create pulse at level 2.0.
-                            driver_points.push(SinglePoint {
-                                unix_time: event_start_time,
-                                point_value: 0.0,
-                            });
-                            driver_points.push(SinglePoint {
-                                unix_time: event_start_time,
-                                point_value: 1.0,
-                            });
-                            driver_points.push(SinglePoint {
-                                unix_time: event_end_time,
-                                point_value: 1.0,
-                            });
-                            driver_points.push(SinglePoint {
-                                unix_time: event_end_time,
-                                point_value: 0.0,
-                            });
-                        }
+                    if is_driver
+                        && let Some(Seconds(duration)) = data.event.duration()
+                    {
+                        let event_end_time = data.unix_time;
+                        let event_start_time = if event_end_time >= duration {
+                            event_end_time - duration
+                        } else {
+                            trip_start_time
+                        };
+                        
+                        // Create pulse at level 2.0.
+                        driver_points.push(SinglePoint {
+                            unix_time: event_start_time,
+                            point_value: 0.0,
+                        });
+                        driver_points.push(SinglePoint {
+                            unix_time: event_start_time,
+                            point_value: 1.0,
+                        });
+                        driver_points.push(SinglePoint {
+                            unix_time: event_end_time,
+                            point_value: 1.0,
+                        });
+                        driver_points.push(SinglePoint {
+                            unix_time: event_end_time,
+                            point_value: 0.0,
+                        });
                     }
                 }
                 
@@ -616,42 +562,36 @@ pub fn create_time_series_datasets(scraper: &Scraper,
                 });
                 
                 // Process Passenger unbuckled events (pulses at level 1.0).
-                for data in trip_data.iter().filter(|d| d.event_type == event_type) {
-                    let is_passenger = data.ev_detail.iter()
-                        .find(|(tag, _)| tag == "Seat owner")
-                        .map(|(_, value)| value == "P")
-                        .unwrap_or(false);
+                for data in trip_data.iter().filter(|d| d.event_type() == event_type) {
+                    let is_passenger = data.event.text(FieldKey::SeatOwner) == Some("P");
                     
-                    if is_passenger {
-                        if let Some(duration) = data.ev_detail.iter()
-                            .find(|(tag, _)| tag == "Duration")
-                            .and_then(|(_, value)| value.parse::<u64>().ok())
-                        {
-                            let event_end_time = data.unix_time;
-                            let event_start_time = if event_end_time >= duration {
-                                event_end_time - duration
-                            } else {
-                                trip_start_time
-                            };
-                            
-                            // Create pulse at level 1.0.
-                            passenger_points.push(SinglePoint {
-                                unix_time: event_start_time,
-                                point_value: 0.0,
-                            });
-                            passenger_points.push(SinglePoint {
-                                unix_time: event_start_time,
-                                point_value: 0.5,
-                            });
-                            passenger_points.push(SinglePoint {
-                                unix_time: event_end_time,
-                                point_value: 0.5,
-                            });
-                            passenger_points.push(SinglePoint {
-                                unix_time: event_end_time,
-                                point_value: 0.0,
-                            });
-                        }
+                    if is_passenger
+                        && let Some(Seconds(duration)) = data.event.duration()
+                    {
+                        let event_end_time = data.unix_time;
+                        let event_start_time = if event_end_time >= duration {
+                            event_end_time - duration
+                        } else {
+                            trip_start_time
+                        };
+                        
+                        // Create pulse at level 1.0.
+                        passenger_points.push(SinglePoint {
+                            unix_time: event_start_time,
+                            point_value: 0.0,
+                        });
+                        passenger_points.push(SinglePoint {
+                            unix_time: event_start_time,
+                            point_value: 0.5,
+                        });
+                        passenger_points.push(SinglePoint {
+                            unix_time: event_end_time,
+                            point_value: 0.5,
+                        });
+                        passenger_points.push(SinglePoint {
+                            unix_time: event_end_time,
+                            point_value: 0.0,
+                        });
                     }
                 }
 
@@ -672,87 +612,76 @@ pub fn create_time_series_datasets(scraper: &Scraper,
             "INPUT" => {
                 let mut input_traces: Vec<Vec<SinglePoint>> = vec![Vec::new(); 8];
                 // Track the polarity (state) for each input trace.
-                let mut input_polarities: Vec<Option<i32>> = vec![None; 8];
+                let mut input_polarities: Vec<Option<i64>> = vec![None; 8];
                 const NUM_TRACES: f32 = 8.0;
 
                 // Process all events and add pulses.
-                for data in trip_data.iter().filter(|d| d.event_type == event_type) {
-                    if let Some(input_num) = data.ev_detail.iter()
-                        .find(|(tag, _)| tag == "Input")
-                        .and_then(|(_, value)| value.parse::<usize>().ok())
+                for data in trip_data.iter().filter(|d| d.event_type() == event_type) {
+                    if let Some(input_num) = data.event.count(FieldKey::Input)
+                        .and_then(|input| usize::try_from(input).ok())
+                        && (1..=8).contains(&input_num)
                     {
-                        if input_num >= 1 && input_num <= 8 {
-                            let trace_index = input_num - 1; 
-                            
-                            // Get the state: 0 = active LOW, 1 = active HIGH
-                            let state_tag = data.ev_detail.iter()
-                                .find(|(tag, _)| tag == "State")
-                                .and_then(|(_, value)| value.parse::<i32>().ok())
-                                .unwrap_or(1);
-                            
-                            // Store the polarity for this trace.
-                            input_polarities[trace_index] = Some(state_tag);
-                            
-                            // Vertical Stacking Calculation.
-                            let i = trace_index as f32;
-                            let h_trace = 1.0 / NUM_TRACES; 
-                            // 10% bottom margin for separation between traces.
-                            let y_base = i * h_trace + h_trace * 0.10;
-                            // 80% signal height.
-                            let h_sig = h_trace * 0.80;
+                        let trace_index = input_num - 1; 
+                        
+                        // Get the state: 0 = active LOW, 1 = active HIGH
+                        let state_tag = data.event.count(FieldKey::State)
+                            .unwrap_or(1);
+                        
+                        // Store the polarity for this trace.
+                        input_polarities[trace_index] = Some(state_tag);
+                        
+                        // Vertical Stacking Calculation.
+                        let i = trace_index as f32;
+                        let h_trace = 1.0 / NUM_TRACES; 
+                        // 10% bottom margin for separation between traces.
+                        let y_base = i * h_trace + h_trace * 0.10;
+                        // 80% signal height.
+                        let h_sig = h_trace * 0.80;
 
-                            // Polarity Calculation:
-                            // state_tag == 0: Active LOW  -> pulse is LOW (y_base), baseline is HIGH (y_base + h_sig)
-                            // state_tag == 1: Active HIGH -> pulse is HIGH (y_base + h_sig), baseline is LOW (y_base)
-                            let (y_pulse, y_baseline) = if state_tag == 0 { 
-                                // State 0: Active LOW (Pulse is low, Baseline is high)
-                                (y_base, y_base + h_sig)
-                            } else {
-                                // State 1: Active HIGH (Pulse is high, Baseline is low)
-                                (y_base + h_sig, y_base)
-                            };
-                            
-                            if let Some(duration) = data.ev_detail.iter()
-                                .find(|(tag, _)| tag == "Duration")
-                                .and_then(|(_, value)| value.parse::<u64>().ok())
-                            {
-                                // Only process events with duration > 0
-                                if duration > 0 {
-                                    let event_end_time = data.unix_time;
-                                    let calculated_start_time = if event_end_time >= duration {
-                                        event_end_time - duration
-                                    } else {
-                                        0
-                                    };
-                                    
-                                    // If the event started before the trip, clamp it to trip start.
-                                    let event_start_time = if calculated_start_time < trip_start_time {
-                                        trip_start_time
-                                    } else {
-                                        calculated_start_time
-                                    };
-                                    
-                                    let current_trace = &mut input_traces[trace_index];
+                        // Polarity Calculation:
+                        // state_tag == 0: Active LOW  -> pulse is LOW (y_base), baseline is HIGH (y_base + h_sig)
+                        // state_tag == 1: Active HIGH -> pulse is HIGH (y_base + h_sig), baseline is LOW (y_base)
+                        let (y_pulse, y_baseline) = if state_tag == 0 { 
+                            // State 0: Active LOW (Pulse is low, Baseline is high)
+                            (y_base, y_base + h_sig)
+                        } else {
+                            // State 1: Active HIGH (Pulse is high, Baseline is low)
+                            (y_base + h_sig, y_base)
+                        };
+                        
+                        if let Some(Seconds(duration)) = data.event.duration() {
+                            // Only process events with duration > 0
+                            if duration > 0 {
+                                let event_end_time = data.unix_time;
+                                let calculated_start_time = event_end_time.saturating_sub(duration);
+                                
+                                // If the event started before the trip, clamp it to trip start.
+                                let event_start_time = if calculated_start_time < trip_start_time {
+                                    trip_start_time
+                                } else {
+                                    calculated_start_time
+                                };
+                                
+                                let current_trace = &mut input_traces[trace_index];
+                                
+                                // Only add baseline before pulse if event didn't start before trip.
+                                if calculated_start_time >= trip_start_time {
+                                    let last_point_is_baseline_at_same_time = current_trace.last()
+                                        .is_some_and(|last| last.unix_time == event_start_time && (last.point_value - y_baseline).abs() < f32::EPSILON);
                                     
-                                    // Only add baseline before pulse if event didn't start before trip.
-                                    if calculated_start_time >= trip_start_time {
-                                        let last_point_is_baseline_at_same_time = current_trace.last()
-                                            .map_or(false, |last| last.unix_time == event_start_time && (last.point_value - y_baseline).abs() < f32::EPSILON);
-                                        
-                                        // Baseline before pulse (conditional insertion).
-                                        if !last_point_is_baseline_at_same_time {
-                                            current_trace.push(SinglePoint {
-                                                unix_time: event_start_time,
-                                                point_value: y_baseline, 
-                                            });
-                                        }
+                                    // Baseline before pulse (conditional insertion).
+                                    if !last_point_is_baseline_at_same_time {
+                                        current_trace.push(SinglePoint {
+                                            unix_time: event_start_time,
+                                            point_value: y_baseline, 
+                                        });
                                     }
-
-                                    // The pulse rectangle.
-                                    current_trace.push(SinglePoint { unix_time: event_start_time, point_value: y_pulse });
-                                    current_trace.push(SinglePoint { unix_time: event_end_time, point_value: y_pulse });
-                                    current_trace.push(SinglePoint { unix_time: event_end_time, point_value: y_baseline });
                                 }
+
+                                // The pulse rectangle.
+                                current_trace.push(SinglePoint { unix_time: event_start_time, point_value: y_pulse });
+                                current_trace.push(SinglePoint { unix_time: event_end_time, point_value: y_pulse });
+                                current_trace.push(SinglePoint { unix_time: event_end_time, point_value: y_baseline });
                             }
                         }
                     }
@@ -816,5 +745,75 @@ pub fn create_time_series_datasets(scraper: &Scraper,
         // Set of all data series to plot.
         }
     }
+
+    // Events from custom scrape rules.
+    // Each numeric field is an analog series,
+    // rules without numeric fields are event markers.
+    let custom_names: std::collections::BTreeSet<&str> = trip_data.iter()
+        .filter_map(|data| match &data.event.kind {
+            EventKind::Custom(name) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    for name in custom_names {
+        let custom_data: Vec<&&ScrapedData> = trip_data.iter()
+            .filter(|data| data.event.kind == EventKind::Custom(name.to_string()))
+            .collect();
+
+        // Field labels in the order they're first seen.
+        let mut labels: Vec<&str> = Vec::new();
+        for data in &custom_data {
+            for field in &data.event.fields {
+                if field.value.as_f64().is_some() && !labels.contains(&field.label.as_str()) {
+                    labels.push(&field.label);
+                }
+            }
+        }
+
+        if labels.is_empty() {
+            let ev_points: Vec<SinglePoint> = custom_data.iter()
+                .map(|data| SinglePoint {
+                    unix_time: data.unix_time,
+                    point_value: 1.0,
+                })
+                .collect();
+
+            datasets.push(TimeSeriesData {
+                data_type: "Impulse".to_string(),
+                series_name: name.to_string(),
+                units: "Event".to_string(),
+                levels: vec!["Active".to_string()],
+                time_series_points: ev_points,
+                multi_traces: Vec::new(),
+                tall_chart: false,
+            });
+            continue;
+        }
+
+        for label in labels {
+            let field_points: Vec<SinglePoint> = custom_data.iter()
+                .filter_map(|data| {
+                    data.event.fields.iter()
+                        .find(|field| field.label == label)
+                        .and_then(|field| field.value.as_f64())
+                        .map(|value| SinglePoint {
+                            unix_time: data.unix_time,
+                            point_value: value as f32,
+                        })
+                })
+                .collect();
+
+            datasets.push(TimeSeriesData {
+                data_type: "Analog".to_string(),
+                series_name: format!("{} {}", name, label),
+                units: label.to_string(),
+                levels: Vec::new(),
+                time_series_points: field_points,
+                multi_traces: Vec::new(),
+                tall_chart: false,
+            });
+        }
+    }
     datasets
 }
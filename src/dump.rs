@@ -0,0 +1,95 @@
+// JSON dumps of a parsed log.
+// Dumps have everything scraped from the log, so they can be
+// read by other tools, or opened again without the log.
+// A JSON dump is the parsed log as one object. An NDJSON dump has
+// a record per line: the controller details first, then the
+// controller history, the events and the diagnostics.
+
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{ControllerChange, Diagnostic, ParsedLog, ScrapedData};
+
+// Record in an NDJSON dump, for writing.
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum RecordRef<'a> {
+    Controller { controller_id: &'a Option<String>, controller_fw: &'a Option<String> },
+    ControllerChange(&'a ControllerChange),
+    Event(&'a ScrapedData),
+    Diagnostic(&'a Diagnostic),
+}
+
+// Record in an NDJSON dump, for reading.
+#[derive(Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record {
+    Controller { controller_id: Option<String>, controller_fw: Option<String> },
+    ControllerChange(ControllerChange),
+    Event(ScrapedData),
+    Diagnostic(Diagnostic),
+}
+
+// Check if a file name is a dump, from its extension.
+// Dumps can be gzip compressed.
+pub fn is_dump_file(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let name = name.strip_suffix(".gz").unwrap_or(&name);
+    name.ends_with(".json") || name.ends_with(".ndjson")
+}
+
+// Write a parsed log as a JSON object.
+pub fn write_json<W: Write>(writer: &mut W, log: &ParsedLog) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, log)?;
+    writeln!(writer)
+}
+
+// Write a parsed log as NDJSON, one record per line.
+pub fn write_ndjson<W: Write>(writer: &mut W, log: &ParsedLog) -> io::Result<()> {
+    let controller = RecordRef::Controller {
+        controller_id: &log.controller_id,
+        controller_fw: &log.controller_fw,
+    };
+    let records = std::iter::once(controller)
+        .chain(log.controller_history.iter().map(RecordRef::ControllerChange))
+        .chain(log.scrapings.iter().map(RecordRef::Event))
+        .chain(log.diagnostics.iter().map(RecordRef::Diagnostic));
+    for record in records {
+        serde_json::to_writer(&mut *writer, &record)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+// Read a JSON or NDJSON dump.
+// NDJSON is recognised by its first line being a record.
+pub fn read_dump<R: BufRead>(mut reader: R) -> io::Result<ParsedLog> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    let first = contents.lines().find(|line| !line.trim().is_empty()).unwrap_or("");
+    if serde_json::from_str::<Record>(first).is_err() {
+        return Ok(serde_json::from_str(&contents)?);
+    }
+
+    let mut log = ParsedLog::default();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<Record>(line).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Invalid record on line {}: {}", index + 1, e))
+        })?;
+        match record {
+            Record::Controller { controller_id, controller_fw } => {
+                log.controller_id = controller_id;
+                log.controller_fw = controller_fw;
+            }
+            Record::ControllerChange(change) => log.controller_history.push(change),
+            Record::Event(event) => log.scrapings.push(event),
+            Record::Diagnostic(diagnostic) => log.diagnostics.push(diagnostic),
+        }
+    }
+    Ok(log)
+}
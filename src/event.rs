@@ -0,0 +1,322 @@
+// Typed event model.
+// Event values carry their units, and are looked up by key rather
+// than by display label. Display strings are derived from the values.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Time in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Seconds(pub u64);
+
+// Speed in kilometres per hour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Kph(pub u32);
+
+// Engine speed in revolutions per minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Rpm(pub u32);
+
+// Voltage in volts.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Volts(pub f32);
+
+// Units of numeric event fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Count,
+    Seconds,
+    Kph,
+    Rpm,
+    Volts,
+}
+
+// Keys for looking up event fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldKey {
+    TripId,
+    OperatorId,
+    CardId,
+    Result,
+    BitsRead,
+    Keyboard,
+    CardReader,
+    Battery,
+    FailedQuestions,
+    FailedQuestion,
+    ChecklistVersion,
+    ChecklistType,
+    Speed,
+    DebugError,
+    Duration,
+    MaxRpm,
+    EquipmentFault,
+    ForwardG,
+    ReverseG,
+    LeftG,
+    RightG,
+    MaxG1,
+    MaxG2,
+    Severity,
+    Input,
+    State,
+    Reason,
+    Direction,
+    Firmware,
+    SeatOwner,
+    MaxIdle,
+    ZonesLoaded,
+    MaxZones,
+    GpsFirmware,
+    FromZone,
+    ToZone,
+    ZoneOutput,
+    MaxSpeed,
+    Transition,
+    TimeFwd,
+    TimeRev,
+    TimeIdle,
+    TimeOnSeat,
+}
+
+// Event types.
+// Events only defined in the event schema are Other.
+// Events from custom scrape rules are Custom, with the rule name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    SignOn,
+    Checklist,
+    ClFail,
+    Config,
+    CriticalOutputSet,
+    Debug,
+    EngineOverspeed,
+    EngineTemp,
+    Hardware,
+    Impact,
+    Input,
+    LowCoolant,
+    OffSeat,
+    OilPressure,
+    OosPm,
+    OosUpm,
+    Overload,
+    Overspeed,
+    PowerDown,
+    Report,
+    Service,
+    SwStart,
+    Unbuckled,
+    XsIdle,
+    XsIdleStart,
+    ZoneOk,
+    ZoneChange,
+    ZoneOverspeed,
+    ZoneTransition,
+    Trip,
+    Other(String),
+    Custom(String),
+}
+
+// Value of an event field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Value {
+    Text(String),
+    Count(i64),
+    Seconds(Seconds),
+    Kph(Kph),
+    Rpm(Rpm),
+    Volts(Volts),
+    Number(f64),
+}
+
+// A single event field.
+// Decimals are the decimal places used to display volts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventField {
+    pub key: Option<FieldKey>,
+    pub label: String,
+    pub value: Value,
+    pub decimals: usize,
+}
+
+// An event with its typed fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub kind: EventKind,
+    pub fields: Vec<EventField>,
+}
+
+impl EventKind {
+    // Event type from its name in the log.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "SIGNON" => Self::SignOn,
+            "CHECKLIST" => Self::Checklist,
+            "CLFAIL" => Self::ClFail,
+            "CONFIG" => Self::Config,
+            "CRITICALOUTPUTSET" => Self::CriticalOutputSet,
+            "DEBUG" => Self::Debug,
+            "ENGINEOVERSPEED" => Self::EngineOverspeed,
+            "ENGINETEMP" => Self::EngineTemp,
+            "HARDWARE" => Self::Hardware,
+            "IMPACT" => Self::Impact,
+            "INPUT" => Self::Input,
+            "LOWCOOLANT" => Self::LowCoolant,
+            "OFFSEAT" => Self::OffSeat,
+            "OILPRESSURE" => Self::OilPressure,
+            "OOS PM" => Self::OosPm,
+            "OOS UPM" => Self::OosUpm,
+            "OVERLOAD" => Self::Overload,
+            "OVERSPEED" => Self::Overspeed,
+            "POWERDOWN" => Self::PowerDown,
+            "REPORT" => Self::Report,
+            "SERVICE" => Self::Service,
+            "SWSTART" => Self::SwStart,
+            "UNBUCKLED" => Self::Unbuckled,
+            "XSIDLE" => Self::XsIdle,
+            "XSIDLESTART" => Self::XsIdleStart,
+            "ZONE_OK" => Self::ZoneOk,
+            "ZONECHANGE" => Self::ZoneChange,
+            "ZONEOVERSPEED" => Self::ZoneOverspeed,
+            "ZONETRANSITION" => Self::ZoneTransition,
+            "TRIP" => Self::Trip,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+
+    // Event name as it appears in the log.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::SignOn => "SIGNON",
+            Self::Checklist => "CHECKLIST",
+            Self::ClFail => "CLFAIL",
+            Self::Config => "CONFIG",
+            Self::CriticalOutputSet => "CRITICALOUTPUTSET",
+            Self::Debug => "DEBUG",
+            Self::EngineOverspeed => "ENGINEOVERSPEED",
+            Self::EngineTemp => "ENGINETEMP",
+            Self::Hardware => "HARDWARE",
+            Self::Impact => "IMPACT",
+            Self::Input => "INPUT",
+            Self::LowCoolant => "LOWCOOLANT",
+            Self::OffSeat => "OFFSEAT",
+            Self::OilPressure => "OILPRESSURE",
+            Self::OosPm => "OOS PM",
+            Self::OosUpm => "OOS UPM",
+            Self::Overload => "OVERLOAD",
+            Self::Overspeed => "OVERSPEED",
+            Self::PowerDown => "POWERDOWN",
+            Self::Report => "REPORT",
+            Self::Service => "SERVICE",
+            Self::SwStart => "SWSTART",
+            Self::Unbuckled => "UNBUCKLED",
+            Self::XsIdle => "XSIDLE",
+            Self::XsIdleStart => "XSIDLESTART",
+            Self::ZoneOk => "ZONE_OK",
+            Self::ZoneChange => "ZONECHANGE",
+            Self::ZoneOverspeed => "ZONEOVERSPEED",
+            Self::ZoneTransition => "ZONETRANSITION",
+            Self::Trip => "TRIP",
+            Self::Other(name) | Self::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Value {
+    // Numeric value, regardless of units.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Text(_) => None,
+            Self::Count(n) => Some(*n as f64),
+            Self::Seconds(s) => Some(s.0 as f64),
+            Self::Kph(k) => Some(k.0 as f64),
+            Self::Rpm(r) => Some(r.0 as f64),
+            Self::Volts(v) => Some(v.0 as f64),
+            Self::Number(n) => Some(*n),
+        }
+    }
+}
+
+impl EventField {
+    // Value formatted for display.
+    pub fn display_value(&self) -> String {
+        match &self.value {
+            Value::Text(text) => text.clone(),
+            Value::Count(n) => n.to_string(),
+            Value::Seconds(s) => s.0.to_string(),
+            Value::Kph(k) => k.0.to_string(),
+            Value::Rpm(r) => r.0.to_string(),
+            Value::Volts(v) => format!("{:.*}", self.decimals, v.0),
+            Value::Number(n) => n.to_string(),
+        }
+    }
+}
+
+impl Event {
+    // Event type name as it appears in the log.
+    pub fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    // Get the value of a field.
+    pub fn value(&self, key: FieldKey) -> Option<&Value> {
+        self.fields.iter()
+            .find(|field| field.key == Some(key))
+            .map(|field| &field.value)
+    }
+
+    // Get a text field.
+    pub fn text(&self, key: FieldKey) -> Option<&str> {
+        match self.value(key)? {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    // Get a count field.
+    pub fn count(&self, key: FieldKey) -> Option<i64> {
+        match self.value(key)? {
+            Value::Count(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    // Get a time field.
+    pub fn seconds(&self, key: FieldKey) -> Option<Seconds> {
+        match self.value(key)? {
+            Value::Seconds(s) => Some(*s),
+            _ => None,
+        }
+    }
+
+    // Event duration, for events that have one.
+    pub fn duration(&self) -> Option<Seconds> {
+        self.seconds(FieldKey::Duration)
+    }
+
+    // Battery voltage at the time of the event.
+    pub fn battery(&self) -> Option<Volts> {
+        match self.value(FieldKey::Battery)? {
+            Value::Volts(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    // Label and value strings for display.
+    pub fn detail(&self) -> Vec<(String, String)> {
+        self.fields.iter()
+            .map(|field| (field.label.clone(), field.display_value()))
+            .collect()
+    }
+}
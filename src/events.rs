@@ -0,0 +1,111 @@
+// Event sub-data decoding.
+// Decoders are built from the event schema.
+
+use log::{info, warn};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::event::EventField;
+use crate::schema::{EventDef, EventSchema};
+
+lazy_static! {
+    // Registry built from the default event schema.
+    static ref DEFAULT_REGISTRY: Arc<EventRegistry> = Arc::new(EventRegistry::new(&EventSchema::default()));
+}
+
+// An event definition with its compiled sub-data pattern.
+#[derive(Debug)]
+struct EventDecoder {
+    def: EventDef,
+    pattern: Option<Regex>,
+}
+
+// Registry of sub-data decoders keyed by event type.
+// Patterns are compiled once when the registry is built, rather than for every event.
+#[derive(Debug)]
+pub struct EventRegistry {
+    decoders: HashMap<String, EventDecoder>,
+}
+
+impl EventRegistry {
+    // Compile the decoders for the event schema.
+    // Patterns that don't compile are left out, so those events report a failed decode.
+    pub fn new(schema: &EventSchema) -> Self {
+        let mut decoders = HashMap::new();
+        for def in &schema.events {
+            let pattern = match &def.pattern {
+                Some(pattern) => match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        warn!("Invalid {} sub-data pattern: {}", def.name, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            decoders.insert(def.name.clone(), EventDecoder { def: def.clone(), pattern });
+        }
+        Self { decoders }
+    }
+
+    // Names of all the defined event types.
+    pub fn event_names(&self) -> impl Iterator<Item = &str> {
+        self.decoders.keys().map(|name| name.as_str())
+    }
+
+    // Registry for the default event schema.
+    pub fn default_registry() -> Arc<Self> {
+        DEFAULT_REGISTRY.clone()
+    }
+
+    // Function to expand on the scraped data.
+    // Sub-data that fails to match, and fields that fail to decode,
+    // are noted in the issues.
+    pub(crate) fn ungroup_event_data(&self, event_type: &str, sub_data: &str, on_trip: &mut bool, ev_supported: &mut bool, issues: &mut Vec<String>) -> Vec<EventField> {
+        // Initialise result vector.
+        let mut result = Vec::new();
+
+        let Some(decoder) = self.decoders.get(event_type) else {
+            // Events not currently supported.
+            // Only appear if show out of trip or supported flag set.
+            // Event and attributes will not be formatted.
+            // Setting unsupported flag to false.
+            *ev_supported = false;
+            return result;
+        };
+
+        // Some events only occur out of trip.
+        if !decoder.def.on_trip {
+            *on_trip = false;
+        }
+
+        if decoder.def.pattern.is_none() {
+            info!("{} event found, no sub-data applicable.", event_type);
+            return result;
+        }
+
+        match decoder.pattern.as_ref().and_then(|pattern| pattern.captures(sub_data)) {
+            Some(captures) => {
+                for field in &decoder.def.fields {
+                    if let Some(raw) = captures.get(field.group) {
+                        match field.decode(raw.as_str()) {
+                            Some(field) => result.push(field),
+                            None => issues.push(format!("Invalid {} '{}'.", field.label, raw.as_str())),
+                        }
+                    }
+                }
+            }
+            None if decoder.pattern.is_none() => {
+                issues.push(format!("Invalid {} sub-data pattern.", event_type));
+            }
+            None => {
+                issues.push(format!("Failed to extract sub-data from {}.", event_type));
+            }
+        }
+
+        result
+    }
+}
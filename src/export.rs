@@ -0,0 +1,324 @@
+// Export of scraped events.
+// Events are exported as given, so callers choose which events
+// to export, such as the events shown in the application.
+
+use std::io::{self, Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{json, Map};
+
+use crate::event::{EventKind, FieldKey};
+use crate::gps::{speed_colour, GpsFix};
+use crate::parser::ScrapedData;
+
+// Colour of trip tracks on maps, as drawn in the map plot.
+const TRACK_COLOUR: [u8; 3] = [0, 120, 255];
+
+// Write events as a CSV table, one row per event.
+// Each event detail label has its own column, in the order first seen,
+// and is empty for events without it.
+pub fn write_events_csv<W: Write>(writer: &mut W, events: &[&ScrapedData]) -> io::Result<()> {
+    let mut labels: Vec<&str> = Vec::new();
+    for event in events {
+        for field in &event.event.fields {
+            if !labels.contains(&field.label.as_str()) {
+                labels.push(&field.label);
+            }
+        }
+    }
+
+    let mut header = vec!["Date", "Time", "Unix Time", "Trip", "Event"];
+    header.extend(labels.iter());
+    header.extend(["Latitude", "Longitude", "Speed", "RSSI", "GPS Aux", "GPS Fix", "Source", "Line"]);
+    write_csv_row(writer, header)?;
+
+    for event in events {
+        let (date, time) = split_date_time(&event.date_time);
+        let mut row = vec![
+            date.to_string(),
+            time.to_string(),
+            event.unix_time.to_string(),
+            event.trip_num.clone(),
+            event.event_type().to_string(),
+        ];
+        for label in &labels {
+            let value = event.event.fields.iter()
+                .find(|field| field.label == *label)
+                .map(|field| field.display_value())
+                .unwrap_or_default();
+            row.push(value);
+        }
+        row.extend([
+            event.gps_locn.lat.to_string(),
+            event.gps_locn.lon.to_string(),
+            event.gps_speed.to_string(),
+            event.gps_rssi.to_string(),
+            event.gps_aux.to_string(),
+            event.gps_fix.label().to_string(),
+            event.source.clone().unwrap_or_default(),
+            event.line_num.to_string(),
+        ]);
+        write_csv_row(writer, row.iter().map(|cell| cell.as_str()))?;
+    }
+    Ok(())
+}
+
+// Write a CSV table of trips, one row per SIGNON to TRIP span.
+// Trips without a TRIP event end at their last event.
+// Event counts are of the events given.
+pub fn write_trips_csv<W: Write>(writer: &mut W, events: &[&ScrapedData]) -> io::Result<()> {
+    write_csv_row(writer, ["Trip", "Start", "End", "Start Unix Time", "End Unix Time", "Duration (s)", "Events", "Max Speed", "Operator", "Firmware", "Source"])?;
+
+    for trip in trip_spans(events) {
+        let start = trip[0];
+        let end = trip[trip.len() - 1];
+        let max_speed = trip.iter().map(|event| event.gps_speed).max().unwrap_or(0);
+        let operator = trip.iter()
+            .find(|event| event.event.kind == EventKind::SignOn)
+            .and_then(|event| event.event.text(FieldKey::OperatorId))
+            .unwrap_or("");
+        write_csv_row(writer, [
+            start.trip_num.as_str(),
+            start.date_time.as_str(),
+            end.date_time.as_str(),
+            &start.unix_time.to_string(),
+            &end.unix_time.to_string(),
+            &end.unix_time.saturating_sub(start.unix_time).to_string(),
+            &trip.len().to_string(),
+            &max_speed.to_string(),
+            operator,
+            start.firmware.as_deref().unwrap_or(""),
+            start.source.as_deref().unwrap_or(""),
+        ])?;
+    }
+    Ok(())
+}
+
+// Write trips as GPX 1.1, with a track for each trip and
+// a waypoint, named after the event type, for each event.
+// Events without a GPS fix are left out, as are poor fixes if asked.
+// Speed is in the Garmin track point extension, in metres per second.
+pub fn write_gpx<W: Write>(writer: &mut W, events: &[&ScrapedData], hide_poor_fixes: bool) -> io::Result<()> {
+    let trips = gps_trips(events, hide_poor_fixes);
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<gpx version="1.1" creator="Scraper" xmlns="http://www.topografix.com/GPX/1/1" xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2">"#)?;
+
+    // Waypoints come before tracks in GPX.
+    for trip in &trips {
+        for event in trip {
+            writeln!(writer, r#"  <wpt lat="{}" lon="{}">"#, event.gps_locn.lat, event.gps_locn.lon)?;
+            writeln!(writer, "    <time>{}</time>", utc_time(event))?;
+            writeln!(writer, "    <name>{}</name>", xml_escape(event.event_type()))?;
+            writeln!(writer, "    <desc>Trip {}</desc>", xml_escape(&event.trip_num))?;
+            writeln!(writer, "  </wpt>")?;
+        }
+    }
+
+    for trip in &trips {
+        writeln!(writer, "  <trk>")?;
+        writeln!(writer, "    <name>Trip {}</name>", xml_escape(&trip[0].trip_num))?;
+        writeln!(writer, "    <trkseg>")?;
+        for event in trip {
+            writeln!(writer, r#"      <trkpt lat="{}" lon="{}">"#, event.gps_locn.lat, event.gps_locn.lon)?;
+            writeln!(writer, "        <time>{}</time>", utc_time(event))?;
+            writeln!(writer, "        <extensions><gpxtpx:TrackPointExtension><gpxtpx:speed>{:.2}</gpxtpx:speed></gpxtpx:TrackPointExtension></extensions>", event.gps_speed as f64 / 3.6)?;
+            writeln!(writer, "      </trkpt>")?;
+        }
+        writeln!(writer, "    </trkseg>")?;
+        writeln!(writer, "  </trk>")?;
+    }
+
+    writeln!(writer, "</gpx>")
+}
+
+// Write trips as a GeoJSON feature collection, with a LineString
+// for each trip and a Point for each event.
+// Event points have the event details as properties, and are coloured
+// by speed as in the map plot, with simplestyle marker-color.
+pub fn write_geojson<W: Write>(writer: &mut W, events: &[&ScrapedData], hide_poor_fixes: bool) -> io::Result<()> {
+    let mut features = Vec::new();
+    for trip in gps_trips(events, hide_poor_fixes) {
+        let coordinates: Vec<[f64; 2]> = trip.iter()
+            .map(|event| [event.gps_locn.lon, event.gps_locn.lat])
+            .collect();
+        features.push(json!({
+            "type": "Feature",
+            "geometry": { "type": "LineString", "coordinates": coordinates },
+            "properties": {
+                "trip": trip[0].trip_num,
+                "trip_key": trip[0].trip_key,
+                "start": utc_time(trip[0]),
+                "end": utc_time(trip[trip.len() - 1]),
+                "stroke": hex_colour(TRACK_COLOUR),
+            },
+        }));
+
+        for event in trip {
+            let mut properties = Map::new();
+            properties.insert("event_type".to_string(), json!(event.event_type()));
+            properties.insert("trip".to_string(), json!(event.trip_num));
+            properties.insert("time".to_string(), json!(utc_time(event)));
+            properties.insert("unix_time".to_string(), json!(event.unix_time));
+            properties.insert("speed".to_string(), json!(event.gps_speed));
+            properties.insert("rssi".to_string(), json!(event.gps_rssi));
+            properties.insert("gps_fix".to_string(), json!(event.gps_fix));
+            for field in &event.event.fields {
+                properties.insert(field.label.clone(), json!(field.display_value()));
+            }
+            properties.insert("marker-color".to_string(), json!(hex_colour(speed_colour(event.gps_speed, event.gps_fix))));
+            features.push(json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [event.gps_locn.lon, event.gps_locn.lat] },
+                "properties": properties,
+            }));
+        }
+    }
+
+    let collection = json!({ "type": "FeatureCollection", "features": features });
+    serde_json::to_writer_pretty(&mut *writer, &collection)?;
+    writeln!(writer)
+}
+
+// Write trips as KML, with a placemark for each trip's track
+// and for each event, in a folder for each trip.
+// Event placemarks have the event details as extended data, and are
+// coloured by speed as in the map plot.
+pub fn write_kml<W: Write>(writer: &mut W, events: &[&ScrapedData], hide_poor_fixes: bool) -> io::Result<()> {
+    let trips = gps_trips(events, hide_poor_fixes);
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(writer, "<Document>")?;
+    writeln!(writer, "  <name>Scraper</name>")?;
+
+    // A style for the track, and for each colour of event.
+    writeln!(writer, r#"  <Style id="track"><LineStyle><color>{}</color><width>3</width></LineStyle></Style>"#, kml_colour(TRACK_COLOUR))?;
+    let mut colours: Vec<[u8; 3]> = Vec::new();
+    for event in trips.iter().flatten() {
+        let colour = speed_colour(event.gps_speed, event.gps_fix);
+        if !colours.contains(&colour) {
+            writeln!(writer, r#"  <Style id="{}"><IconStyle><color>{}</color></IconStyle></Style>"#, kml_style_id(colour), kml_colour(colour))?;
+            colours.push(colour);
+        }
+    }
+
+    for trip in &trips {
+        writeln!(writer, "  <Folder>")?;
+        writeln!(writer, "    <name>Trip {}</name>", xml_escape(&trip[0].trip_num))?;
+        writeln!(writer, "    <Placemark>")?;
+        writeln!(writer, "      <name>Trip {}</name>", xml_escape(&trip[0].trip_num))?;
+        writeln!(writer, "      <styleUrl>#track</styleUrl>")?;
+        let coordinates: Vec<String> = trip.iter()
+            .map(|event| format!("{},{}", event.gps_locn.lon, event.gps_locn.lat))
+            .collect();
+        writeln!(writer, "      <LineString><coordinates>{}</coordinates></LineString>", coordinates.join(" "))?;
+        writeln!(writer, "    </Placemark>")?;
+
+        for event in trip {
+            writeln!(writer, "    <Placemark>")?;
+            writeln!(writer, "      <name>{}</name>", xml_escape(event.event_type()))?;
+            writeln!(writer, "      <TimeStamp><when>{}</when></TimeStamp>", utc_time(event))?;
+            writeln!(writer, "      <styleUrl>#{}</styleUrl>", kml_style_id(speed_colour(event.gps_speed, event.gps_fix)))?;
+            writeln!(writer, "      <ExtendedData>")?;
+            let data = [
+                ("Speed".to_string(), event.gps_speed.to_string()),
+                ("RSSI".to_string(), event.gps_rssi.to_string()),
+                ("GPS Fix".to_string(), event.gps_fix.label().to_string()),
+            ];
+            for (name, value) in event.event.detail().into_iter().chain(data) {
+                writeln!(writer, r#"        <Data name="{}"><value>{}</value></Data>"#, xml_escape(&name), xml_escape(&value))?;
+            }
+            writeln!(writer, "      </ExtendedData>")?;
+            writeln!(writer, "      <Point><coordinates>{},{}</coordinates></Point>", event.gps_locn.lon, event.gps_locn.lat)?;
+            writeln!(writer, "    </Placemark>")?;
+        }
+        writeln!(writer, "  </Folder>")?;
+    }
+
+    writeln!(writer, "</Document>")?;
+    writeln!(writer, "</kml>")
+}
+
+// Events with a GPS fix, grouped into trips.
+// Poor fixes are left out if asked, and so are trips with no fixes.
+fn gps_trips<'a>(events: &[&'a ScrapedData], hide_poor_fixes: bool) -> Vec<Vec<&'a ScrapedData>> {
+    trip_spans(events).into_iter()
+        .map(|trip| trip.into_iter()
+            .filter(|event| event.gps_fix != GpsFix::NoFix)
+            .filter(|event| !hide_poor_fixes || event.gps_fix.is_good())
+            .collect::<Vec<_>>())
+        .filter(|trip| !trip.is_empty())
+        .collect()
+}
+
+// Events grouped into trips by trip key.
+// Events out of trip are left out.
+pub(crate) fn trip_spans<'a>(events: &[&'a ScrapedData]) -> Vec<Vec<&'a ScrapedData>> {
+    let mut trips: Vec<Vec<&ScrapedData>> = Vec::new();
+    for event in events {
+        if event.trip_key.is_empty() {
+            continue;
+        }
+        match trips.last_mut() {
+            Some(trip) if trip[0].trip_key == event.trip_key => trip.push(event),
+            _ => trips.push(vec![event]),
+        }
+    }
+    trips
+}
+
+// Date and time from a date time as logged.
+// The time can be 12 hour, with AM or PM after a space.
+fn split_date_time(date_time: &str) -> (&str, &str) {
+    date_time.split_once(' ').unwrap_or((date_time, ""))
+}
+
+// Time of an event in UTC, for map formats.
+// Uses the controller unix time if the logged date time couldn't be read.
+fn utc_time(event: &ScrapedData) -> String {
+    event.timestamp
+        .or_else(|| DateTime::<Utc>::from_timestamp(event.unix_time as i64, 0))
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Colour as #rrggbb.
+fn hex_colour([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Colour as a KML aabbggrr colour, fully opaque.
+fn kml_colour([r, g, b]: [u8; 3]) -> String {
+    format!("ff{:02x}{:02x}{:02x}", b, g, r)
+}
+
+// KML style id for a point colour.
+fn kml_style_id([r, g, b]: [u8; 3]) -> String {
+    format!("point-{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Escape text for XML, in element text or either kind of quoted attribute.
+// Also used for the SVG plot images.
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Write a row of CSV cells.
+// Cells with commas, quotes or line breaks are quoted.
+fn write_csv_row<'a, W: Write>(writer: &mut W, cells: impl IntoIterator<Item = &'a str>) -> io::Result<()> {
+    let row: Vec<String> = cells.into_iter()
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect();
+    writeln!(writer, "{}", row.join(","))
+}
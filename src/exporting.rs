@@ -0,0 +1,245 @@
+// Exporting scraped data and plots to files.
+// Refer to ui.rs for the File / Export menu,
+// and the Export image menus of the plot windows.
+
+use log::{info, warn};
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+
+use scraper::{write_events_csv, write_geojson, write_gpx, write_json, write_kml, write_ndjson, write_trips_csv, ScrapedData};
+
+use crate::app::MyApp;
+use crate::log_display::should_show_event;
+use crate::plot_image::ImageFormat;
+use crate::{gps_plot, time_series_plot};
+
+// Use conditional includes for linux and Windows,
+// as for the open file dialog.
+
+#[cfg(target_os = "windows")]
+use rfd::FileDialog;
+#[cfg(target_os = "linux")]
+use tinyfiledialogs::save_file_dialog_with_filter;
+
+// Trips in a map export.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TripScope {
+    Selected,
+    All,
+}
+
+// Plots that can be saved as images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlotImage {
+    TimeSeries,
+    Gps,
+}
+
+// Map export for a trip scope.
+type MapExport = fn(TripScope) -> ExportKind;
+
+// Kinds of export.
+// Map formats are for the selected trip or all trips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportKind {
+    EventsCsv,
+    TripsCsv,
+    Json,
+    Ndjson,
+    Gpx(TripScope),
+    GeoJson(TripScope),
+    Kml(TripScope),
+}
+
+impl ExportKind {
+    // Table and log exports, in menu order.
+    pub const TABLES: [ExportKind; 4] = [ExportKind::EventsCsv, ExportKind::TripsCsv, ExportKind::Json, ExportKind::Ndjson];
+
+    // Map formats, in menu order, with their names.
+    pub const MAPS: [(&'static str, MapExport); 3] = [
+        ("GPX", ExportKind::Gpx),
+        ("GeoJSON", ExportKind::GeoJson),
+        ("KML", ExportKind::Kml),
+    ];
+
+    // Name for the menu.
+    // Map formats are named in their own menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportKind::EventsCsv => "Events to CSV...",
+            ExportKind::TripsCsv => "Trips to CSV...",
+            ExportKind::Json => "Log to JSON...",
+            ExportKind::Ndjson => "Log to NDJSON...",
+            ExportKind::Gpx(scope) | ExportKind::GeoJson(scope) | ExportKind::Kml(scope) => match scope {
+                TripScope::Selected => "Selected trip...",
+                TripScope::All => "All trips...",
+            },
+        }
+    }
+
+    // Check if the export can be made.
+    // Selected trip exports need a trip to be selected.
+    pub fn is_enabled(&self, app: &MyApp) -> bool {
+        match self {
+            ExportKind::Gpx(TripScope::Selected)
+            | ExportKind::GeoJson(TripScope::Selected)
+            | ExportKind::Kml(TripScope::Selected) => selected_trip(app).is_some(),
+            _ => true,
+        }
+    }
+
+    // Default file name, and its extension.
+    fn file_name(&self) -> (&'static str, &'static str) {
+        match self {
+            ExportKind::EventsCsv => ("events.csv", "csv"),
+            ExportKind::TripsCsv => ("trips.csv", "csv"),
+            ExportKind::Json => ("scraper.json", "json"),
+            ExportKind::Ndjson => ("scraper.ndjson", "ndjson"),
+            ExportKind::Gpx(TripScope::Selected) => ("trip.gpx", "gpx"),
+            ExportKind::Gpx(TripScope::All) => ("trips.gpx", "gpx"),
+            ExportKind::GeoJson(TripScope::Selected) => ("trip.geojson", "geojson"),
+            ExportKind::GeoJson(TripScope::All) => ("trips.geojson", "geojson"),
+            ExportKind::Kml(TripScope::Selected) => ("trip.kml", "kml"),
+            ExportKind::Kml(TripScope::All) => ("trips.kml", "kml"),
+        }
+    }
+
+    // Write the export to a file.
+    // CSV exports have the events shown with the current Show menu settings,
+    // JSON dumps have everything, so they can be opened again.
+    // Map exports match the GPS plot, so hide poor fixes if the plot does.
+    fn write(&self, writer: &mut impl Write, app: &MyApp) -> io::Result<()> {
+        match self {
+            ExportKind::EventsCsv => write_events_csv(writer, &shown_events(app)),
+            ExportKind::TripsCsv => write_trips_csv(writer, &shown_events(app)),
+            ExportKind::Json => write_json(writer, &app.scraper.parsed_log()),
+            ExportKind::Ndjson => write_ndjson(writer, &app.scraper.parsed_log()),
+            ExportKind::Gpx(scope) => write_gpx(writer, &trip_events(app, *scope), app.hide_poor_gps),
+            ExportKind::GeoJson(scope) => write_geojson(writer, &trip_events(app, *scope), app.hide_poor_gps),
+            ExportKind::Kml(scope) => write_kml(writer, &trip_events(app, *scope), app.hide_poor_gps),
+        }
+    }
+}
+
+// Export to a file.
+// The result is shown in the status bar.
+pub fn export(app: &mut MyApp, kind: ExportKind) {
+    let (file_name, extension) = kind.file_name();
+    let Some(path) = save_file_path(file_name, extension) else {
+        info!("No export file was selected.");
+        return;
+    };
+
+    let result = File::create(&path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        kind.write(&mut writer, app)?;
+        writer.flush()
+    });
+    report_export(app, &path, result);
+}
+
+// Save a plot of the selected trip as an image.
+// The plot is drawn at the size of the plot window,
+// and PNG images at the chosen scale.
+pub fn export_plot_image(app: &mut MyApp, plot: PlotImage, format: ImageFormat, size: egui::Vec2) {
+    let Some(trip_key) = selected_trip(app).map(str::to_string) else {
+        return;
+    };
+    let file_name = match plot {
+        PlotImage::TimeSeries => format!("time_series.{}", format.extension()),
+        PlotImage::Gps => format!("gps_plot.{}", format.extension()),
+    };
+    let Some(path) = save_file_path(&file_name, format.extension()) else {
+        info!("No image file was selected.");
+        return;
+    };
+
+    let result = match plot {
+        PlotImage::TimeSeries => time_series_plot::save_plot_image(
+            &path,
+            format,
+            app.image_scale,
+            size.x,
+            &app.scraper,
+            &trip_key,
+            &app.plot_state,
+            app.dark_mode,
+            app.plot_battery,
+            app.plot_speed,
+            app.hide_poor_gps,
+        ),
+        PlotImage::Gps => gps_plot::save_plot_image(
+            &path,
+            format,
+            app.image_scale,
+            size,
+            &app.scraper,
+            &trip_key,
+            app.dark_mode,
+            app.hide_poor_gps,
+        ),
+    };
+    report_export(app, &path, result);
+}
+
+// Show the result of an export in the status bar.
+fn report_export(app: &mut MyApp, path: &Path, result: io::Result<()>) {
+    app.scraper.processing_status = match result {
+        Ok(()) => {
+            info!("Exported to {:?}.", path);
+            format!("Exported to {}.", path.display())
+        }
+        Err(e) => {
+            warn!("Failed to export to {:?}: {}", path, e);
+            format!("Failed to export to {}: {}", path.display(), e)
+        }
+    };
+}
+
+// Events shown with the current Show menu settings.
+fn shown_events(app: &MyApp) -> Vec<&ScrapedData> {
+    app.scraper.scrapings.iter()
+        .filter(|item| should_show_event(item,
+            app.show_oot_events,
+            app.show_unsupported_events,
+            app.show_input_events,
+            app.show_report_events,
+            app.show_debug_events))
+        .collect()
+}
+
+// Events in the selected trip, or all events.
+fn trip_events(app: &MyApp, scope: TripScope) -> Vec<&ScrapedData> {
+    let trip_key = selected_trip(app);
+    app.scraper.scrapings.iter()
+        .filter(|scraped| scope == TripScope::All || Some(scraped.trip_key.as_str()) == trip_key)
+        .collect()
+}
+
+// Key of the selected trip, if any.
+pub fn selected_trip(app: &MyApp) -> Option<&str> {
+    app.selected_id.as_deref().filter(|id| !id.is_empty())
+}
+
+// Ask for the file to export to.
+fn save_file_path(file_name: &str, extension: &str) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        // For windows use FileDialog.
+        FileDialog::new()
+            .add_filter(extension, &[extension])
+            .set_file_name(file_name)
+            .save_file()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // Use tinyfiledialogs synchronous dialog.
+        let pattern = format!("*.{}", extension);
+        save_file_dialog_with_filter("Export", file_name, &[pattern.as_str()], extension)
+            .map(PathBuf::from)
+    }
+}
@@ -0,0 +1,127 @@
+// Following a log that is still being written.
+// New lines are parsed as they are appended, carrying on from
+// where the last read stopped. If the log is truncated or rolled
+// over to a new file it is read again from the start.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::parser::{LogParser, ParsedLog};
+
+// What was scraped from a log since the last poll.
+// Restarted is set if the log was truncated or replaced,
+// in which case everything previously scraped should be dropped.
+#[derive(Debug, Clone, Default)]
+pub struct FollowUpdate {
+    pub restarted: bool,
+    pub log: ParsedLog,
+}
+
+// Log file follower.
+// Only complete lines are parsed, so a line that is
+// half written when polled is finished on a later poll.
+#[derive(Debug)]
+pub struct LogFollower {
+    path: PathBuf,
+    template: LogParser,
+    parser: LogParser,
+    reader: Option<BufReader<File>>,
+    file_id: Option<FileId>,
+    position: u64,
+    partial: Vec<u8>,
+}
+
+impl LogFollower {
+    // Follow the log at the given path.
+    // The parser is used as is for the first read,
+    // and a copy of it each time the log restarts.
+    pub fn new(path: &Path, parser: LogParser) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            template: parser.clone(),
+            parser,
+            reader: None,
+            file_id: None,
+            position: 0,
+            partial: Vec::new(),
+        }
+    }
+
+    // Read and parse any lines appended since the last poll.
+    // The first poll reads the whole log.
+    // Returns None if nothing has changed.
+    pub fn poll(&mut self) -> io::Result<Option<FollowUpdate>> {
+        let mut restarted = false;
+        let metadata = fs::metadata(&self.path)?;
+        let file_id = FileId::from_metadata(&metadata);
+
+        // Start again if the log was truncated or replaced.
+        if self.reader.is_none() || metadata.len() < self.position || file_id != self.file_id {
+            restarted = true;
+            self.restart(file_id)?;
+        }
+
+        let Some(reader) = &mut self.reader else {
+            return Ok(None);
+        };
+
+        let mut parsed_lines = false;
+        loop {
+            let num_bytes = reader.read_until(b'\n', &mut self.partial)?;
+            if num_bytes == 0 {
+                break;
+            }
+            self.position += num_bytes as u64;
+
+            // Wait for the rest of a half written line.
+            if !self.partial.ends_with(b"\n") {
+                break;
+            }
+            self.parser.parse_line_bytes(&self.partial);
+            self.partial.clear();
+            parsed_lines = true;
+        }
+
+        // Everything written so far has been read, so don't
+        // hold lines back waiting for more to detect the format.
+        self.parser.settle_format();
+
+        if !restarted && !parsed_lines {
+            return Ok(None);
+        }
+        Ok(Some(FollowUpdate {
+            restarted,
+            log: self.parser.take_parsed(),
+        }))
+    }
+
+    // Open the log and read it from the start with a fresh parser.
+    fn restart(&mut self, file_id: Option<FileId>) -> io::Result<()> {
+        self.reader = Some(BufReader::new(File::open(&self.path)?));
+        self.file_id = file_id;
+        self.position = 0;
+        self.partial.clear();
+        self.parser = self.template.clone();
+        Ok(())
+    }
+}
+
+// Identity of a file, used to spot a log being rolled over
+// to a new file with the same name.
+// Only available on unix, elsewhere only truncation is spotted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId(u64, u64);
+
+impl FileId {
+    #[cfg(unix)]
+    fn from_metadata(metadata: &fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+        Some(FileId(metadata.dev(), metadata.ino()))
+    }
+
+    #[cfg(not(unix))]
+    fn from_metadata(_metadata: &fs::Metadata) -> Option<Self> {
+        None
+    }
+}
@@ -0,0 +1,240 @@
+// Log formats.
+// Controller generations, and the telematics portal, log events
+// in different syntaxes. Each syntax is a log format that reads
+// lines into the same event fields.
+
+use log::info;
+
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::events::EventRegistry;
+use crate::parser::{ControllerDetail, GpsLocation};
+use crate::timestamp::TimestampFormat;
+
+// Number of lines read from the start of a log to detect its format.
+pub const DETECT_LINES: usize = 20;
+
+// Fields of an event, however it was logged.
+// The GPS location is in degrees.
+#[derive(Debug, Clone)]
+pub struct EventRecord {
+    pub date: String,
+    pub time: String,
+    pub trip_id: String,
+    pub unix_time: u64,
+    pub gps_locn: GpsLocation,
+    pub gps_aux: u32,
+    pub gps_rssi: u32,
+    pub gps_speed: u32,
+    pub event_type: String,
+    pub detail: String,
+}
+
+// Event found on a line of a log.
+// Invalid lines give the reason they couldn't be read,
+// and the event type if it was found.
+#[derive(Debug, Clone)]
+pub enum LineEvent {
+    None,
+    Event(EventRecord),
+    Invalid { reason: String, event_type: Option<String> },
+}
+
+// Controller id or firmware version found on a line of a log,
+// with the date and time it was logged.
+#[derive(Debug, Clone)]
+pub struct LineMetadata {
+    pub date: String,
+    pub time: String,
+    pub detail: ControllerDetail,
+}
+
+// A log syntax.
+// Detect is given lines from the start of a log, and says if they are in the format.
+// Metadata is read from a line before its event, so that
+// an event that changes the firmware is tagged with the new version.
+pub trait LogFormat: fmt::Debug + Send {
+    // Name for display.
+    fn name(&self) -> &'static str;
+
+    // Check if lines from the start of a log are in this format.
+    fn detect(&self, lines: &[&str]) -> bool;
+
+    // Read the event on a line.
+    fn parse_line(&mut self, line: &str, timestamp_format: &TimestampFormat) -> LineEvent;
+
+    // Read the controller id or firmware version on a line.
+    fn metadata(&self, line: &str) -> Option<LineMetadata>;
+
+    // Copy of the format, ready to read a log from the start.
+    fn boxed_clone(&self) -> Box<dyn LogFormat>;
+}
+
+impl Clone for Box<dyn LogFormat> {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+// Pick the first of the formats that the lines are in.
+pub fn detect_format(formats: &[Box<dyn LogFormat>], lines: &[&str]) -> Option<Box<dyn LogFormat>> {
+    let format = formats.iter().find(|format| format.detect(lines))?;
+    info!("Detected {} log format.", format.name());
+    Some(format.boxed_clone())
+}
+
+// Date and time at the start of a line.
+// Dates may be in any order, times may be 12 hour and have fractional seconds.
+const DATE_PATTERN: &str = r"([0-9]{1,4}[/.-][0-9]{1,2}[/.-][0-9]{1,4})";
+const TIME_PATTERN: &str = r"([0-9]{1,2}:[0-9]{2}:[0-9]{2}(?:\.[0-9]+)?(?: ?[AaPp][Mm])?)";
+
+// Controller log format.
+// Space delimited EVENT lines with the GPS location in 1e-7 degrees,
+// UNIT lines with the controller id, and SWSTART events with the firmware version.
+#[derive(Debug, Clone)]
+pub struct ControllerFormat {
+    sn_pattern: Regex,
+    fw_pattern: Regex,
+    ev_pattern: Regex,
+    ev_like_pattern: Regex,
+}
+
+impl ControllerFormat {
+    // Format for the events in the registry.
+    pub fn new(registry: &EventRegistry) -> Self {
+        // Get the serial number of the controller (microseconds on time).
+        // Additional string before UNIT,
+        let sn_pattern = format!(r"{DATE_PATTERN} {TIME_PATTERN} (?:\S+ )?UNIT ([0-9]+)(?: (.+))?$");
+
+        // Get the controller firmware version.
+        let fw_pattern = format!(r"{DATE_PATTERN} {TIME_PATTERN}[:, ]EVENT ([0-9]+) ([0-9]+) (.+)/(.+)/(.+)/([-0-9]+)/([0-9]+) SWSTART (.+) ([.0-9]+.+) v(.+)$");
+
+        // Get the controller events
+        // Extra string before EVENT.
+        // Event types with spaces in their names must be known to the registry.
+        let ev_pattern = format!(
+            r"{DATE_PATTERN} {TIME_PATTERN} (?:\S+ )?EVENT ([0-9]+) ([0-9]+) ([-0-9]+)/([-0-9]+)/([0-9]+)/([0-9]+)/([0-9]+) ({}) (.+)$",
+            event_type_pattern(registry)
+        );
+
+        // Anything that looks like an event.
+        // Used to report EVENT lines that don't fully match.
+        let ev_like_pattern = format!(r"{DATE_PATTERN} .*\bEVENT [0-9]");
+
+        Self {
+            sn_pattern: Regex::new(&sn_pattern).expect("Invalid UNIT regex pattern"),
+            fw_pattern: Regex::new(&fw_pattern).expect("Invalid SWSTART regex pattern"),
+            ev_pattern: Regex::new(&ev_pattern).expect("Invalid EVENT regex pattern"),
+            ev_like_pattern: Regex::new(&ev_like_pattern).expect("Invalid EVENT regex pattern"),
+        }
+    }
+}
+
+impl LogFormat for ControllerFormat {
+    fn name(&self) -> &'static str {
+        "Controller log"
+    }
+
+    fn detect(&self, lines: &[&str]) -> bool {
+        lines.iter().any(|line| self.ev_like_pattern.is_match(line) || self.sn_pattern.is_match(line))
+    }
+
+    // Lines with fields that don't parse are noted as invalid.
+    fn parse_line(&mut self, line: &str, _timestamp_format: &TimestampFormat) -> LineEvent {
+        if !line.contains("EVENT") {
+            return LineEvent::None;
+        }
+        let Some(captures) = self.ev_pattern.captures(line) else {
+            if self.ev_like_pattern.is_match(line) {
+                return LineEvent::Invalid { reason: "Malformed EVENT line.".to_string(), event_type: None };
+            }
+            return LineEvent::None;
+        };
+
+        // Get the unix time, gps location, aux, RSSI and speed from the event data.
+        // While gps location is included in the event string,
+        // it's not part of the event detail.
+        let event_type = &captures[10];
+        let record = || -> Result<EventRecord, String> {
+            Ok(EventRecord {
+                date: captures[1].to_string(),
+                time: captures[2].to_string(),
+                trip_id: captures[3].to_string(),
+                unix_time: parse_field::<u64>(&captures[4], "unix time")?,
+                gps_locn: GpsLocation {
+                    lat: parse_field::<f64>(&captures[5], "latitude")? / 10_000_000.0,
+                    lon: parse_field::<f64>(&captures[6], "longitude")? / 10_000_000.0,
+                },
+                gps_aux: parse_field::<u32>(&captures[7], "gps aux")?,
+                gps_rssi: parse_field::<u32>(&captures[8], "gps rssi")?,
+                gps_speed: parse_field::<u32>(&captures[9], "gps speed")?,
+                event_type: event_type.to_string(),
+                detail: captures[11].to_string(),
+            })
+        };
+        match record() {
+            Ok(record) => LineEvent::Event(record),
+            Err(reason) => LineEvent::Invalid { reason, event_type: Some(event_type.to_string()) },
+        }
+    }
+
+    // Controller id from a UNIT line, or firmware version from a SWSTART line.
+    fn metadata(&self, line: &str) -> Option<LineMetadata> {
+        if line.contains("UNIT")
+            && let Some(captures) = self.sn_pattern.captures(line)
+        {
+            // Combine unit number with optional suffix.
+            let unit = match captures.get(4) {
+                Some(suffix) => format!("{} {}", &captures[3], suffix.as_str()),
+                None => captures[3].to_string(),
+            };
+            return Some(LineMetadata {
+                date: captures[1].to_string(),
+                time: captures[2].to_string(),
+                detail: ControllerDetail::Id(unit),
+            });
+        }
+
+        if line.contains("SWSTART")
+            && let Some(captures) = self.fw_pattern.captures(line)
+        {
+            // Group 11 contains the firmware version.
+            return Some(LineMetadata {
+                date: captures[1].to_string(),
+                time: captures[2].to_string(),
+                detail: ControllerDetail::Firmware(captures[11].to_string()),
+            });
+        }
+
+        None
+    }
+
+    fn boxed_clone(&self) -> Box<dyn LogFormat> {
+        Box::new(self.clone())
+    }
+}
+
+// Pattern for the event type in an EVENT line.
+// Multi-word event names (like OOS PM) come first, longest first,
+// so that they are matched before the single word fallback.
+fn event_type_pattern(registry: &EventRegistry) -> String {
+    let mut multi_word: Vec<&str> = registry.event_names()
+        .filter(|name| name.contains(' '))
+        .collect();
+    multi_word.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+    let mut alternatives: Vec<String> = multi_word.iter()
+        .map(|name| regex::escape(name))
+        .collect();
+    alternatives.push("[A-Z_]+".to_string());
+    alternatives.join("|")
+}
+
+// Parse a numeric field of an event.
+// Errors give the reason the field couldn't be parsed.
+pub(crate) fn parse_field<T: FromStr>(value: &str, name: &str) -> Result<T, String> {
+    value.parse::<T>()
+        .map_err(|_| format!("Invalid {} '{}'.", name, value))
+}
@@ -0,0 +1,141 @@
+// GPS fix quality.
+// Each event has a GPS fix, which can be missing, weak,
+// or jump further than the vehicle could have travelled.
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{GpsLocation, ScrapedData};
+
+// Quality of the GPS fix of an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GpsFix {
+    #[default]
+    Good,
+    NoFix,
+    WeakSignal,
+    Jump,
+}
+
+impl GpsFix {
+    // Check if the fix can be trusted.
+    pub fn is_good(&self) -> bool {
+        *self == GpsFix::Good
+    }
+
+    // Name for display.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GpsFix::Good => "Good",
+            GpsFix::NoFix => "No fix",
+            GpsFix::WeakSignal => "Weak signal",
+            GpsFix::Jump => "Jump",
+        }
+    }
+}
+
+// Limits for classifying GPS fixes.
+// Fixes with RSSI below weak RSSI are weak.
+// Fixes further from the last good fix than max speed allows are jumps.
+// After max jumps jumps in a row, the new location is trusted,
+// in case it was the last good fix that was wrong.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsFixModel {
+    pub weak_rssi: u32,
+    pub max_speed_kph: f64,
+    pub max_jumps: u32,
+}
+
+impl Default for GpsFixModel {
+    fn default() -> Self {
+        Self {
+            weak_rssi: 20,
+            max_speed_kph: 300.0,
+            max_jumps: 3,
+        }
+    }
+}
+
+// Classifies GPS fixes in time order.
+#[derive(Debug, Clone, Default)]
+pub struct GpsFixClassifier {
+    model: GpsFixModel,
+    last_good: Option<(u64, GpsLocation)>,
+    jumps: u32,
+}
+
+impl GpsFixClassifier {
+    pub fn new(model: GpsFixModel) -> Self {
+        Self {
+            model,
+            last_good: None,
+            jumps: 0,
+        }
+    }
+
+    // Classify the next fix.
+    pub fn classify(&mut self, unix_time: u64, locn: &GpsLocation, rssi: u32) -> GpsFix {
+        if locn.lat == 0.0 && locn.lon == 0.0 {
+            return GpsFix::NoFix;
+        }
+
+        // Compare with the last good fix, allowing at least
+        // a second between fixes logged in the same second.
+        if let Some((last_time, last_locn)) = &self.last_good {
+            let hours = unix_time.saturating_sub(*last_time).max(1) as f64 / 3600.0;
+            let speed_kph = distance_km(last_locn, locn) / hours;
+            if speed_kph > self.model.max_speed_kph {
+                self.jumps += 1;
+                if self.jumps >= self.model.max_jumps {
+                    self.last_good = Some((unix_time, locn.clone()));
+                    self.jumps = 0;
+                }
+                return GpsFix::Jump;
+            }
+        }
+        self.last_good = Some((unix_time, locn.clone()));
+        self.jumps = 0;
+
+        if rssi < self.model.weak_rssi {
+            GpsFix::WeakSignal
+        } else {
+            GpsFix::Good
+        }
+    }
+}
+
+// Classify the GPS fixes of events again, in order.
+// Needed when events are reordered, such as when logs are merged.
+pub fn classify_gps_fixes(scrapings: &mut [ScrapedData], model: GpsFixModel) {
+    let mut classifier = GpsFixClassifier::new(model);
+    for event in scrapings {
+        event.gps_fix = classifier.classify(event.unix_time, &event.gps_locn, event.gps_rssi);
+    }
+}
+
+// Great circle distance between two locations in km.
+pub fn distance_km(a: &GpsLocation, b: &GpsLocation) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lat2) = (a.lat.to_radians(), b.lat.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (b.lon - a.lon).to_radians();
+    let h = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * h.sqrt().asin()
+}
+
+// Colour of a GPS point, as red, green and blue.
+// Poor fixes are grey, otherwise the colour shows the speed band,
+// green to 60 kph, then blue, orange, and red over 100 kph.
+pub fn speed_colour(speed_kph: u32, fix: GpsFix) -> [u8; 3] {
+    if !fix.is_good() {
+        [150, 150, 150]
+    } else if speed_kph > 100 {
+        [255, 0, 0]
+    } else if speed_kph > 80 {
+        [255, 165, 0]
+    } else if speed_kph > 60 {
+        [0, 0, 255]
+    } else {
+        [0, 255, 0]
+    }
+}
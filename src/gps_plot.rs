@@ -3,14 +3,21 @@
 use eframe::egui;
 use egui::epaint;
 use geo_types::Point;
-use chrono::{DateTime, NaiveDateTime, Utc, ParseError};
+use chrono::{DateTime, Utc};
 use walkers::{Map, MapMemory, HttpTiles};
 use walkers::Plugin;
 use walkers::sources::{TileSource, Attribution};
 use reqwest::Client;
 
-use crate::scraper::{Scraper, ScrapedData};
+use std::io;
+use std::path::Path;
+
+use scraper::{speed_colour, GpsFix, ScrapedData};
+
+use crate::scraper::Scraper;
 use crate::app::PlotViewState;
+use crate::setting_up::SETTINGS;
+use crate::plot_image::{self, ImageFormat};
 
 // PlotPoint struct.
 #[derive(Debug, Clone)]
@@ -21,6 +28,7 @@ pub struct PlotPoint {
     pub lon: f64,
     pub speed: u32,
     pub _rssi: u32,
+    pub fix: GpsFix,
 }
 
 #[derive(Debug, Clone)]
@@ -44,16 +52,35 @@ impl MapState {
 impl From<&ScrapedData> for PlotPoint {
     fn from(data: &ScrapedData) -> Self {
         Self {
-            _timestamp: parse_datetime(&data.date_time).unwrap(),
+            // Use the controller unix time if the logged date time couldn't be read.
+            _timestamp: data.timestamp
+                .or_else(|| DateTime::from_timestamp(data.unix_time as i64, 0))
+                .unwrap_or_default(),
             _trip_num : data.trip_num.clone(),
             lat: data.gps_locn.lat,
             lon: data.gps_locn.lon,
             speed: data.gps_speed,
             _rssi: data.gps_rssi,
+            fix: data.gps_fix,
         }
     }
 }
 
+// Colour of points with a poor GPS fix.
+const POOR_FIX_COLOUR: egui::Color32 = egui::Color32::from_gray(150);
+
+// Get the plotting points of a trip.
+// Points without a fix are never plotted,
+// other poor fixes are left out if hiding them, and flagged otherwise.
+fn trip_plot_points(scraper: &Scraper, selected_trip: &str, hide_poor_gps: bool) -> Vec<PlotPoint> {
+    scraper.scrapings.iter()
+        .filter(|scraped| scraped.trip_key == selected_trip)
+        .filter(|scraped| scraped.gps_fix != GpsFix::NoFix)
+        .filter(|scraped| !hide_poor_gps || scraped.gps_fix.is_good())
+        .map(PlotPoint::from)
+        .collect()
+}
+
 // Custom satellite tile source.
 #[derive(Clone, Debug)]
 pub struct SatelliteTiles;
@@ -69,8 +96,8 @@ impl TileSource for SatelliteTiles {
 
     fn attribution(&self) -> Attribution {
         Attribution {
-            text: "Tiles © Esri — Source: Esri, i-cubed, USDA, USGS, AEX, GeoEye, Getmapping, Aerogrid, IGN, IGP, UPR-EGP, and the GIS User Community".into(),
-            url: "https://www.esri.com/".into(),
+            text: "Tiles © Esri — Source: Esri, i-cubed, USDA, USGS, AEX, GeoEye, Getmapping, Aerogrid, IGN, IGP, UPR-EGP, and the GIS User Community",
+            url: "https://www.esri.com/",
             logo_dark: None,
             logo_light: None,
         }
@@ -159,36 +186,17 @@ impl GpsPlotPlugin {
 }
 
 // Instantiate plugin for walkers API.
-impl Plugin for GpsPlotPlugin {
-    fn run(
-        self: Box<Self>,
-        ui: &mut egui::Ui,
-        response: &egui::Response,
-        projector: &walkers::Projector,
-        _map_memory: &MapMemory,
-    ) {
-        // Get the painter from the UI.
-        let painter = ui.painter();
-        
+impl GpsPlotPlugin {
+    // Draw the track, connecting lines then points coloured by speed,
+    // with start and finish pins. Only the part within 'rect' is drawn.
+    fn draw_track(&self, painter: &egui::Painter, rect: egui::Rect, project: impl Fn(&PlotPoint) -> egui::Pos2) {
         // Draw connecting lines between GPS points.
         for window in self.plot_points.windows(2) {
-            let prev_point = &window[0];
-            let curr_point = &window[1];
-            
-            // Use walkers' projector to convert GPS to screen coordinates.
-            let prev_pos = walkers::Position::from(Point::new(prev_point.lon, prev_point.lat));
-            let curr_pos = walkers::Position::from(Point::new(curr_point.lon, curr_point.lat));
-            
-            // Project to screen coordinates using walkers' projector.
-            let prev_screen = projector.project(prev_pos);
-            let curr_screen = projector.project(curr_pos);
-            
-            // Convert Vec2 to Pos2 for proper positioning.
-            let prev_screen_pos = egui::Pos2::new(prev_screen.x, prev_screen.y);
-            let curr_screen_pos = egui::Pos2::new(curr_screen.x, curr_screen.y);
+            let prev_screen_pos = project(&window[0]);
+            let curr_screen_pos = project(&window[1]);
             
             // Only draw if both points are within the visible area.
-            if response.rect.contains(prev_screen_pos) && response.rect.contains(curr_screen_pos) {
+            if rect.contains(prev_screen_pos) && rect.contains(curr_screen_pos) {
                 painter.line_segment(
                     [prev_screen_pos, curr_screen_pos],
                     egui::Stroke::new(3.0, egui::Color32::from_rgba_unmultiplied(0, 120, 255, 200))
@@ -198,35 +206,25 @@ impl Plugin for GpsPlotPlugin {
         
         // Draw GPS points.
         for (i, point) in self.plot_points.iter().enumerate() {
-            let position = walkers::Position::from(Point::new(point.lon, point.lat));
-            let screen_vec = projector.project(position);
-            
-            // Convert Vec2 to Pos2.
-            let screen_pos = egui::Pos2::new(screen_vec.x, screen_vec.y);
+            let screen_pos = project(point);
             
             // Only draw if the point is within the visible area.
-            if response.rect.contains(screen_pos) {
+            if rect.contains(screen_pos) {
                 // Check if this is start or end point.
                 let is_start = i == 0;
                 let is_end = i == self.plot_points.len() - 1;
                 
                 if is_start {
                     // Draw start pin (green flag-style).
-                    self.draw_start_pin(&painter, screen_pos);
+                    self.draw_start_pin(painter, screen_pos);
                 } else if is_end {
                     // Draw finish pin (checkered flag-style).
-                    self.draw_finish_pin(&painter, screen_pos);
+                    self.draw_finish_pin(painter, screen_pos);
                 } else {
-                    // Regular GPS point - colour based on speed.
-                    let color = if point.speed > 100 {
-                        egui::Color32::RED
-                    } else if point.speed > 80 {
-                        egui::Color32::from_rgb(255, 165, 0)
-                    } else if point.speed > 60 {
-                        egui::Color32::BLUE
-                    } else {
-                        egui::Color32::GREEN
-                    };
+                    // Regular GPS point - colour based on speed,
+                    // or flagged if the fix is poor.
+                    let [r, g, b] = speed_colour(point.speed, point.fix);
+                    let color = egui::Color32::from_rgb(r, g, b);
                     
                     // Draw the point with outline.
                     painter.circle_filled(screen_pos, 5.0, color);
@@ -237,10 +235,121 @@ impl Plugin for GpsPlotPlugin {
     }
 }
 
-// Helper function for getting dates.
-pub fn parse_datetime(date_str: &str) -> Result<DateTime<Utc>, ParseError> {
-    let naive = NaiveDateTime::parse_from_str(date_str, "%d/%m/%Y %H:%M:%S")?;
-    Ok(naive.and_utc())
+impl Plugin for GpsPlotPlugin {
+    fn run(
+        self: Box<Self>,
+        ui: &mut egui::Ui,
+        response: &egui::Response,
+        projector: &walkers::Projector,
+        _map_memory: &MapMemory,
+    ) {
+        // Use walkers' projector to convert GPS to screen coordinates.
+        self.draw_track(ui.painter(), response.rect, |point| {
+            let position = walkers::Position::from(Point::new(point.lon, point.lat));
+            let screen_vec = projector.project(position);
+            egui::Pos2::new(screen_vec.x, screen_vec.y)
+        });
+    }
+}
+
+// Save the selected trip's track as an image.
+// Map tiles aren't saved, the track is drawn as on the map,
+// fitted to the image, with the speed legend underneath.
+#[allow(clippy::too_many_arguments)]
+pub fn save_plot_image(
+    path: &Path,
+    format: ImageFormat,
+    scale: f32,
+    size: egui::Vec2,
+    scraper: &Scraper,
+    selected_trip: &str,
+    dark_mode: bool,
+    hide_poor_gps: bool,
+) -> io::Result<()> {
+    let plot_points = trip_plot_points(scraper, selected_trip, hide_poor_gps);
+    if plot_points.is_empty() {
+        return Err(io::Error::other("No valid GPS points found for this trip."));
+    }
+    let title = format!("Trip {}", scraper.trip_num(selected_trip).unwrap_or(selected_trip));
+    let gps_plugin = GpsPlotPlugin { plot_points };
+
+    plot_image::save_plot_image(path, format, size, scale, dark_mode, |painter| {
+        let visuals = painter.ctx().style().visuals.clone();
+        let legend_height = 24.0;
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, size).shrink(8.0);
+        let plot_rect = egui::Rect::from_min_max(rect.min, egui::pos2(rect.max.x, rect.max.y - legend_height));
+
+        // Plot background and border, as in the simple plot.
+        painter.rect_filled(plot_rect, epaint::CornerRadius::same(5), visuals.extreme_bg_color);
+        painter.rect_stroke(
+            plot_rect,
+            epaint::CornerRadius::same(5),
+            egui::Stroke::new(1.0, visuals.widgets.noninteractive.bg_stroke.color),
+            egui::epaint::StrokeKind::Inside,
+        );
+        painter.text(
+            plot_rect.min + egui::vec2(10.0, 10.0),
+            egui::Align2::LEFT_TOP,
+            &title,
+            egui::FontId::proportional(14.0),
+            visuals.text_color(),
+        );
+
+        let project = fit_track(&gps_plugin.plot_points, plot_rect.shrink(30.0));
+        gps_plugin.draw_track(painter, plot_rect, project);
+
+        // Speed legend.
+        let mut legend = vec![
+            (speed_colour(0, GpsFix::Good), "≤60 km/h"),
+            (speed_colour(70, GpsFix::Good), "60-80 km/h"),
+            (speed_colour(90, GpsFix::Good), "80-100 km/h"),
+            (speed_colour(110, GpsFix::Good), ">100 km/h"),
+        ];
+        if !hide_poor_gps {
+            legend.push((speed_colour(0, GpsFix::NoFix), "Poor GPS fix"));
+        }
+        let font_id = egui::FontId::proportional(13.0);
+        let centre_y = rect.max.y - legend_height / 2.0;
+        let galley = painter.layout_no_wrap("Speed legend:".to_string(), font_id.clone(), visuals.text_color());
+        let mut x = rect.min.x + galley.size().x + 16.0;
+        painter.galley(egui::pos2(rect.min.x, centre_y - galley.size().y / 2.0), galley, visuals.text_color());
+        for ([r, g, b], label) in legend {
+            painter.circle_filled(egui::pos2(x + 5.0, centre_y), 5.0, egui::Color32::from_rgb(r, g, b));
+            let galley = painter.layout_no_wrap(label.to_string(), font_id.clone(), visuals.text_color());
+            let width = galley.size().x;
+            painter.galley(egui::pos2(x + 14.0, centre_y - galley.size().y / 2.0), galley, visuals.text_color());
+            x += width + 30.0;
+        }
+    })
+}
+
+// Projection fitting a track into a rect, keeping its shape.
+// Longitude is scaled by the cosine of the latitude, as on a map.
+fn fit_track(plot_points: &[PlotPoint], rect: egui::Rect) -> impl Fn(&PlotPoint) -> egui::Pos2 {
+    let mut min_lat = f64::MAX;
+    let mut max_lat = f64::MIN;
+    let mut min_lon = f64::MAX;
+    let mut max_lon = f64::MIN;
+
+    for point in plot_points {
+        min_lat = min_lat.min(point.lat);
+        max_lat = max_lat.max(point.lat);
+        min_lon = min_lon.min(point.lon);
+        max_lon = max_lon.max(point.lon);
+    }
+
+    let center_lat = (min_lat + max_lat) / 2.0;
+    let center_lon = (min_lon + max_lon) / 2.0;
+    let lon_scale = center_lat.to_radians().cos();
+    let width = ((max_lon - min_lon) * lon_scale).max(f64::EPSILON);
+    let height = (max_lat - min_lat).max(f64::EPSILON);
+    let scale = (rect.width() as f64 / width).min(rect.height() as f64 / height);
+    let center = rect.center();
+
+    move |point| egui::pos2(
+        center.x + ((point.lon - center_lon) * lon_scale * scale) as f32,
+        center.y - ((point.lat - center_lat) * scale) as f32,
+    )
 }
 
 // Modified function with pan and zoom support
@@ -250,6 +359,7 @@ pub fn plot_gps_data(
     selected_id: &Option<String>,
     view_state: &mut PlotViewState,
     last_trip_id: &mut Option<String>,
+    hide_poor_gps: bool,
 ) {
     // Get id of selected trip, or show prompt if no trip selected.
     let selected_trip = match selected_id.as_ref() {
@@ -264,11 +374,7 @@ pub fn plot_gps_data(
     };
 
     // Get all the plotting points.
-    let plot_points: Vec<PlotPoint> = scraper.scrapings.iter()
-        .filter(|scraped| scraped.trip_num == *selected_trip)
-        .filter(|scraped| scraped.gps_locn.lat != 0.0 && scraped.gps_locn.lon != 0.0)
-        .map(PlotPoint::from)
-        .collect();
+    let plot_points = trip_plot_points(scraper, selected_trip, hide_poor_gps);
 
     if plot_points.is_empty() {
         ui.label("No valid GPS points found for this trip.");
@@ -331,8 +437,8 @@ pub fn plot_gps_data(
             let plot_rect = rect.shrink(20.0);
             
             // Convert drag pixels to lat/lon offset.
-            let lat_span = (padded_max_lat - padded_min_lat) / view_state.zoom * -1.0;
-            let lon_span = (padded_max_lon - padded_min_lon) / view_state.zoom * -1.0;
+            let lat_span = -((padded_max_lat - padded_min_lat) / view_state.zoom);
+            let lon_span = -((padded_max_lon - padded_min_lon) / view_state.zoom);
             
             let lat_offset = -(view_state.drag_offset.y as f64 / plot_rect.height() as f64) * lat_span;
             let lon_offset = (view_state.drag_offset.x as f64 / plot_rect.width() as f64) * lon_span;
@@ -351,7 +457,7 @@ pub fn plot_gps_data(
         let scroll_delta = ui.input(|i| i.raw_scroll_delta.y);
         if scroll_delta != 0.0 {
             let zoom_factor = 1.0 + scroll_delta * 0.001;
-            view_state.zoom = (view_state.zoom as f64 * zoom_factor as f64).clamp(0.5, 20.0);
+            view_state.zoom = (view_state.zoom * zoom_factor as f64).clamp(0.5, 20.0);
         }
     }
 
@@ -402,8 +508,10 @@ pub fn plot_gps_data(
             continue;
         }
         
-        // Colour based on speed.
-        let color = if point.speed > 100 {
+        // Colour based on speed, or flagged if the fix is poor.
+        let color = if !point.fix.is_good() {
+            POOR_FIX_COLOUR
+        } else if point.speed > 100 {
             egui::Color32::RED
         } else if point.speed > 80 {
             egui::Color32::BLUE
@@ -444,6 +552,9 @@ pub fn plot_gps_data(
         ui.colored_label(egui::Color32::ORANGE, "● 60-80 km/h");
         ui.colored_label(egui::Color32::BLUE, "● 80-100 km/h");
         ui.colored_label(egui::Color32::RED, "● >100 km/h");
+        if !hide_poor_gps {
+            ui.colored_label(POOR_FIX_COLOUR, "● Poor GPS fix");
+        }
     });
 
     // Show some statistics.
@@ -469,11 +580,13 @@ pub fn plot_gps_data(
             let seconds = total_seconds % 60;
 
             // Show start and end trip times, and also duration.
+            // Times are in the display time zone.
+            let display_tz = SETTINGS.lock().unwrap().display_tz();
             ui.label("Trip time:");
             ui.strong(format!(
                 "{} to {}  ({:02}:{:02}:{:02})", 
-                first._timestamp.format("%H:%M:%S"), 
-                last._timestamp.format("%H:%M:%S"),
+                first._timestamp.with_timezone(&display_tz).format("%H:%M:%S"), 
+                last._timestamp.with_timezone(&display_tz).format("%H:%M:%S"),
                 hours, 
                 minutes, 
                 seconds
@@ -489,6 +602,7 @@ pub fn plot_gps_data(
 }
 
 // Replace the plot_gps_data_with_tiles function with this updated version:
+#[allow(clippy::too_many_arguments)]
 pub fn plot_gps_data_with_tiles(
     ui: &mut egui::Ui, 
     scraper: &Scraper, 
@@ -497,6 +611,7 @@ pub fn plot_gps_data_with_tiles(
     tiles: &mut HttpTiles,
     last_trip_id: &mut Option<String>,
     map_state: &mut Option<MapState>, // Add this parameter
+    hide_poor_gps: bool,
 ) {
 
     // Get id of selected trip, or show prompt if no trip selected.
@@ -512,11 +627,7 @@ pub fn plot_gps_data_with_tiles(
     };
 
     // Get all the plotting points.
-    let plot_points: Vec<PlotPoint> = scraper.scrapings.iter()
-        .filter(|scraped| scraped.trip_num == *selected_trip)
-        .filter(|scraped| scraped.gps_locn.lat != 0.0 && scraped.gps_locn.lon != 0.0)
-        .map(PlotPoint::from)
-        .collect();
+    let plot_points = trip_plot_points(scraper, selected_trip, hide_poor_gps);
 
     if plot_points.is_empty() {
         ui.label("No valid GPS points found for this trip.");
@@ -649,6 +760,9 @@ pub fn plot_gps_data_with_tiles(
         ui.colored_label(egui::Color32::BLUE, "● 60-80 km/h");
         ui.colored_label(egui::Color32::ORANGE, "● 80-100 km/h");
         ui.colored_label(egui::Color32::RED, "● >100 km/h");
+        if !hide_poor_gps {
+            ui.colored_label(POOR_FIX_COLOUR, "● Poor GPS fix");
+        }
     });
     
     // Show some statistics.
@@ -672,11 +786,13 @@ pub fn plot_gps_data_with_tiles(
             let seconds = total_seconds % 60;
             
             // Show start and end trip times, and also duration.
+            // Times are in the display time zone.
+            let display_tz = SETTINGS.lock().unwrap().display_tz();
             ui.label("Trip time:");
             ui.strong(format!(
                 "{} to {}  ({:02}:{:02}:{:02})", 
-                first._timestamp.format("%H:%M:%S"), 
-                last._timestamp.format("%H:%M:%S"),
+                first._timestamp.with_timezone(&display_tz).format("%H:%M:%S"), 
+                last._timestamp.with_timezone(&display_tz).format("%H:%M:%S"),
                 hours, 
                 minutes, 
                 seconds
@@ -13,6 +13,27 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
     ui.collapsing("1.0 Getting Started", |ui| {
         ui.label("From the main menu select 'File' / 'Open' to select a log file to process.");
         ui.label("Alternatively, drag and drop a log file onto the application window.");
+        ui.label("Log files compressed with gzip (.gz) are decompressed as they are read.");
+        ui.label("CSV exports from the telematics portal can also be opened, see section 5.6 for setting up the columns.");
+        ui.label("The format of each file, a controller log or a CSV export, is detected from its first lines. If a file is detected wrongly, select the format from 'File' / 'Format', which reloads the files.");
+        ui.label("Zip archives (.zip) can also be opened or dropped. If the archive holds more than one file, such as the current log and older .bak files, a list of the files is shown to select the ones to load.");
+        ui.label("Several log files can be selected or dropped at once, such as a log and the .bak files it was rolled over into.");
+        ui.label("The events from all the files are merged in time order, with events that appear in more than one file only shown once, and trips split across files joined back together.");
+        ui.label("The files loaded are shown in the status bar at the bottom of the window.");
+        ui.label("To watch a log that is still being written, for example when bench testing a controller, select 'File' / 'Follow'.");
+        ui.label("The log is read again from the start, then new events are added as they are written, and the trip list and plots update as they arrive.");
+        ui.label("If the log is cleared or rolled over to a new file, it is read again from the start.");
+        ui.label("Only a single uncompressed log file can be followed. Select 'Follow' again to stop following.");
+        ui.label("Logs can also be read live from a controller on the bench, by selecting 'File' / 'Connect...'.");
+        ui.label("Enter the serial device (such as /dev/ttyUSB0 or COM3) and baud rate, or the host:port of a TCP serial server.");
+        ui.label("Events are added as they arrive. If the connection drops it is retried until 'Disconnect' is selected.");
+        ui.label("Lines over 64 KiB long, usually noise on the line, are dropped and listed as parse issues.");
+        ui.label("Optionally everything received can be saved to a capture file, which can be opened later like any other log file.");
+        ui.label("The events, or a summary of the trips, can be saved to a CSV file for spreadsheets from 'File' / 'Export'.");
+        ui.label("Exports include the events shown with the current 'Show' menu settings. The trip summary has a row for each trip, with its start and end time, duration, number of events, top speed and operator.");
+        ui.label("The GPS track of the selected trip, or of all trips, can be saved for mapping tools as GPX, GeoJSON (for QGIS) or KML (for Google Earth), with a point for each event. Poor GPS fixes are left out if they are hidden from the plots.");
+        ui.label("In GeoJSON and KML, event points have the event data, and are coloured by speed as in the GPS plot.");
+        ui.label("Everything scraped from the log can be saved to a JSON or NDJSON file, for other tools, from 'File' / 'Export'. These files can be opened like a log file, without the original log.");
     });
     ui.collapsing("2.0 Scraped Data", |ui| {
         ui.label("On load, a scraped log file will list the trips in the file as illusrated below.");
@@ -35,7 +56,11 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
     ui.collapsing("2.2 Program info and status", |ui| {
         ui.label("At the bottom of the screen, information about the file, and status of the processing is shown as illustrated below.");
         ui.label("Also in the bottom panel is the detected controller ID, and the firmware version running on the controller.");
-        ui.label("Note that the controller ID and firmware version is only the first of each record encountered in the file.");
+        ui.label("If the controller was swapped, or the firmware upgraded, part way through the log, the first and last of each are shown along with how many there were.");
+        ui.label("Press 'History', or select 'Controller history' from the 'View' menu, to list every controller ID and firmware version found, with the line number and time. Changes from the previous ID or version are highlighted.");
+        ui.label("Each trip in the trip list also shows the firmware version running on the controller at the start of the trip.");
+        ui.label("While a file is loading the status shows a progress bar with the amount of the file read and the number of events found. Press Cancel to stop the load.");
+        ui.label("Lines that couldn't be scraped are skipped, and the number of parse issues is shown in the status. Press 'Show issues', or select 'Parse issues' from the 'View' menu, to list them with their line number, reason and raw text, and counts per event type. Lines with corrupted characters are still scraped, with those characters replaced, and are listed too.");
         ui.label("At the far right is the trip ID of the currently selected trip (if one is selected).");
     });
 
@@ -63,6 +88,8 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
         ui.label("Note that selecting the trip again will collapse the event back one level.");
         ui.label("Note also that this applies to the trip, i.e. selecting an expanded trip will collapse the trip to the top level.");
         ui.label("Any number of trips and/or events can be expanded at a time as the window supports vertical scrolling as required.");
+        ui.label("Check 'Raw log lines' from the 'Show' menu to also show each event's line as logged, with the log file and line number it came from.");
+        ui.label("Right click an event and select 'Copy raw' to copy its line as logged to the clipboard.");
     });
 
     // Event expanded to show event details.
@@ -111,6 +138,8 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
         ui.label("Most trip events include some GPS data in the event string.");
         ui.label("By checking 'GPS event data' from the 'Show' menu option, GPS data is appended to the event data for each event as illustrated in the figure below.");
         ui.label("Note that GPS data relies on a gps fix for data to be accurate or useful.");
+        ui.label("The GPS data also shows the aux field, thought to be the heading or satellite count, and the quality of the fix.");
+        ui.label("A fix is 'No fix' at 0,0, 'Weak signal' if the RSSI is low, and 'Jump' if it is further from the last good fix than the machine could have travelled.");
     });
 
     // Optional gps data included with event data.
@@ -147,6 +176,7 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
         ui.label("As events are recorded in sequence, when plotted, the gps data is a breadcrumb trail of the machine in question.");
         ui.label("From the Plot menu the user can select to show the gps plot for the currently selected trip (if there is one selected).");
         ui.label("The Plot menu allows the user to select a simple plot (no background), or one with an Open street (OSM), or ESRI satelitte view tile background as illustrated belwo.");
+        ui.label("Points with a weak signal or a jump are shown in grey. Check 'Hide poor GPS fixes' in the Plot menu to leave them out of the GPS plots and the speed time series.");
     });
 
     // GPS plot menu.
@@ -288,6 +318,13 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
         ui.label("Deleting the delta time cursor will still leave the time cursor alone.");
     });
 
+    ui.collapsing("4.7 Saving plots as images", |ui| {
+        ui.label("The time series and GPS plot windows have an Export image menu, to save the plots of the selected trip as a PNG or SVG image.");
+        ui.label("Time series images have all the plots, including any scrolled out of view, with the current pan and zoom, and the time and delta cursors if they are on.");
+        ui.label("GPS images have the trip track and speed legend, but not the map tiles.");
+        ui.label("PNG images can be saved at 1, 2 or 3 times the size of the plot window, SVG images can be scaled to any size.");
+    });
+
     ui.collapsing("5.0 User settings", |ui| {
         ui.label("There are some program related settings that the user can select or use independent of the log scraping functionality.");
         ui.label("These include the dark/light program setting, and the font size of trip and event data information, on the main display.");
@@ -340,4 +377,42 @@ pub fn draw_help_content(ui: &mut egui::Ui, app: &mut MyApp) {
         ui.add(egui::Image::new(texture).max_width(400.0));
         ui.add_space(10.0);
     }
+
+    ui.collapsing("5.4 Event definitions", |ui| {
+        ui.label("The events that can be scraped, and how their data is shown, are defined in the 'events.yml' file.");
+        ui.label("If there is no event definitions file in the top level directory, a default one will be created on application start.");
+        ui.label("Each event has a name, a pattern for the event data, and a list of fields with a label and the pattern group to take the value from.");
+        ui.label("Fields used in plots also have a key, such as 'duration' or 'battery', and a unit, such as 'seconds' or 'volts'.");
+        ui.label("Keys the application doesn't know are ignored, and noted in the log file, but the field is still shown.");
+        ui.label("Numeric fields can have a scale and number of decimal places, for example battery voltage is scaled by 0.1 and shown to 1 decimal place.");
+        ui.label("Events that only occur out of trip are marked with 'on_trip: false'.");
+        ui.label("Changes to the event definitions take effect the next time the application is started.");
+    });
+
+    ui.collapsing("5.5 Dates, times and time zones", |ui| {
+        ui.label("Log files can be written with the day, month or year first, and with 12 hour (AM/PM) or 24 hour times.");
+        ui.label("From the 'Time' menu, the user can select the date order and time zone of the loaded log file.");
+        ui.label("Changing either of these reloads the log file.");
+        ui.label("The display time zone, also on the 'Time' menu, is used for the trip and event list, and for the GPS and time series plots.");
+        ui.label("All three are saved in the 'settings.yml' file when changed, as 'date_order' (dmy, mdy or ymd), 'source_time_zone' and 'display_time_zone'.");
+        ui.label("Time zones are names such as 'UTC' or 'America/Chicago'.");
+        ui.label("Events with dates that can't be read are still shown, with the date and time as logged, and are listed in the Parse issues window.");
+    });
+
+    ui.collapsing("5.6 CSV log files", |ui| {
+        ui.label("CSV exports from the telematics portal are read as one event per row.");
+        ui.label("The columns are set in the 'settings.yml' file under 'csv_columns', as a header name or a column number (starting at 1) for files without a header.");
+        ui.label("The columns are 'date', 'time', 'trip', 'unix_time', 'latitude', 'longitude' (in degrees), 'gps_aux', 'rssi', 'speed', 'event_type' and 'detail' (the event sub-data), and the 'delimiter'.");
+        ui.label("Columns that aren't in the file can be left empty, apart from the date, trip, latitude, longitude and event type. If 'time' is empty the date column has the date and time, and if 'unix_time' is empty it is worked out from the date and time.");
+        ui.label("The first row is taken to be a header if it has any of the column names, or has no date in the date column. Columns that can't be found are listed in the Parse issues window.");
+    });
+
+    ui.collapsing("5.7 Custom scrape rules", |ui| {
+        ui.label("Log lines that aren't events, such as modem or CAN bus lines, can be scraped with rules in the 'rules.yml' file.");
+        ui.label("If there is no rules file in the top level directory, one with an example rule will be created on application start.");
+        ui.label("Each rule has a name, shown as the event name, and a pattern with named groups, such as '(?P<rssi>[0-9]+)', that become the event data.");
+        ui.label("Groups named 'date' and 'time' give the time of the event, otherwise the time of the event before it is used. Matches before the first event are listed as parse issues.");
+        ui.label("Numeric values from rules are plotted in the time series, and rules without numeric values are shown as event markers.");
+        ui.label("Changes to the rules take effect the next time the application is started.");
+    });
 }
@@ -1,17 +1,19 @@
 // Helper functions for time series plots.
 
+ use chrono::DateTime;
+
+ use crate::setting_up::SETTINGS;
  use crate::time_series_plot::TimeSeriesData;
  use crate::time_series_plot::SinglePoint;
 
 // Helper function to convert Unix timestamp to hh:mm:ss format.
+// Time of day is in the display time zone.
 pub fn unix_time_to_hms(unix_time: u64) -> String {
-    // Convert Unix timestamp to time of day (UTC).
-    let seconds_in_day = unix_time % 86400;
-    let hours = seconds_in_day / 3600;
-    let minutes = (seconds_in_day % 3600) / 60;
-    let seconds = seconds_in_day % 60;
-    
-    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    let display_tz = SETTINGS.lock().unwrap().display_tz();
+    match DateTime::from_timestamp(unix_time as i64, 0) {
+        Some(timestamp) => timestamp.with_timezone(&display_tz).format("%H:%M:%S").to_string(),
+        None => "--:--:--".to_string(),
+    }
 }
 
 // Helper function to calculate the overall time range across all datasets.
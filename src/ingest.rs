@@ -0,0 +1,211 @@
+// Live ingest of log lines from a controller.
+// Controllers on the bench stream their log over a serial port,
+// or a TCP socket via a serial server. Lines are parsed as they
+// arrive, and the connection is retried if it drops.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::parser::{LogParser, ParsedLog};
+
+// How long a read waits for data before checking for cancel.
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+// Longest line kept while waiting for its end.
+// Anything longer is noise on the line, and is dropped.
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+// Where to read the log from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IngestSource {
+    Serial { path: String, baud_rate: u32 },
+    Tcp { address: String },
+}
+
+impl IngestSource {
+    // Work out the source from what the user entered.
+    // host:port is a TCP socket, anything else is a serial device,
+    // like /dev/ttyUSB0 or COM3.
+    pub fn parse(spec: &str, baud_rate: u32) -> Self {
+        let spec = spec.trim();
+        let is_tcp = spec.rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if is_tcp {
+            IngestSource::Tcp { address: spec.to_string() }
+        } else {
+            IngestSource::Serial { path: spec.to_string(), baud_rate }
+        }
+    }
+
+    // Open a connection to the source.
+    // Reads time out, so that the reader can be stopped.
+    fn connect(&self) -> io::Result<Box<dyn Read + Send>> {
+        match self {
+            IngestSource::Serial { path, baud_rate } => {
+                let port = serialport::new(path, *baud_rate)
+                    .timeout(READ_TIMEOUT)
+                    .open()?;
+                Ok(Box::new(port))
+            }
+            IngestSource::Tcp { address } => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+impl fmt::Display for IngestSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestSource::Serial { path, baud_rate } => write!(f, "{} at {} baud", path, baud_rate),
+            IngestSource::Tcp { address } => write!(f, "{}", address),
+        }
+    }
+}
+
+// Live ingest options.
+// Capture is a file to append everything received to, as received.
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    pub source: IngestSource,
+    pub capture: Option<PathBuf>,
+    pub reconnect_delay: Duration,
+}
+
+impl IngestOptions {
+    pub fn new(source: IngestSource) -> Self {
+        Self {
+            source,
+            capture: None,
+            reconnect_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+// Things that happen during live ingest.
+// Parsed holds what was scraped since the last Parsed.
+#[derive(Debug, Clone)]
+pub enum IngestEvent {
+    Connected,
+    Disconnected(String),
+    Parsed(ParsedLog),
+}
+
+// Read and parse lines from the source until cancelled.
+// Events are passed to the given function, which returns
+// false to stop. Errors are only returned for the capture file,
+// connection errors are passed on as Disconnected and retried.
+pub fn run_ingest(
+    options: &IngestOptions,
+    mut parser: LogParser,
+    cancel: &AtomicBool,
+    mut on_event: impl FnMut(IngestEvent) -> bool,
+) -> io::Result<()> {
+    let mut capture = match &options.capture {
+        Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+        None => None,
+    };
+    let mut buffer = [0u8; 4096];
+    let mut partial: Vec<u8> = Vec::new();
+    let mut skipping_line = false;
+
+    while !cancel.load(Ordering::Relaxed) {
+        let mut stream = match options.source.connect() {
+            Ok(stream) => stream,
+            Err(e) => {
+                if !on_event(IngestEvent::Disconnected(e.to_string())) {
+                    return Ok(());
+                }
+                wait(options.reconnect_delay, cancel);
+                continue;
+            }
+        };
+        if !on_event(IngestEvent::Connected) {
+            return Ok(());
+        }
+
+        let reason = loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+
+            let num_bytes = match stream.read(&mut buffer) {
+                Ok(0) => break "Connection closed.".to_string(),
+                Ok(num_bytes) => num_bytes,
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted) => continue,
+                Err(e) => break e.to_string(),
+            };
+            let mut received = &buffer[..num_bytes];
+            write_capture(&mut capture, received)?;
+
+            // Skip the rest of a line that was too long.
+            if skipping_line {
+                let Some(newline) = received.iter().position(|&byte| byte == b'\n') else {
+                    continue;
+                };
+                received = &received[newline + 1..];
+                skipping_line = false;
+            }
+
+            // Parse the complete lines, keeping any half received line.
+            partial.extend_from_slice(received);
+            let last_newline = partial.iter().rposition(|&byte| byte == b'\n');
+            if let Some(last_newline) = last_newline {
+                for line in partial[..last_newline].split(|&byte| byte == b'\n') {
+                    parser.parse_line_bytes(line);
+                }
+                partial.drain(..=last_newline);
+            }
+
+            // Don't wait forever for the end of a line that's too long.
+            let too_long = partial.len() > MAX_LINE_LENGTH;
+            if too_long {
+                let reason = format!("Line longer than {} bytes dropped.", MAX_LINE_LENGTH);
+                parser.reject_line(&String::from_utf8_lossy(&partial), &reason);
+                partial.clear();
+                skipping_line = true;
+            }
+            if last_newline.is_none() && !too_long {
+                continue;
+            }
+
+            if !on_event(IngestEvent::Parsed(parser.take_parsed())) {
+                return Ok(());
+            }
+        };
+
+        // A line cut off by the connection dropping can't be finished.
+        partial.clear();
+        skipping_line = false;
+        if !on_event(IngestEvent::Disconnected(reason)) {
+            return Ok(());
+        }
+        wait(options.reconnect_delay, cancel);
+    }
+
+    Ok(())
+}
+
+// Save received data to the capture file.
+fn write_capture(capture: &mut Option<File>, received: &[u8]) -> io::Result<()> {
+    if let Some(file) = capture {
+        file.write_all(received)?;
+    }
+    Ok(())
+}
+
+// Wait before reconnecting, stopping early if cancelled.
+fn wait(delay: Duration, cancel: &AtomicBool) {
+    let start = Instant::now();
+    while start.elapsed() < delay && !cancel.load(Ordering::Relaxed) {
+        thread::sleep(READ_TIMEOUT.min(delay));
+    }
+}
@@ -0,0 +1,35 @@
+// Log file parsing library for Scraper.
+// Has no GUI dependencies, so that the parser can be reused
+// from scripts and services as well as the desktop application.
+
+pub mod archive;
+pub mod csv_log;
+pub mod dump;
+pub mod event;
+pub mod export;
+pub mod follow;
+pub mod format;
+pub mod gps;
+pub mod ingest;
+pub mod merge;
+pub mod parser;
+pub mod rules;
+pub mod schema;
+pub mod timestamp;
+mod events;
+
+pub use archive::{log_file_kind, read_log_file, zip_members, ArchiveMember, LogFileKind};
+pub use csv_log::{CsvFormat, CsvMapping};
+pub use dump::{is_dump_file, read_dump, write_json, write_ndjson};
+pub use event::{Event, EventField, EventKind, FieldKey, Kph, Rpm, Seconds, Unit, Value, Volts};
+pub use events::EventRegistry;
+pub use export::{write_events_csv, write_geojson, write_gpx, write_kml, write_trips_csv, xml_escape};
+pub use follow::{FollowUpdate, LogFollower};
+pub use format::{detect_format, ControllerFormat, EventRecord, LineEvent, LineMetadata, LogFormat};
+pub use gps::{classify_gps_fixes, speed_colour, GpsFix, GpsFixClassifier, GpsFixModel};
+pub use ingest::{run_ingest, IngestEvent, IngestOptions, IngestSource};
+pub use merge::{assign_trips, merge_logs};
+pub use parser::{parse_log, ControllerChange, ControllerDetail, Diagnostic, GpsLocation, LogParser, ParsedLog, ScrapedData};
+pub use rules::{RuleDef, RuleMatch, RuleSchema, RuleSet};
+pub use schema::{EventDef, EventSchema, FieldDef};
+pub use timestamp::{format_timestamp, DateOrder, TimestampFormat};
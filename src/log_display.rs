@@ -8,25 +8,17 @@ use eframe::egui::{RichText};
 use crate::egui::{ScrollArea, Ui};
 
 use crate::colours;
-use crate::scraper::ScrapedData;
+use scraper::{format_timestamp, EventKind, ScrapedData};
 use crate::setting_up::DETAILS;
 use crate::setting_up::SETTINGS;
 use crate::settings::Settings;
 
 // Simple UI state to hold processed display data.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct UiState {
     pub display_ready: bool,
 }
 
-impl Default for UiState {
-    fn default() -> Self {
-        Self {
-            display_ready: false,
-        }
-    }
-}
-
 impl UiState {
     // Process scraped data for display.
     pub fn update_with_scraped_data(&mut self, scraped_data: &[ScrapedData]) {
@@ -41,6 +33,7 @@ impl UiState {
 
 // Main rendering function.
 // The display entry point.
+#[allow(clippy::too_many_arguments)]
 pub fn render_scraped_data(
         ui: &mut Ui,
         ui_state: &mut UiState,
@@ -53,6 +46,7 @@ pub fn render_scraped_data(
         show_report_events: bool,
         show_debug_events: bool,
         show_gps_events: bool,
+        show_raw_lines: bool,
         selected_id: &mut Option<String>,
         dark_mode: bool,
 ) {
@@ -94,20 +88,20 @@ pub fn render_scraped_data(
         // Go through all events and if applicable render them to the UI.
         // If "Show" menu settings that events should be ignored then don't render.
         for (index, item) in filtered_data {
-            match item.event_type.as_str() {
-                "SIGNON" => {
+            match item.event.kind {
+                EventKind::SignOn => {
                     // Start a new trip.
                     current_trip_header = Some(item);
                     trip_events.clear();
                     trip_events.push((index, item));
                     in_trip = true;
                 }
-                "TRIP" => {
+                EventKind::Trip => {
                     if in_trip {
                         // Add TRIP event to current trip and then render the complete trip.
                         trip_events.push((index, item));
                         if let Some(trip_data) = current_trip_header {
-                            render_trip_section(ui, trip_data, &trip_events, selected_id, dark_mode, show_gps_events);
+                            render_trip_section(ui, trip_data, &trip_events, selected_id, dark_mode, show_gps_events, show_raw_lines);
                         }
                         // End the trip.
                         current_trip_header = None;
@@ -117,7 +111,7 @@ pub fn render_scraped_data(
                         // TRIP event without SIGNON,
                         // Out of trip events, generally at the start of a log file.
                         // These events get displayed at the top level.
-                        render_top_level_event(ui, index, item, dark_mode);
+                        render_top_level_event(ui, index, item, dark_mode, show_raw_lines);
                     }
                 }
                 _ => {
@@ -127,23 +121,109 @@ pub fn render_scraped_data(
                     } else {
                         // Not in trip.
                         // Display at top level (in between TRIP and SIGNON).
-                        render_top_level_event(ui, index, item, dark_mode);
+                        render_top_level_event(ui, index, item, dark_mode, show_raw_lines);
                     }
                 }
             }
         }
 
         // Handle case where data ends without a TRIP event (incomplete trip).
-        if in_trip && !trip_events.is_empty() {
-            if let Some(trip_data) = current_trip_header {
-                render_trip_section(ui, trip_data, &trip_events, selected_id, dark_mode, show_gps_events);
-            }
+        if in_trip && !trip_events.is_empty()
+            && let Some(trip_data) = current_trip_header
+        {
+            render_trip_section(ui, trip_data, &trip_events, selected_id, dark_mode, show_gps_events, show_raw_lines);
+        }
+    });
+}
+
+// Date and time of an event in the display time zone.
+// Falls back to the date and time as logged if it couldn't be read.
+fn display_time(item: &ScrapedData, settings: &Settings) -> String {
+    match &item.timestamp {
+        Some(timestamp) => format_timestamp(timestamp, settings.display_tz(), settings.date_order),
+        None => item.date_time.clone(),
+    }
+}
+
+// Trip header, with the firmware running at the start of the trip.
+fn trip_title(trip_data: &ScrapedData, settings: &Settings) -> String {
+    let title = format!("TRIP {:} - {}", trip_data.trip_num, display_time(trip_data, settings));
+    match &trip_data.firmware {
+        Some(firmware) => format!("{} (fw {})", title, firmware),
+        None => title,
+    }
+}
+
+// Where an event came from, the log name if known and line number.
+fn raw_line_location(item: &ScrapedData) -> String {
+    match &item.source {
+        Some(source) => format!("{} line {}", source, item.line_num),
+        None => format!("Line {}", item.line_num),
+    }
+}
+
+// Raw log line of an event, and where it came from.
+fn render_raw_line(ui: &mut Ui, item: &ScrapedData, dark_mode: bool, settings: &Settings) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(format!("{:20}", "Raw line"))
+            .color(colours::key_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+        ui.label(RichText::new(raw_line_location(item))
+            .color(colours::value_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+    });
+    ui.label(RichText::new(&item.raw)
+        .color(colours::value_colour(dark_mode))
+        .family(egui::FontFamily::Monospace)
+        .size(settings.event_font_size));
+}
+
+// GPS aux value and quality of the GPS fix of an event.
+// Aux is thought to be heading or satellite count.
+fn render_gps_quality(ui: &mut Ui, item: &ScrapedData, dark_mode: bool, settings: &Settings) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(format!("{:20}", "GPS Aux"))
+            .color(colours::gps_key_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+        ui.label(RichText::new(format!("{}", &item.gps_aux))
+            .color(colours::gps_value_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+    });
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(format!("{:20}", "GPS Fix"))
+            .color(colours::gps_key_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+        ui.label(RichText::new(item.gps_fix.label())
+            .color(colours::gps_value_colour(dark_mode))
+            .family(egui::FontFamily::Monospace)
+            .size(settings.event_font_size)
+            .italics());
+    });
+}
+
+// Context menu for an event header, to copy the raw log line.
+fn add_copy_raw_menu(response: &egui::Response, item: &ScrapedData) {
+    response.context_menu(|ui| {
+        if ui.button("Copy raw").clicked() {
+            info!("Copied raw line {} to clipboard.", item.line_num);
+            ui.ctx().copy_text(item.raw.clone());
+            ui.close_menu();
         }
     });
 }
 
 // Function to determine if an event should be shown based on current menu filter settings.
-fn should_show_event(
+pub fn should_show_event(
     item: &ScrapedData,
     show_oot_events: bool,
     show_unsupported_events: bool,
@@ -151,16 +231,16 @@ fn should_show_event(
     show_report_events: bool,
     show_debug_events: bool,
 ) -> bool {
-    match item.event_type.as_str() {
+    match item.event.kind {
         // Always show SIGNON events.
-        "SIGNON" => true,
+        EventKind::SignOn => true,
         // Show TRIP events unless not on trip.
-        "TRIP" => item.on_trip,
+        EventKind::Trip => item.on_trip,
         // Show these events according to the Show menu settings,
         // unless they are out of trip.
-        "REPORT" => show_report_events && item.on_trip,
-        "DEBUG" => show_debug_events && item.on_trip,
-        "INPUT" => show_input_events && item.on_trip,
+        EventKind::Report => show_report_events && item.on_trip,
+        EventKind::Debug => show_debug_events && item.on_trip,
+        EventKind::Input => show_input_events && item.on_trip,
         _ => {
             // For other events, decide based on whether they're on trip.
             // Unless the event is unsupported and show unsupported events is enabled.
@@ -173,7 +253,7 @@ fn should_show_event(
                     return true;
                 }
             }
-            return false;
+            false
         } 
     }
 }
@@ -185,43 +265,44 @@ fn render_trip_section(
     trip_events: &[(usize, &ScrapedData)],
     selected_id: &mut Option<String>,
     dark_mode: bool,
-    show_gps_events: bool)
+    show_gps_events: bool,
+    show_raw_lines: bool)
 {
     // Get application settings in scope.
     let settings: Settings = SETTINGS.lock().unwrap().clone();
 
-    // Generate a unique trip ID.
-    let trip_id = format!("{}", trip_data.trip_num);
+    // Trips are selected by their key, as trip numbers repeat.
+    let trip_id = trip_data.trip_key.to_string();
     let _is_trip_selected = selected_id.as_ref() == Some(&trip_id);
     
     ui.push_id(&trip_id, |ui| {
         let trip_header_response = ui.collapsing(
-            RichText::new(format!("TRIP {:} - {}", trip_data.trip_num, &trip_data.date_time))
+            RichText::new(trip_title(trip_data, &settings))
                 .color(colours::trip_colour(dark_mode))
                 .family(egui::FontFamily::Monospace)
                 .size(settings.trip_font_size),
             |ui| {
                 // Display all events for this trip.
                 for (index, item) in trip_events {
-                    let event_id = format!("event_{}_{}", index, &item.event_type);
-                    let _is_event_selected = selected_id.as_ref() == Some(&trip_data.trip_num);
+                    let event_id = format!("event_{}_{}", index, item.event_type());
+                    let _is_event_selected = selected_id.as_ref() == Some(&trip_id);
                     ui.push_id(&event_id, |ui| {
                         let event_header_response = ui.collapsing(
                             // Event name and the date/time.
-                            RichText::new(format!("{:20} {}",&item.event_type, &item.date_time))
+                            RichText::new(format!("{:20} {}",item.event_type(), display_time(item, &settings)))
                                 .color(colours::event_colour(dark_mode))
                                 .family(egui::FontFamily::Monospace)
                                 .size(settings.event_font_size),
                             |ui| {
                                 // Do the event detail key-value pairs
-                                for (key, value) in &item.ev_detail {
+                                for (key, value) in &item.event.detail() {
                                     ui.horizontal(|ui| {
                                         ui.label(RichText::new(format!("{:20}", key))
                                             .color(colours::key_colour(dark_mode))
                                             .family(egui::FontFamily::Monospace)
                                             .size(settings.event_font_size)
                                             .italics());
-                                        ui.label(RichText::new(format!("{}", value))
+                                        ui.label(RichText::new(value.as_str())
                                             .color(colours::value_colour(dark_mode))
                                             .family(egui::FontFamily::Monospace)
                                             .size(settings.event_font_size)
@@ -281,14 +362,20 @@ fn render_trip_section(
                                             .size(settings.event_font_size)
                                             .italics());
                                     });
+                                    render_gps_quality(ui, item, dark_mode, &settings);
+                                }
+                                if show_raw_lines {
+                                    render_raw_line(ui, item, dark_mode, &settings);
                                 }
                             }
                         );
+                        // Right click the event header to copy the raw line.
+                        add_copy_raw_menu(&event_header_response.header_response, item);
                         
                         // Check if event header was clicked.
                         if event_header_response.header_response.clicked() {
-                            *selected_id = Some(trip_data.trip_num.to_string());
-                            handle_event_selected(&trip_data.trip_num, &item.event_type);
+                            *selected_id = Some(trip_id.clone());
+                            handle_event_selected(&trip_data.trip_num, item.event_type());
                         }
                     });
                 }
@@ -297,7 +384,7 @@ fn render_trip_section(
         
         // Check if trip header was clicked.
         if trip_header_response.header_response.clicked() {
-            *selected_id = Some(trip_data.trip_num.to_string());
+            *selected_id = Some(trip_id.clone());
             handle_trip_selected(&trip_data.trip_num);
         }
     });
@@ -310,12 +397,13 @@ fn render_top_level_event(
     ui: &mut Ui,
     index: usize,
     item: &ScrapedData,
-    dark_mode: bool)
+    dark_mode: bool,
+    show_raw_lines: bool)
 {
     // Get application settings in scope.
     let settings: Settings = SETTINGS.lock().unwrap().clone();
 
-    let event_id = format!("{}_{}", index, &item.event_type);
+    let event_id = format!("{}_{}", index, item.event_type());
 
     // Event is not supported.
     // For unsupprted events attributes are not included.
@@ -323,8 +411,8 @@ fn render_top_level_event(
     if !item.ev_supported {
 
         ui.push_id(&event_id, |ui| {
-            ui.collapsing(
-                RichText::new(format!("{:20} {}",&item.event_type, &item.date_time))
+            let event_header_response = ui.collapsing(
+                RichText::new(format!("{:20} {}",item.event_type(), display_time(item, &settings)))
                     .color(colours::us_event_colour(dark_mode))
                     .family(egui::FontFamily::Monospace)
                     .size(settings.event_font_size),
@@ -380,8 +468,14 @@ fn render_top_level_event(
                             .size(settings.event_font_size)
                             .italics());
                     });
+                    render_gps_quality(ui, item, dark_mode, &settings);
+                    if show_raw_lines {
+                        render_raw_line(ui, item, dark_mode, &settings);
+                    }
                 }
             );
+            // Right click the event header to copy the raw line.
+            add_copy_raw_menu(&event_header_response.header_response, item);
         });
     }
     // Event is not inside trip.
@@ -389,22 +483,22 @@ fn render_top_level_event(
     // or a trip that is supposed to be in a trip but wasn't.
     else if !item.on_trip {
         ui.push_id(&event_id, |ui| {
-            ui.collapsing(
-                RichText::new(format!("{:20} {}",&item.event_type, &item.date_time))
+            let event_header_response = ui.collapsing(
+                RichText::new(format!("{:20} {}",item.event_type(), display_time(item, &settings)))
                     .color(colours::oot_event_colour(dark_mode))
                     .family(egui::FontFamily::Monospace)
                     .size(settings.event_font_size),
                 |ui| {
 
                     // Do the event data key value pairs.
-                    for (key, value) in &item.ev_detail {
+                    for (key, value) in &item.event.detail() {
                         ui.horizontal(|ui| {
                             ui.label(RichText::new(format!("{:20}", key))
                                 .color(colours::key_colour(dark_mode))
                                 .family(egui::FontFamily::Monospace)
                                 .size(settings.event_font_size)
                                 .italics());
-                            ui.label(RichText::new(format!("{}", value))
+                            ui.label(RichText::new(value.as_str())
                                 .color(colours::value_colour(dark_mode))
                                 .family(egui::FontFamily::Monospace)
                                 .size(settings.event_font_size)
@@ -462,30 +556,36 @@ fn render_top_level_event(
                             .size(settings.event_font_size)
                             .italics());
                     });
+                    render_gps_quality(ui, item, dark_mode, &settings);
+                    if show_raw_lines {
+                        render_raw_line(ui, item, dark_mode, &settings);
+                    }
                 }
             );
+            // Right click the event header to copy the raw line.
+            add_copy_raw_menu(&event_header_response.header_response, item);
         });
     }
     else {
         // Possible occuring during a trip or not but is always a top level event.
         // The even is however a supported event.
         ui.push_id(&event_id, |ui| {
-            ui.collapsing(
-                RichText::new(format!("{:20} {}",&item.event_type, &item.date_time))
+            let event_header_response = ui.collapsing(
+                RichText::new(format!("{:20} {}",item.event_type(), display_time(item, &settings)))
                     .color(colours::oot_event_colour(dark_mode))
                     .family(egui::FontFamily::Monospace)
                     .size(settings.event_font_size),
                 |ui| {
 
                     // Do the event data key value pairs.
-                    for (key, value) in &item.ev_detail {
+                    for (key, value) in &item.event.detail() {
                         ui.horizontal(|ui| {
                             ui.label(RichText::new(format!("{:20}", key))
                                 .color(colours::key_colour(dark_mode))
                                 .family(egui::FontFamily::Monospace)
                                 .size(settings.event_font_size)
                                 .italics());
-                            ui.label(RichText::new(format!("{}", value))
+                            ui.label(RichText::new(value.as_str())
                                 .color(colours::value_colour(dark_mode))
                                 .family(egui::FontFamily::Monospace)
                                 .size(settings.event_font_size)
@@ -543,8 +643,14 @@ fn render_top_level_event(
                             .size(settings.event_font_size)
                             .italics());
                     });
+                    render_gps_quality(ui, item, dark_mode, &settings);
+                    if show_raw_lines {
+                        render_raw_line(ui, item, dark_mode, &settings);
+                    }
                 }
             );
+            // Right click the event header to copy the raw line.
+            add_copy_raw_menu(&event_header_response.header_response, item);
         });
     }
 }
@@ -18,12 +18,16 @@ mod scraper;
 mod ui;
 mod help_content;
 mod changelog_content;
+mod parse_issues;
+mod controller_history;
+mod exporting;
 mod log_display;
 mod colours;
 mod gps_plot;
 mod time_series_plot;
 mod helpers_ts;
 mod dataset_ts;
+mod plot_image;
 
 // Application launch.
 #[tokio::main]
@@ -33,11 +37,17 @@ async fn main() -> Result<(), eframe::Error> {
 
     // Logging configuration held in log4rs.yml.
     // Set up logging.
-    let _ = logging::set_up_logging();
+    logging::set_up_logging();
 
     // Get application settings in scope (triggers lazy initialization).
     let _settings = setting_up::SETTINGS.lock().unwrap().clone();
 
+    // Load event definitions (triggers lazy initialization).
+    let _event_registry = setting_up::EVENT_REGISTRY.clone();
+
+    // Load custom scrape rules (triggers lazy initialization).
+    let _rules = setting_up::RULES.clone();
+
     // Get application details in scope.
     let details = setting_up::DETAILS.lock().unwrap().clone();
 
@@ -0,0 +1,150 @@
+// Merging of several logs into one timeline.
+// Controllers roll their log over into .bak files, so a trip
+// can be split across files.
+
+use chrono::{DateTime, Utc};
+
+use crate::event::EventKind;
+use crate::gps::{classify_gps_fixes, GpsFixModel};
+use crate::parser::{trip_key, ControllerChange, Diagnostic, ParsedLog, ScrapedData};
+
+// Merge logs into a single log, with events in unix time order.
+// Each log is given with a name for its events and diagnostics, usually the file name.
+// Events that appear in more than one log are only kept once,
+// and trips are numbered again so that trips split across logs are joined.
+// GPS fixes are classified again, in the merged order.
+pub fn merge_logs(logs: Vec<(String, ParsedLog)>) -> ParsedLog {
+    let mut merged = ParsedLog::default();
+    let mut events: Vec<(usize, ScrapedData)> = Vec::new();
+    let mut history: Vec<(Option<DateTime<Utc>>, ControllerChange)> = Vec::new();
+
+    for (source_num, (source, log)) in logs.into_iter().enumerate() {
+        merge_controller_detail(&mut merged.controller_id, log.controller_id, "controllers", &mut merged.diagnostics);
+        merge_controller_detail(&mut merged.controller_fw, log.controller_fw, "firmware versions", &mut merged.diagnostics);
+
+        // Line numbers aren't compared, the same line has a different number in each log.
+        let sort_times = history_sort_times(&log.controller_history);
+        for (sort_time, change) in sort_times.into_iter().zip(log.controller_history) {
+            let seen = history.iter()
+                .any(|(_, other)| other.date_time == change.date_time && other.detail == change.detail);
+            if !seen {
+                history.push((sort_time, change));
+            }
+        }
+        merged.diagnostics.extend(log.diagnostics.into_iter().map(|diagnostic| Diagnostic {
+            source: Some(source.clone()),
+            ..diagnostic
+        }));
+        events.extend(log.scrapings.into_iter().map(|event| (source_num, ScrapedData {
+            source: Some(source.clone()),
+            ..event
+        })));
+    }
+
+    // Stable sort, so events at the same time stay in log order.
+    events.sort_by_key(|(_, event)| event.unix_time);
+
+    // Drop events already seen at the same time in another log.
+    // Repeats within a log are real events and are kept.
+    let mut kept: Vec<(usize, ScrapedData)> = Vec::with_capacity(events.len());
+    for (source_num, event) in events {
+        let duplicate = kept.iter()
+            .rev()
+            .take_while(|(_, other)| other.unix_time == event.unix_time)
+            .any(|(other_num, other)| *other_num != source_num && same_event(other, &event));
+        if !duplicate {
+            kept.push((source_num, event));
+        }
+    }
+
+    merged.scrapings = kept.into_iter().map(|(_, event)| event).collect();
+    assign_trips(&mut merged.scrapings);
+    assign_firmware(&mut merged.scrapings);
+    classify_gps_fixes(&mut merged.scrapings, GpsFixModel::default());
+
+    // Stable sort, so changes at the same time stay in log order.
+    history.sort_by_key(|(sort_time, _)| *sort_time);
+    merged.controller_history = history.into_iter().map(|(_, change)| change).collect();
+    merged
+}
+
+// Times to sort a log's controller changes by.
+// Changes without a timestamp take the time of the change before them
+// (or after, at the start of the log), so they keep their place in the log.
+fn history_sort_times(history: &[ControllerChange]) -> Vec<Option<DateTime<Utc>>> {
+    let mut last_time = history.iter().find_map(|change| change.timestamp);
+    history.iter()
+        .map(|change| {
+            last_time = change.timestamp.or(last_time);
+            last_time
+        })
+        .collect()
+}
+
+// Number events with the trip they are in.
+// A SIGNON starts a trip, and the TRIP event ends it.
+// Matches how trips are numbered when a single log is parsed.
+pub fn assign_trips(scrapings: &mut [ScrapedData]) {
+    let mut trip_num_id = String::new();
+    let mut trip_key_id = String::new();
+    for event in scrapings {
+        match event.event.kind {
+            EventKind::SignOn => {
+                // SIGNON events hold the trip number from the log.
+                trip_num_id = event.trip_num.clone();
+                trip_key_id = trip_key(&event.trip_num, event.unix_time);
+                event.trip_key = trip_key_id.clone();
+            }
+            EventKind::Trip => {
+                event.trip_num = std::mem::take(&mut trip_num_id);
+                event.trip_key = std::mem::take(&mut trip_key_id);
+            }
+            _ => {
+                event.trip_num = trip_num_id.clone();
+                event.trip_key = trip_key_id.clone();
+            }
+        }
+    }
+}
+
+// Tag events before the first SWSTART of a log with the
+// firmware from the end of the log before it.
+fn assign_firmware(scrapings: &mut [ScrapedData]) {
+    let mut firmware: Option<String> = None;
+    for event in scrapings {
+        match &event.firmware {
+            Some(event_firmware) => firmware = Some(event_firmware.clone()),
+            None => event.firmware = firmware.clone(),
+        }
+    }
+}
+
+// Check if two events came from the same log line.
+// Trip numbers aren't compared as they depend on where the log started.
+fn same_event(a: &ScrapedData, b: &ScrapedData) -> bool {
+    a.unix_time == b.unix_time
+        && a.date_time == b.date_time
+        && a.event == b.event
+        && a.gps_locn.lat == b.gps_locn.lat
+        && a.gps_locn.lon == b.gps_locn.lon
+        && a.gps_rssi == b.gps_rssi
+        && a.gps_speed == b.gps_speed
+}
+
+// Keep the first controller id or firmware version found,
+// noting if the logs don't agree.
+fn merge_controller_detail(merged: &mut Option<String>, detail: Option<String>, what: &str, diagnostics: &mut Vec<Diagnostic>) {
+    match (merged.as_ref(), detail) {
+        (None, detail) => *merged = detail,
+        (Some(first), Some(detail)) if *first != detail => {
+            diagnostics.push(Diagnostic {
+                line_num: 0,
+                raw: String::new(),
+                reason: format!("Logs are from different {} ({} and {}).", what, first, detail),
+                event_type: None,
+                source: None,
+            });
+        }
+        _ => {}
+    }
+}
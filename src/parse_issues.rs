@@ -0,0 +1,68 @@
+// Place for parse issues content.
+// Refer to ui.rs for associated ui definitions.
+
+use eframe::egui;
+use egui::RichText;
+use std::collections::BTreeMap;
+
+use crate::app::MyApp;
+use crate::colours;
+
+// Render the parse issues, with counts per event type,
+// followed by each issue with its line number and raw text.
+pub fn draw_parse_issues_content(ui: &mut egui::Ui, app: &mut MyApp) {
+    let diagnostics = &app.scraper.diagnostics;
+
+    if diagnostics.is_empty() {
+        ui.label("No parse issues.");
+        return;
+    }
+
+    // Count the issues for each event type.
+    // Issues not related to an event are counted against the log.
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        let event_type = diagnostic.event_type.as_deref().unwrap_or("(log)");
+        *counts.entry(event_type).or_insert(0) += 1;
+    }
+
+    ui.strong(format!("{} parse issues", diagnostics.len()));
+    egui::Grid::new("parse_issue_counts")
+        .striped(true)
+        .show(ui, |ui| {
+            for (event_type, count) in &counts {
+                ui.label(*event_type);
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+    ui.separator();
+
+    // List all the issues.
+    // Only the visible rows are laid out, as there can be a lot of them.
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    egui::ScrollArea::both()
+        .auto_shrink([false, false])
+        .show_rows(ui, row_height, diagnostics.len(), |ui, row_range| {
+            for diagnostic in &diagnostics[row_range] {
+                ui.horizontal(|ui| {
+                    // Source file is only set when several files are loaded.
+                    if let Some(source) = &diagnostic.source {
+                        ui.label(RichText::new(source)
+                            .color(colours::key_colour(app.dark_mode))
+                            .family(egui::FontFamily::Monospace));
+                    }
+                    ui.label(RichText::new(format!("{:>8}", diagnostic.line_num))
+                        .family(egui::FontFamily::Monospace));
+                    ui.label(RichText::new(format!("{:<18}", diagnostic.event_type.as_deref().unwrap_or("")))
+                        .color(colours::event_colour(app.dark_mode))
+                        .family(egui::FontFamily::Monospace));
+                    ui.label(RichText::new(&diagnostic.reason)
+                        .family(egui::FontFamily::Monospace));
+                    ui.label(RichText::new(&diagnostic.raw)
+                        .color(colours::key_colour(app.dark_mode))
+                        .family(egui::FontFamily::Monospace));
+                });
+            }
+        });
+}
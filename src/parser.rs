@@ -0,0 +1,513 @@
+// Log file parser.
+// Scrapes controller details and events from a log.
+
+use log::{info, warn};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead};
+
+use std::sync::Arc;
+
+use crate::csv_log::{CsvFormat, CsvMapping};
+use crate::event::{Event, EventKind};
+use crate::events::EventRegistry;
+use crate::format::{detect_format, ControllerFormat, EventRecord, LineEvent, LineMetadata, LogFormat, DETECT_LINES};
+use crate::gps::{GpsFix, GpsFixClassifier};
+use crate::rules::{RuleMatch, RuleSet};
+use crate::timestamp::TimestampFormat;
+
+// GPS location (lat, lon)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GpsLocation {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+// Data that is scraped.
+// Date time is as logged, timestamp is None if it couldn't be read.
+// Firmware is the version the controller was running at the time,
+// None if no SWSTART has been seen yet.
+// GPS aux is the GPS field between longitude and RSSI, its meaning
+// isn't known, but it is thought to be heading or satellite count.
+// Source is the name of the log the event came from, if known,
+// line number is 1 based, and raw is the line as logged.
+// Trip number is as logged, and restarts when the controller is power cycled,
+// trip key is unique to the trip, and is used to select it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapedData {
+    pub source: Option<String>,
+    pub line_num: usize,
+    pub raw: String,
+    pub date_time: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub unix_time: u64,
+    pub on_trip: bool,
+    pub trip_num: String,
+    pub trip_key: String,
+    pub firmware: Option<String>,
+    pub event: Event,
+    pub ev_supported: bool,
+    pub gps_rssi: u32,
+    pub gps_speed: u32,
+    pub gps_aux: u32,
+    pub gps_locn: GpsLocation,
+    pub gps_fix: GpsFix,
+}
+
+impl ScrapedData {
+    // Event type name as it appears in the log.
+    pub fn event_type(&self) -> &str {
+        self.event.name()
+    }
+}
+
+// A problem found while parsing the log.
+// Line number is 1 based, or 0 for problems with the log as a whole.
+// Event type is None if the problem isn't with an event.
+// Source is the name of the log, if known.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub line_num: usize,
+    pub raw: String,
+    pub reason: String,
+    pub event_type: Option<String>,
+    pub source: Option<String>,
+}
+
+// A controller id (from a UNIT line) or firmware version
+// (from a SWSTART line) found in the log.
+// Line number is 1 based, timestamp is None if the date time couldn't be read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControllerChange {
+    pub line_num: usize,
+    pub date_time: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub detail: ControllerDetail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerDetail {
+    Id(String),
+    Firmware(String),
+}
+
+// Everything scraped from a single log.
+// Controller id and firmware are the first found in the log,
+// None if not found. The history has every one found, in log order.
+// Diagnostics are problems with lines that couldn't be fully scraped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParsedLog {
+    pub controller_id: Option<String>,
+    pub controller_fw: Option<String>,
+    pub controller_history: Vec<ControllerChange>,
+    pub scrapings: Vec<ScrapedData>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Streaming log parser.
+// Lines are read by the log format, so the log only needs to be read in a single pass.
+// When given several formats, the first lines are held until the format is detected.
+#[derive(Debug, Clone)]
+pub struct LogParser {
+    format: Box<dyn LogFormat>,
+    candidates: Vec<Box<dyn LogFormat>>,
+    held_lines: Vec<(usize, String)>,
+    registry: Arc<EventRegistry>,
+    timestamp_format: TimestampFormat,
+    source: Option<String>,
+    line_num: usize,
+    trip_num_id: String,
+    trip_key_id: String,
+    firmware: Option<String>,
+    gps_fixes: GpsFixClassifier,
+    rules: Option<Arc<RuleSet>>,
+    last_time: Option<(String, Option<DateTime<Utc>>, u64)>,
+    log: ParsedLog,
+}
+
+impl LogParser {
+    // Parser using the default event definitions.
+    pub fn new() -> Self {
+        Self::with_registry(EventRegistry::default_registry())
+    }
+
+    // Parser using the given event definitions.
+    // Reads controller logs unless given another format.
+    pub fn with_registry(registry: Arc<EventRegistry>) -> Self {
+        Self {
+            format: Box::new(ControllerFormat::new(&registry)),
+            candidates: Vec::new(),
+            held_lines: Vec::new(),
+            registry,
+            timestamp_format: TimestampFormat::default(),
+            source: None,
+            line_num: 0,
+            trip_num_id: "".to_string(),
+            trip_key_id: "".to_string(),
+            firmware: None,
+            gps_fixes: GpsFixClassifier::default(),
+            rules: None,
+            last_time: None,
+            log: ParsedLog::default(),
+        }
+    }
+
+    // Read timestamps in the given format.
+    // Defaults to day first dates in UTC.
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    // Read the log in the given format.
+    pub fn with_format(mut self, format: Box<dyn LogFormat>) -> Self {
+        self.format = format;
+        self.candidates.clear();
+        self
+    }
+
+    // Detect which of the formats the log is in, from its first lines.
+    // Falls back to the first format if none of them match.
+    pub fn with_formats(mut self, formats: Vec<Box<dyn LogFormat>>) -> Self {
+        if let Some(first) = formats.first() {
+            self.format = first.clone();
+            self.candidates = formats;
+        }
+        self
+    }
+
+    // Read the log as CSV, with the given columns.
+    pub fn with_csv(self, mapping: CsvMapping) -> Self {
+        self.with_format(Box::new(CsvFormat::new(mapping)))
+    }
+
+    // Scrape lines that aren't events with custom rules.
+    pub fn with_rules(mut self, rules: Arc<RuleSet>) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    // Name the log being parsed, usually the file name.
+    // Events and diagnostics are tagged with it.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    // Number of events scraped so far.
+    pub fn event_count(&self) -> usize {
+        self.log.scrapings.len()
+    }
+
+    // Scrape a single line of the log.
+    pub fn parse_line(&mut self, line: &str) {
+        self.line_num += 1;
+
+        if self.candidates.is_empty() {
+            self.scrape_line(line);
+            return;
+        }
+
+        // Hold lines until the format is known.
+        self.held_lines.push((self.line_num, line.to_string()));
+        let lines: Vec<&str> = self.held_lines.iter().map(|(_, line)| line.as_str()).collect();
+        if let Some(format) = detect_format(&self.candidates, &lines) {
+            self.format = format;
+        } else if self.held_lines.len() < DETECT_LINES {
+            return;
+        }
+        self.release_held_lines();
+    }
+
+    // Scrape a single line of the log, as read.
+    // The line ending is ignored. Characters that aren't UTF-8 are
+    // replaced and noted, so that a corrupted line doesn't stop the load.
+    pub fn parse_line_bytes(&mut self, line: &[u8]) {
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        match std::str::from_utf8(line) {
+            Ok(line) => self.parse_line(line),
+            Err(_) => {
+                let line = String::from_utf8_lossy(line);
+                self.parse_line(&line);
+                self.add_diagnostic(&line, "Invalid characters replaced.".to_string(), None);
+            }
+        }
+    }
+
+    // Skip a line that can't be scraped, noting why.
+    pub fn reject_line(&mut self, line: &str, reason: &str) {
+        self.line_num += 1;
+        self.add_diagnostic(line, reason.to_string(), None);
+    }
+
+    // Stop detecting the format, scraping any held lines
+    // with the format found so far or the fallback.
+    // Used when no more lines are expected for a while.
+    pub fn settle_format(&mut self) {
+        if !self.candidates.is_empty() {
+            self.release_held_lines();
+        }
+    }
+
+    // Scrape the lines held while detecting the format,
+    // with the format found or the fallback.
+    fn release_held_lines(&mut self) {
+        self.candidates.clear();
+        let held_lines = std::mem::take(&mut self.held_lines);
+        let line_num = self.line_num;
+        for (held_num, line) in held_lines {
+            self.line_num = held_num;
+            self.scrape_line(&line);
+        }
+        self.line_num = line_num;
+    }
+
+    // Scrape a line with the log format.
+    // Every controller number and firmware version is kept, as the controller
+    // can be swapped, or the firmware upgraded, part way through a log.
+    // Note that a SWSTART line is also an EVENT line,
+    // and the event is tagged with the new firmware.
+    fn scrape_line(&mut self, line: &str) {
+        if let Some(metadata) = self.format.metadata(line) {
+            self.add_metadata(metadata);
+        }
+
+        match self.format.parse_line(line, &self.timestamp_format) {
+            LineEvent::None => {
+                if let Some(rule_match) = self.rules.as_ref().and_then(|rules| rules.scrape(line)) {
+                    self.add_rule_event(line, rule_match);
+                }
+            }
+            LineEvent::Event(record) => self.add_event(line, record),
+            LineEvent::Invalid { reason, event_type } => self.add_diagnostic(line, reason, event_type.as_deref()),
+        }
+    }
+
+    // Take everything scraped since the last take,
+    // leaving the parser ready for more lines.
+    // Used for logs that are still being written.
+    pub fn take_parsed(&mut self) -> ParsedLog {
+        ParsedLog {
+            controller_id: self.log.controller_id.clone(),
+            controller_fw: self.log.controller_fw.clone(),
+            controller_history: std::mem::take(&mut self.log.controller_history),
+            scrapings: std::mem::take(&mut self.log.scrapings),
+            diagnostics: std::mem::take(&mut self.log.diagnostics),
+        }
+    }
+
+    // Finish parsing and return everything that was scraped.
+    pub fn finish(mut self) -> ParsedLog {
+        self.settle_format();
+        if self.log.controller_id.is_none() {
+            info!("Failed to find controller serial number.");
+            self.add_log_diagnostic("Controller serial number not found.");
+        }
+        if self.log.controller_fw.is_none() {
+            info!("Failed to find controller firmware version.");
+            self.add_log_diagnostic("Controller firmware version not found.");
+        }
+        self.log
+    }
+
+    // Record a problem with the current line.
+    fn add_diagnostic(&mut self, line: &str, reason: String, event_type: Option<&str>) {
+        warn!("Line {}: {}", self.line_num, reason);
+        self.log.diagnostics.push(Diagnostic {
+            line_num: self.line_num,
+            raw: line.to_string(),
+            reason,
+            event_type: event_type.map(|event_type| event_type.to_string()),
+            source: self.source.clone(),
+        });
+    }
+
+    // Record a problem with the log as a whole.
+    fn add_log_diagnostic(&mut self, reason: &str) {
+        self.log.diagnostics.push(Diagnostic {
+            line_num: 0,
+            raw: String::new(),
+            reason: reason.to_string(),
+            event_type: None,
+            source: self.source.clone(),
+        });
+    }
+
+    // Record a controller id or firmware version found in the log.
+    fn add_metadata(&mut self, metadata: LineMetadata) {
+        match &metadata.detail {
+            ControllerDetail::Id(unit) => {
+                info!("Found controller: {:?}", unit);
+                if self.log.controller_id.is_none() {
+                    self.log.controller_id = Some(unit.clone());
+                }
+            }
+            ControllerDetail::Firmware(firmware) => {
+                info!("Found controller firmware: {:?}", firmware);
+                self.firmware = Some(firmware.clone());
+                if self.log.controller_fw.is_none() {
+                    self.log.controller_fw = Some(firmware.clone());
+                }
+            }
+        }
+        self.add_controller_change(&metadata.date, &metadata.time, metadata.detail);
+    }
+
+    // Record a controller id or firmware version in the history.
+    fn add_controller_change(&mut self, date: &str, time: &str, detail: ControllerDetail) {
+        self.log.controller_history.push(ControllerChange {
+            line_num: self.line_num,
+            date_time: format!("{} {}", date, time),
+            timestamp: self.timestamp_format.parse_utc(date, time),
+            detail,
+        });
+    }
+
+    // Add an event, however it was logged.
+    fn add_event(&mut self, line: &str, record: EventRecord) {
+        let EventRecord { date, time, trip_id, unix_time, gps_locn, gps_aux, gps_rssi, gps_speed, event_type, detail } = record;
+
+        // Events are kept even if the date time can't be read,
+        // as the unix time is still available.
+        let timestamp = self.timestamp_format.parse_utc(&date, &time);
+        if timestamp.is_none() {
+            self.add_diagnostic(line, format!("Invalid date time '{} {}'.", date, time), Some(&event_type));
+        }
+
+        let gps_fix = self.gps_fixes.classify(unix_time, &gps_locn, gps_rssi);
+
+        // Decode the event sub-data.
+        let mut on_trip = true;
+        let mut ev_supported = true;
+        let mut issues = Vec::new();
+        let ev_fields = self.registry.ungroup_event_data(&event_type, &detail, &mut on_trip, &mut ev_supported, &mut issues);
+        for reason in issues {
+            self.add_diagnostic(line, reason, Some(&event_type));
+        }
+
+        // Keep track of on-trip state.
+        // SIGNON sets TRIP clears.
+        if event_type == "SIGNON" {
+            // Save the trip number to apply to other events.
+            self.trip_key_id = trip_key(&trip_id, unix_time);
+            self.trip_num_id = trip_id;
+        }
+
+        // Create and populate the struct.
+        // Initialise events to be supported; change later if not.
+        let ev_data = ScrapedData {
+            source: self.source.clone(),
+            line_num: self.line_num,
+            raw: line.to_string(),
+            date_time: format!("{} {}", date, time),
+            timestamp,
+            unix_time,
+            on_trip,
+            trip_num: self.trip_num_id.clone(),
+            trip_key: self.trip_key_id.clone(),
+            firmware: self.firmware.clone(),
+            event: Event {
+                kind: EventKind::from_name(&event_type),
+                fields: ev_fields,
+            },
+            ev_supported,
+            gps_locn,
+            gps_fix,
+            gps_rssi,
+            gps_speed,
+            gps_aux,
+        };
+
+        // Push the struct onto the vector.
+        self.last_time = Some((ev_data.date_time.clone(), timestamp, unix_time));
+        self.log.scrapings.push(ev_data);
+
+        // Clear on trip flag after TRIP event.
+        // This makes TRIP still part of the trip.
+        if event_type == "TRIP" {
+            // Clear the saved trip number as
+            // following events are out of trip.
+            self.trip_num_id = "".to_string();
+            self.trip_key_id = "".to_string();
+        }
+    }
+
+    // Add an event from a custom rule.
+    // Lines without a valid date and time take the time of the event before,
+    // and there is no GPS location. Rule events are in a trip if they come
+    // between a SIGNON and its TRIP, as for other events.
+    // Matches before any timed event can't be placed, so are reported instead.
+    fn add_rule_event(&mut self, line: &str, rule_match: RuleMatch) {
+        let RuleMatch { name, date, time, fields } = rule_match;
+
+        let matched_time = match (date, time) {
+            (Some(date), Some(time)) => {
+                let timestamp = self.timestamp_format.parse_utc(&date, &time);
+                if timestamp.is_none() {
+                    self.add_diagnostic(line, format!("Invalid date time '{} {}'.", date, time), Some(&name));
+                }
+                timestamp.map(|timestamp| (format!("{} {}", date, time), Some(timestamp), timestamp.timestamp().max(0) as u64))
+            }
+            _ => None,
+        };
+        let Some((date_time, timestamp, unix_time)) = matched_time.or_else(|| self.last_time.clone()) else {
+            self.add_diagnostic(line, "Rule match before any event time.".to_string(), Some(&name));
+            return;
+        };
+
+        let ev_data = ScrapedData {
+            source: self.source.clone(),
+            line_num: self.line_num,
+            raw: line.to_string(),
+            date_time,
+            timestamp,
+            unix_time,
+            on_trip: !self.trip_num_id.is_empty(),
+            trip_num: self.trip_num_id.clone(),
+            trip_key: self.trip_key_id.clone(),
+            firmware: self.firmware.clone(),
+            event: Event {
+                kind: EventKind::Custom(name),
+                fields,
+            },
+            ev_supported: true,
+            gps_locn: GpsLocation { lat: 0.0, lon: 0.0 },
+            gps_fix: GpsFix::NoFix,
+            gps_rssi: 0,
+            gps_speed: 0,
+            gps_aux: 0,
+        };
+        self.last_time = Some((ev_data.date_time.clone(), timestamp, unix_time));
+        self.log.scrapings.push(ev_data);
+    }
+}
+
+// Key for a trip, from the trip number and the time of its SIGNON.
+pub(crate) fn trip_key(trip_num: &str, unix_time: u64) -> String {
+    format!("{}@{}", trip_num, unix_time)
+}
+
+impl Default for LogParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Parse a log from any buffered reader in a single pass.
+// Returns an error only if the log couldn't be read.
+pub fn parse_log<R: BufRead>(mut reader: R) -> io::Result<ParsedLog> {
+    let mut parser = LogParser::new();
+
+    info!("Searching log for controller details and events.");
+
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        parser.parse_line_bytes(&line);
+        line.clear();
+    }
+
+    Ok(parser.finish())
+}
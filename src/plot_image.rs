@@ -0,0 +1,391 @@
+// Saving plots as PNG or SVG images.
+// Plots are drawn off screen with their usual painter code, so
+// images have the whole plot, not just the part scrolled into view.
+// SVG images are written from the painted shapes, and PNG images
+// are rasterised from the same triangles egui draws on screen.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use eframe::egui;
+use egui::epaint::{ClippedPrimitive, ClippedShape, ColorMode, ImageData, Primitive, Shape, StrokeKind, TextShape, TextureId};
+use egui::{Color32, ColorImage, FontFamily, Pos2, Rect, Stroke, Vec2};
+
+use scraper::xml_escape;
+
+// Image formats for plots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    // Name for the menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "Save PNG...",
+            ImageFormat::Svg => "Save SVG...",
+        }
+    }
+
+    // File extension.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+// PNG resolutions, as multiples of the size on screen.
+pub const IMAGE_SCALES: [f32; 3] = [1.0, 2.0, 3.0];
+
+// Draw a plot off screen and save it as an image.
+// The plot is drawn by 'draw', on a painter covering 'size' points.
+// PNG images have 'scale' pixels per point, SVG images are in points.
+pub fn save_plot_image(
+    path: &Path,
+    format: ImageFormat,
+    size: Vec2,
+    scale: f32,
+    dark_mode: bool,
+    draw: impl Fn(&egui::Painter),
+) -> io::Result<()> {
+    let scale = match format {
+        ImageFormat::Png => scale,
+        ImageFormat::Svg => 1.0,
+    };
+    let ctx = egui::Context::default();
+    let output = render(&ctx, size, scale, dark_mode, draw);
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::Png => {
+            let mut textures = HashMap::new();
+            for (id, delta) in output.textures_delta.set {
+                apply_texture_delta(&mut textures, id, delta);
+            }
+            let primitives = ctx.tessellate(output.shapes, scale);
+            let image = rasterise(&primitives, &textures, size, scale);
+            let size = [image.size[0] as u32, image.size[1] as u32];
+            image::write_buffer_with_format(&mut writer, image.as_raw(), size[0], size[1], image::ColorType::Rgba8, image::ImageFormat::Png)
+                .map_err(io::Error::other)?;
+        }
+        ImageFormat::Svg => write_svg(&mut writer, &output.shapes, size)?,
+    }
+    writer.flush()
+}
+
+// Run a frame of an off screen context, with the plot
+// drawn over the window background colour.
+fn render(ctx: &egui::Context, size: Vec2, scale: f32, dark_mode: bool, draw: impl Fn(&egui::Painter)) -> egui::FullOutput {
+    if dark_mode {
+        ctx.set_visuals(egui::Visuals::dark());
+    } else {
+        ctx.set_visuals(egui::Visuals::light());
+    }
+
+    let screen_rect = Rect::from_min_size(Pos2::ZERO, size);
+    let mut input = egui::RawInput {
+        screen_rect: Some(screen_rect),
+        ..Default::default()
+    };
+    input.viewports.entry(egui::ViewportId::ROOT).or_default().native_pixels_per_point = Some(scale);
+
+    ctx.run(input, |ctx| {
+        let painter = ctx.layer_painter(egui::LayerId::background());
+        painter.rect_filled(screen_rect, 0.0, ctx.style().visuals.panel_fill);
+        draw(&painter);
+    })
+}
+
+// Update a texture from the frame output.
+// The font atlas is the only texture the plots use.
+fn apply_texture_delta(textures: &mut HashMap<TextureId, ColorImage>, id: TextureId, delta: egui::epaint::ImageDelta) {
+    let image = match delta.image {
+        ImageData::Color(image) => (*image).clone(),
+        ImageData::Font(font) => ColorImage {
+            size: font.size,
+            pixels: font.srgba_pixels(None).collect(),
+        },
+    };
+    match delta.pos {
+        None => {
+            textures.insert(id, image);
+        }
+        Some([x, y]) => {
+            if let Some(texture) = textures.get_mut(&id) {
+                for row in 0..image.size[1] {
+                    for col in 0..image.size[0] {
+                        texture[(x + col, y + row)] = image[(col, row)];
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Rasterise tessellated meshes to an image.
+// Colours are premultiplied, and blended as egui does on screen.
+fn rasterise(primitives: &[ClippedPrimitive], textures: &HashMap<TextureId, ColorImage>, size: Vec2, scale: f32) -> ColorImage {
+    let width = (size.x * scale).round() as usize;
+    let height = (size.y * scale).round() as usize;
+    let mut image = ColorImage::new([width, height], Color32::TRANSPARENT);
+
+    for primitive in primitives {
+        let Primitive::Mesh(mesh) = &primitive.primitive else {
+            continue;
+        };
+        let Some(texture) = textures.get(&mesh.texture_id) else {
+            continue;
+        };
+        let clip = Rect::from_min_max(primitive.clip_rect.min * scale, primitive.clip_rect.max * scale)
+            .intersect(Rect::from_min_size(Pos2::ZERO, egui::vec2(width as f32, height as f32)));
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+            let [pa, pb, pc] = [a.pos, b.pos, c.pos].map(|pos| pos * scale);
+            let area = edge(pa, pb, pc);
+            if area.abs() < f32::EPSILON {
+                continue;
+            }
+
+            let bounds = Rect::from_points(&[pa, pb, pc]).intersect(clip);
+            if !bounds.is_positive() {
+                continue;
+            }
+            for y in bounds.min.y.floor() as usize..bounds.max.y.ceil() as usize {
+                for x in bounds.min.x.floor() as usize..bounds.max.x.ceil() as usize {
+                    let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                    let weights = [edge(pb, pc, p) / area, edge(pc, pa, p) / area, edge(pa, pb, p) / area];
+                    if weights.iter().any(|w| *w < 0.0) {
+                        continue;
+                    }
+
+                    let uv = a.uv.to_vec2() * weights[0] + b.uv.to_vec2() * weights[1] + c.uv.to_vec2() * weights[2];
+                    let texel = sample(texture, uv);
+                    let colours = [a.color, b.color, c.color];
+                    let src: [f32; 4] = std::array::from_fn(|i| {
+                        let vertex = colours.iter().zip(weights).map(|(colour, w)| colour[i] as f32 * w).sum::<f32>();
+                        vertex * texel[i] / 255.0
+                    });
+
+                    let dst = &mut image[(x, y)];
+                    let keep = 1.0 - src[3] / 255.0;
+                    *dst = Color32::from_rgba_premultiplied(
+                        (src[0] + dst.r() as f32 * keep).round() as u8,
+                        (src[1] + dst.g() as f32 * keep).round() as u8,
+                        (src[2] + dst.b() as f32 * keep).round() as u8,
+                        (src[3] + dst.a() as f32 * keep).round() as u8,
+                    );
+                }
+            }
+        }
+    }
+    image
+}
+
+// Twice the signed area of a triangle.
+fn edge(a: Pos2, b: Pos2, c: Pos2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// Bilinear sample of a texture, with components from 0 to 255.
+fn sample(texture: &ColorImage, uv: Vec2) -> [f32; 4] {
+    let [width, height] = texture.size;
+    let x = (uv.x * width as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+    let y = (uv.y * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x.fract(), y.fract());
+
+    std::array::from_fn(|i| {
+        let top = texture[(x0, y0)][i] as f32 * (1.0 - fx) + texture[(x1, y0)][i] as f32 * fx;
+        let bottom = texture[(x0, y1)][i] as f32 * (1.0 - fx) + texture[(x1, y1)][i] as f32 * fx;
+        top * (1.0 - fy) + bottom * fy
+    })
+}
+
+// Write painted shapes as an SVG image.
+// Shapes clipped to less than the image are put in a clipped group.
+fn write_svg<W: Write>(writer: &mut W, shapes: &[ClippedShape], size: Vec2) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        size.x, size.y
+    )?;
+
+    let image_rect = Rect::from_min_size(Pos2::ZERO, size);
+    for (index, clipped) in shapes.iter().enumerate() {
+        if clipped.clip_rect.contains_rect(image_rect) {
+            write_svg_shape(writer, &clipped.shape)?;
+        } else {
+            let clip = clipped.clip_rect;
+            writeln!(
+                writer,
+                r#"<clipPath id="clip{}"><rect x="{}" y="{}" width="{}" height="{}"/></clipPath>"#,
+                index, clip.min.x, clip.min.y, clip.width(), clip.height()
+            )?;
+            writeln!(writer, r#"<g clip-path="url(#clip{})">"#, index)?;
+            write_svg_shape(writer, &clipped.shape)?;
+            writeln!(writer, "</g>")?;
+        }
+    }
+    writeln!(writer, "</svg>")
+}
+
+// Write a shape as SVG elements.
+// Meshes and paint callbacks aren't used by the plots, so are left out.
+fn write_svg_shape<W: Write>(writer: &mut W, shape: &Shape) -> io::Result<()> {
+    match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                write_svg_shape(writer, shape)?;
+            }
+        }
+        Shape::Circle(circle) => writeln!(
+            writer,
+            r#"<circle cx="{}" cy="{}" r="{}" {} {}/>"#,
+            circle.center.x, circle.center.y, circle.radius,
+            colour_attrs("fill", circle.fill), stroke_attrs(circle.stroke)
+        )?,
+        Shape::Ellipse(ellipse) => writeln!(
+            writer,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" {} {}/>"#,
+            ellipse.center.x, ellipse.center.y, ellipse.radius.x, ellipse.radius.y,
+            colour_attrs("fill", ellipse.fill), stroke_attrs(ellipse.stroke)
+        )?,
+        Shape::LineSegment { points: [a, b], stroke } => writeln!(
+            writer,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            a.x, a.y, b.x, b.y, stroke_attrs(*stroke)
+        )?,
+        Shape::Path(path) => {
+            let stroke = solid_stroke(path.stroke.width, &path.stroke.color);
+            let (element, fill) = if path.closed { ("polygon", path.fill) } else { ("polyline", Color32::TRANSPARENT) };
+            writeln!(
+                writer,
+                r#"<{} points="{}" {} {}/>"#,
+                element, svg_points(&path.points), colour_attrs("fill", fill), stroke_attrs(stroke)
+            )?;
+        }
+        Shape::Rect(rect) => {
+            let radius = f32::from(rect.corner_radius.nw);
+            if rect.fill.a() > 0 {
+                write_svg_rect(writer, rect.rect, radius, &colour_attrs("fill", rect.fill))?;
+            }
+            if !rect.stroke.is_empty() {
+                // Strokes are inside, centred on or outside the rect edge.
+                let offset = match rect.stroke_kind {
+                    StrokeKind::Inside => -rect.stroke.width / 2.0,
+                    StrokeKind::Middle => 0.0,
+                    StrokeKind::Outside => rect.stroke.width / 2.0,
+                };
+                let attrs = format!(r#"fill="none" {}"#, stroke_attrs(rect.stroke));
+                write_svg_rect(writer, rect.rect.expand(offset), radius, &attrs)?;
+            }
+        }
+        Shape::Text(text) => write_svg_text(writer, text)?,
+        Shape::QuadraticBezier(bezier) => {
+            let [p0, p1, p2] = bezier.points;
+            let close = if bezier.closed { " Z" } else { "" };
+            writeln!(
+                writer,
+                r#"<path d="M {} {} Q {} {} {} {}{}" {} {}/>"#,
+                p0.x, p0.y, p1.x, p1.y, p2.x, p2.y, close,
+                colour_attrs("fill", bezier.fill), stroke_attrs(solid_stroke(bezier.stroke.width, &bezier.stroke.color))
+            )?;
+        }
+        Shape::CubicBezier(bezier) => {
+            let [p0, p1, p2, p3] = bezier.points;
+            let close = if bezier.closed { " Z" } else { "" };
+            writeln!(
+                writer,
+                r#"<path d="M {} {} C {} {} {} {} {} {}{}" {} {}/>"#,
+                p0.x, p0.y, p1.x, p1.y, p2.x, p2.y, p3.x, p3.y, close,
+                colour_attrs("fill", bezier.fill), stroke_attrs(solid_stroke(bezier.stroke.width, &bezier.stroke.color))
+            )?;
+        }
+        Shape::Noop | Shape::Mesh(_) | Shape::Callback(_) => (),
+    }
+    Ok(())
+}
+
+// Write a rect, with rounded corners if it has them.
+fn write_svg_rect<W: Write>(writer: &mut W, rect: Rect, radius: f32, attrs: &str) -> io::Result<()> {
+    writeln!(
+        writer,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" {}/>"#,
+        rect.min.x, rect.min.y, rect.width(), rect.height(), radius, attrs
+    )
+}
+
+// Write laid out text, a text element per row.
+// Rows keep their laid out width, so labels line up as on screen.
+fn write_svg_text<W: Write>(writer: &mut W, text: &TextShape) -> io::Result<()> {
+    let galley = &text.galley;
+    for row in &galley.rows {
+        let (Some(first), Some(last)) = (row.glyphs.first(), row.glyphs.last()) else {
+            continue;
+        };
+        let format = &galley.job.sections[first.section_index as usize].format;
+        let colour = text.override_text_color.unwrap_or(if format.color == Color32::PLACEHOLDER {
+            text.fallback_color
+        } else {
+            format.color
+        });
+        let family = match format.font_id.family {
+            FontFamily::Monospace => "monospace",
+            _ => "sans-serif",
+        };
+        let content: String = row.glyphs.iter().map(|glyph| glyph.chr).collect();
+        let pos = text.pos + first.pos.to_vec2();
+        let length = last.pos.x + last.advance_width - first.pos.x;
+
+        writeln!(
+            writer,
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" textLength="{}" lengthAdjust="spacingAndGlyphs" {}>{}</text>"#,
+            pos.x, pos.y, family, format.font_id.size, length, colour_attrs("fill", colour), xml_escape(content.trim_end())
+        )?;
+    }
+    Ok(())
+}
+
+// Stroke from a path stroke, if it has a solid colour.
+fn solid_stroke(width: f32, colour: &ColorMode) -> Stroke {
+    match colour {
+        ColorMode::Solid(colour) => Stroke::new(width, *colour),
+        ColorMode::UV(_) => Stroke::NONE,
+    }
+}
+
+// SVG colour attribute, with opacity if the colour isn't opaque.
+fn colour_attrs(name: &str, colour: Color32) -> String {
+    if colour.a() == 0 {
+        return format!(r#"{}="none""#, name);
+    }
+    let [r, g, b, a] = colour.to_srgba_unmultiplied();
+    if a == 255 {
+        format!(r##"{}="#{:02x}{:02x}{:02x}""##, name, r, g, b)
+    } else {
+        format!(r##"{0}="#{1:02x}{2:02x}{3:02x}" {0}-opacity="{4:.3}""##, name, r, g, b, a as f32 / 255.0)
+    }
+}
+
+// SVG stroke attributes.
+fn stroke_attrs(stroke: Stroke) -> String {
+    if stroke.is_empty() {
+        r#"stroke="none""#.to_string()
+    } else {
+        format!(r#"{} stroke-width="{}""#, colour_attrs("stroke", stroke.color), stroke.width)
+    }
+}
+
+// Points of a polygon or polyline.
+fn svg_points(points: &[Pos2]) -> String {
+    points.iter().map(|p| format!("{},{}", p.x, p.y)).collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,130 @@
+// Custom scrape rules.
+// Rules turn log lines that aren't events, like modem or CAN bus lines,
+// into events. Rules are data, loaded from yaml, like the event schema.
+
+use log::warn;
+
+use regex::{Regex, RegexSet};
+use serde::{Deserialize, Serialize};
+
+use crate::event::{EventField, Value};
+
+// Default rules, built into the program.
+// There are none, the file has an example.
+pub const DEFAULT_RULES: &str = include_str!("../assets/rules.yml");
+
+// All the rule definitions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleSchema {
+    #[serde(default)]
+    pub rules: Vec<RuleDef>,
+}
+
+// Definition of a single rule.
+// Named groups in the pattern are the event fields,
+// except date and time which are the time of the event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDef {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl RuleSchema {
+    // Read rule definitions from yaml.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+}
+
+// A rule with its compiled pattern.
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    pattern: Regex,
+}
+
+// Line matched by a rule.
+// Date and time are None if the rule doesn't capture them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleMatch {
+    pub name: String,
+    pub date: Option<String>,
+    pub time: Option<String>,
+    pub fields: Vec<EventField>,
+}
+
+// Compiled rules.
+// A regex set finds the matching rule in one pass over the line.
+#[derive(Debug)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    set: RegexSet,
+}
+
+impl RuleSet {
+    // Compile the rules.
+    // Patterns that don't compile are left out.
+    pub fn new(schema: &RuleSchema) -> Self {
+        let mut rules = Vec::new();
+        for def in &schema.rules {
+            match Regex::new(&def.pattern) {
+                Ok(pattern) => rules.push(Rule { name: def.name.clone(), pattern }),
+                Err(e) => warn!("Invalid {} rule pattern: {}", def.name, e),
+            }
+        }
+        let set = RegexSet::new(rules.iter().map(|rule| rule.pattern.as_str()))
+            .expect("Invalid rule patterns");
+        Self { rules, set }
+    }
+
+    // Check if there are any rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Match a line against the rules, first rule first.
+    // Whole numbers are counts, other numbers are numbers, and the rest text.
+    pub fn scrape(&self, line: &str) -> Option<RuleMatch> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let index = self.set.matches(line).into_iter().next()?;
+        let rule = &self.rules[index];
+        let captures = rule.pattern.captures(line)?;
+
+        let mut result = RuleMatch {
+            name: rule.name.clone(),
+            date: None,
+            time: None,
+            fields: Vec::new(),
+        };
+        for name in rule.pattern.capture_names().flatten() {
+            let Some(raw) = captures.name(name) else {
+                continue;
+            };
+            let raw = raw.as_str();
+            match name {
+                "date" => result.date = Some(raw.to_string()),
+                "time" => result.time = Some(raw.to_string()),
+                _ => {
+                    let value = if let Ok(n) = raw.parse::<i64>() {
+                        Value::Count(n)
+                    } else if let Ok(n) = raw.parse::<f64>()
+                        && n.is_finite()
+                    {
+                        Value::Number(n)
+                    } else {
+                        Value::Text(raw.to_string())
+                    };
+                    result.fields.push(EventField {
+                        key: None,
+                        label: name.to_string(),
+                        value,
+                        decimals: 0,
+                    });
+                }
+            }
+        }
+        Some(result)
+    }
+}
@@ -0,0 +1,116 @@
+// Event schema.
+// Event definitions are data, loaded from yaml, so that support
+// for new events can be added without a new release.
+
+use log::warn;
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::event::{EventField, FieldKey, Kph, Rpm, Seconds, Unit, Value, Volts};
+
+// Default event definitions, built into the program.
+pub const DEFAULT_EVENT_SCHEMA: &str = include_str!("../assets/events.yml");
+
+// All the event definitions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub events: Vec<EventDef>,
+}
+
+// Definition of a single event type.
+// Events without a pattern have no sub-data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDef {
+    pub name: String,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default = "default_on_trip")]
+    pub on_trip: bool,
+    #[serde(default)]
+    pub fields: Vec<FieldDef>,
+}
+
+// Definition of a field in the event sub-data.
+// Fields with a unit, scale or decimals are numeric.
+// Keys the program doesn't know are dropped, so the field is still shown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDef {
+    #[serde(default, deserialize_with = "known_field_key")]
+    pub key: Option<FieldKey>,
+    pub label: String,
+    pub group: usize,
+    #[serde(default)]
+    pub unit: Option<Unit>,
+    #[serde(default)]
+    pub scale: Option<f64>,
+    #[serde(default)]
+    pub decimals: Option<usize>,
+    #[serde(default)]
+    pub fallback: Option<String>,
+}
+
+fn default_on_trip() -> bool {
+    true
+}
+
+// Read a field key, dropping it with a warning if it isn't known,
+// rather than failing the whole schema.
+fn known_field_key<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<FieldKey>, D::Error> {
+    let Some(key) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let known: Result<FieldKey, serde::de::value::Error> = FieldKey::deserialize(key.as_str().into_deserializer());
+    match known {
+        Ok(key) => Ok(Some(key)),
+        Err(_) => {
+            warn!("Unknown event field key '{}' ignored.", key);
+            Ok(None)
+        }
+    }
+}
+
+impl EventSchema {
+    // Read event definitions from yaml.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+}
+
+impl Default for EventSchema {
+    fn default() -> Self {
+        Self::from_yaml(DEFAULT_EVENT_SCHEMA).expect("Invalid default event schema")
+    }
+}
+
+impl FieldDef {
+    // Decode a captured value into a typed field.
+    // Returns None if a numeric field doesn't parse and has no fallback.
+    pub fn decode(&self, raw: &str) -> Option<EventField> {
+        let decimals = self.decimals.unwrap_or(0);
+        let value = if self.unit.is_none() && self.scale.is_none() && self.decimals.is_none() {
+            Value::Text(raw.to_string())
+        } else {
+            match raw.parse::<f64>() {
+                Ok(number) => {
+                    let scaled = number * self.scale.unwrap_or(1.0);
+                    match self.unit {
+                        Some(Unit::Count) => Value::Count(scaled as i64),
+                        Some(Unit::Seconds) => Value::Seconds(Seconds(scaled as u64)),
+                        Some(Unit::Kph) => Value::Kph(Kph(scaled as u32)),
+                        Some(Unit::Rpm) => Value::Rpm(Rpm(scaled as u32)),
+                        Some(Unit::Volts) => Value::Volts(Volts(scaled as f32)),
+                        None => Value::Text(format!("{:.*}", decimals, scaled)),
+                    }
+                }
+                Err(_) => Value::Text(self.fallback.clone()?),
+            }
+        };
+
+        Some(EventField {
+            key: self.key,
+            label: self.label.clone(),
+            value,
+            decimals,
+        })
+    }
+}
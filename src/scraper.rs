@@ -1,16 +1,18 @@
 // Scraper structure and methods.
 
 use log::info;
-use log::warn;
 
-use regex::Regex;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 use std::time::{Instant, Duration};
 
+use scraper::{is_dump_file, log_file_kind, merge_logs, read_dump, read_log_file, run_ingest, zip_members};
+use scraper::{ArchiveMember, ControllerChange, ControllerDetail, ControllerFormat, CsvFormat, CsvMapping, Diagnostic, FollowUpdate, IngestEvent, IngestOptions, LogFileKind, LogFollower, LogParser, ParsedLog, ScrapedData, TimestampFormat};
+
 use crate::egui;
+use crate::setting_up::{EVENT_REGISTRY, RULES, SETTINGS};
 
 // Use conditional includes for linux and Windows,
 // as tinyfiledialogs doesn't readily compile and
@@ -19,7 +21,7 @@ use crate::egui;
 #[cfg(target_os = "windows")]
 use rfd::FileDialog;
 #[cfg(target_os = "linux")]
-use tinyfiledialogs::open_file_dialog;
+use tinyfiledialogs::open_file_dialog_multi;
 
 #[allow(dead_code)]
 
@@ -27,41 +29,100 @@ use tinyfiledialogs::open_file_dialog;
 pub enum FileDialogMessage {
     FileSelected(PathBuf),
     DialogClosed,
+    Progress(LoadProgress),
+    Loaded(Box<ParsedLog>),
+    Followed(Box<FollowUpdate>),
+    Live(Box<IngestEvent>),
+    Failed(String),
+    Cancelled,
 }
 
-// GPS location (lat, lon)
-#[derive(Debug)]
-pub struct GpsLocation {
-    pub lat: f64,
-    pub lon: f64,
+// Progress of a log file being loaded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadProgress {
+    pub bytes_read: u64,
+    pub total_bytes: u64,
+    pub events: usize,
 }
 
-// Data that is scraped.
-#[derive(Debug)]
-pub struct ScrapedData {
-    pub date_time: String,
-    pub unix_time: u64,
-    pub on_trip: bool,
-    pub trip_num: String,
-    pub event_type: String,
-    pub ev_detail: Vec<(String, String)>,
-    pub ev_supported: bool,
-    pub gps_rssi: u32,
-    pub gps_speed: u32,
-    pub gps_locn: GpsLocation,
+// A log file to load, or a file in a zip archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSource {
+    pub path: PathBuf,
+    pub member: Option<String>,
+}
+
+impl LogSource {
+    // Name for display.
+    // Files from zip archives are shown as archive/file.
+    pub fn name(&self) -> String {
+        let filename = self.path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match &self.member {
+            Some(member) => format!("{}/{}", filename, member),
+            None => filename,
+        }
+    }
+}
+
+// Format to read logs in.
+// Detected from the first lines of the log unless chosen from the File menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormatChoice {
+    Detect,
+    Controller,
+    Csv,
+}
+
+impl LogFormatChoice {
+    // Name for display.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogFormatChoice::Detect => "Detect",
+            LogFormatChoice::Controller => "Controller log",
+            LogFormatChoice::Csv => "CSV",
+        }
+    }
+}
+
+// Zip archive waiting for the user to pick files from it.
+// Picked has an entry for each member.
+#[derive(Debug, Clone)]
+pub struct ZipPicker {
+    pub path: PathBuf,
+    pub members: Vec<ArchiveMember>,
+    pub picked: Vec<bool>,
 }
 
+// Minimum time between progress messages from the load worker.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Time between checks for new lines when following a file.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
 // Scraper struct and methods.
 #[derive(Debug)]
 pub struct Scraper {
-    pub selected_file: Option<PathBuf>,
+    pub sources: Vec<LogSource>,
+    pub zip_picker: Option<ZipPicker>,
     pub file_dialog_open: bool,
     pub file_receiver: Option<mpsc::Receiver<FileDialogMessage>>,
     pub processing_status: String,
     pub processing_duration: Duration,
+    pub processing_start: Option<Instant>,
+    pub load_progress: Option<LoadProgress>,
+    pub cancel_load: Option<Arc<AtomicBool>>,
+    pub following: bool,
+    pub live_source: Option<String>,
     pub controller_id: String,
     pub controller_fw: String,
+    pub controller_history: Vec<ControllerChange>,
     pub scrapings: Vec<ScrapedData>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub timestamp_format: TimestampFormat,
+    pub log_format: LogFormatChoice,
+    pub csv_mapping: CsvMapping,
 }
 
 // Implement Sraper class.
@@ -70,15 +131,34 @@ impl Scraper {
     pub fn new() -> Self {
         info!("Creating new instance of Scraper.");
 
+        // Start with the timestamp format from the settings.
+        // This can be changed for each file.
+        let settings = SETTINGS.lock().unwrap().clone();
+        let timestamp_format = TimestampFormat {
+            date_order: settings.date_order,
+            source_tz: settings.source_tz(),
+        };
+
         Self {
-            selected_file: None,
+            sources: Vec::new(),
+            zip_picker: None,
             file_dialog_open: false,
             file_receiver: None,
             processing_status: "No file selected.".to_string(),
             processing_duration: Duration::new(0, 0),
+            processing_start: None,
+            load_progress: None,
+            cancel_load: None,
+            following: false,
+            live_source: None,
             controller_id: "".to_string(),
             controller_fw: "".to_string(),
+            controller_history: Vec::new(),
             scrapings: Vec::new(),
+            diagnostics: Vec::new(),
+            timestamp_format,
+            log_format: LogFormatChoice::Detect,
+            csv_mapping: settings.csv_columns,
         }
     }
 }
@@ -105,36 +185,35 @@ impl Scraper {
         // doesn't readily build for Windows because of
         // the available toolchain.
 
-        let file_path = {
+        let file_paths = {
             #[cfg(target_os = "windows")]
             {
                 // For windows use FileDialog.
                 FileDialog::new()
                     // .add_filter("text", &["txt"])
-                    .add_filter("Log files", &["log", "bak", "csv", "txt"])
+                    .add_filter("Log files", &["log", "bak", "csv", "txt", "gz", "zip", "json", "ndjson"])
                     .add_filter("All files", &["*"])
-                    .pick_file()
-                    .map(|path| path.to_string_lossy().to_string())
+                    .pick_files()
+                    .map(|paths| paths.iter().map(|path| path.to_string_lossy().to_string()).collect::<Vec<_>>())
             }
             #[cfg(target_os = "linux")]
             {
                 // Use tinyfiledialogs synchronous dialog.
-                open_file_dialog(
-                    "Select log file",
+                open_file_dialog_multi(
+                    "Select log files",
                     "",
-                    Some((&["*.log", "*.bak", "*.csv", "*.txt"], "Log files (log, bak, csv, txt)")),
+                    Some((&["*.log", "*.bak", "*.csv", "*.txt", "*.gz", "*.zip", "*.json", "*.ndjson"], "Log files (log, bak, csv, txt, gz, zip, json, ndjson)")),
                 )
             }
         };
 
-        match file_path {
-            Some(path_string) => {
-                let path = PathBuf::from(path_string);
-                info!("File selected: {:?}", path);
-                self.selected_file = Some(path.clone());
-                self.process_file(&path);
+        match file_paths {
+            Some(path_strings) if !path_strings.is_empty() => {
+                let paths: Vec<PathBuf> = path_strings.into_iter().map(PathBuf::from).collect();
+                info!("Files selected: {:?}", paths);
+                self.open_paths(ctx, &paths);
             }
-            None => {
+            _ => {
                 info!("No file was selected.");
             }
         }
@@ -152,1015 +231,555 @@ impl Scraper {
         self.controller_id = "".to_string();
         self.controller_fw = "".to_string();
 
-        // Clear any ongoing file dialog state.
+        // Stop any load in progress, and
+        // clear any ongoing file dialog state.
+        self.cancel_loading();
         self.file_dialog_open = false;
         self.file_receiver = None;
+        self.load_progress = None;
+        self.zip_picker = None;
+        self.controller_history.clear();
         self.scrapings.clear();
+        self.diagnostics.clear();
     }
 
-    // Method to load file from a given path.
+    // Method to load files from given paths.
     // Required for drag and drop files.
-    pub fn load_file_from_path(&mut self, path: &std::path::Path) {
+    pub fn load_files_from_paths(&mut self, ctx: &egui::Context, paths: &[PathBuf]) {
         // First initialize scraped data.
         self.reinitialize_data();
 
-        info!("Loading file from path: {:?}", path);
-        
-        let path_buf = path.to_path_buf();
-        self.selected_file = Some(path_buf.clone());
-        self.process_file(&path_buf);
+        info!("Loading files from paths: {:?}", paths);
+        self.open_paths(ctx, paths);
     }
 
-    // Method to scrape the selected file.
-    fn process_file(&mut self, path: &PathBuf) {
-
-        // Initialise timer for proocessing.
-        let processing_start = Instant::now();
-
-        // First initialize scraped data.
-        self.reinitialize_data();
-
-        info!("Processing file: {:?}", path);
-
-        match self.read_and_process_file(path) {
-            Ok(_sn) => {
-                self.processing_duration = processing_start.elapsed();
-                self.processing_status = format!("Successfully completed processing in {:?}.", self.processing_duration);
-                info!("Successfully completed processing in {:?}", self.processing_duration);
-            }
-            Err(e) => {
-                self.processing_status = format!("Error processing file: {}", e);
-                info!("File processing error: {}", e);
-            }
-        }
-    }
-
-    // Main file processing logic.
-    fn read_and_process_file(&mut self, path: &PathBuf) -> Result<usize, Box<dyn std::error::Error>> {
-
-        // Clear fields at start of processing to ensure clean state.
-        self.controller_id.clear();
-        self.controller_fw.clear();
-
-        // Open the file.
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        
-        info!("Searching file for controller serial number.");
-    
-        // Get the serial number of the controller (microseconds on time).
-        // let sn_pattern = Regex::new(r"([0-9]{1,2}/[0-9]{2}/[0-9]{4}) ([0-9]{1,2}:[0-9]{2}:[0-9]{2}(?:\.\d+)?(?: [AP]M)?)[:, ]UNIT ([0-9]+)(?: (.+))?$")?;
-        // Additional string before UNIT,
-        let sn_pattern = Regex::new(r"([0-9]{1,2}/[0-9]{2}/[0-9]{4}) ([0-9]{1,2}:[0-9]{2}:[0-9]{2}(?:\.\d+)?(?: [AP]M)?) (?:\S+ )?UNIT ([0-9]+)(?: (.+))?$")?;
-        let mut found_sn = false;
-        
-        // Process file line by line,
-        for line_result in reader.lines() {
-            let line = line_result?;
- 
-            if let Some(caps) = sn_pattern.captures(&line) {
-                let unit_number = &caps[3];
-
-                // Combine unit number with optional suffix.
-                let unit = if let Some(suffix) = caps.get(4) {
-                    format!("{} {}", unit_number, suffix.as_str())
-                } else {
-                    unit_number.to_string()
-                };
-
-                found_sn = true;
-
-                // Get combined string for controller id.
-                self.controller_id = unit;
-
-                info!("Found controller: {:?}", self.controller_id); 
-            }
-            if found_sn {
-                // Have found one instance of controller number.
-                // Don't need to look any further.
-                break;
+    // Open log files, or zip archives of log files.
+    // Several files are merged into one timeline.
+    // A single zip archive with more than one file needs
+    // the user to pick files from it, see load_zip_members.
+    // Otherwise all the files in zip archives are loaded.
+    fn open_paths(&mut self, ctx: &egui::Context, paths: &[PathBuf]) {
+        // New files aren't followed until asked.
+        self.following = false;
+        self.live_source = None;
+
+        let mut sources = Vec::new();
+        for path in paths {
+            if !matches!(log_file_kind(path), Ok(LogFileKind::Zip)) {
+                sources.push(LogSource {
+                    path: path.clone(),
+                    member: None,
+                });
+                continue;
+            }
+
+            match zip_members(path) {
+                Ok(members) if paths.len() == 1 && members.len() > 1 => {
+                    info!("Zip archive has {} files.", members.len());
+                    self.processing_status = "Select files from the zip archive.".to_string();
+                    self.zip_picker = Some(ZipPicker {
+                        path: path.clone(),
+                        picked: vec![false; members.len()],
+                        members,
+                    });
+                    return;
+                }
+                Ok(members) => {
+                    sources.extend(members.into_iter().map(|member| LogSource {
+                        path: path.clone(),
+                        member: Some(member.name),
+                    }));
+                }
+                Err(e) => {
+                    self.processing_status = format!("Error reading zip archive: {}", e);
+                    info!("Zip archive error: {}", e);
+                    return;
+                }
             }
         }
-        if found_sn == false {
-            self.controller_id = "Not defined.".to_string();
-            info!("Failed to find controller serial number."); 
-        }
 
-        // Initialise file reader again.
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-
-        info!("Searching file for controller firmware version.");
-       
-        // Get the controller firmware version.
-        let fw_pattern = Regex::new(r"([0-9]{1,2}/[0-9]{2}/[0-9]{4}) ([0-9]{1,2}:[0-9]{2}:[0-9]{2}(?:\.\d+)?(?: [AP]M)?)[:, ]EVENT ([0-9]+) ([0-9]+) (.+)/(.+)/(.+)/([-0-9]+)/([0-9]+) SWSTART (.+) ([.0-9]+.+) v(.+)$")?;
-        let mut found_fw = false;
-
-        // Process file line by line,
-        for line_result in reader.lines() {
-            let line = line_result?;
-            
-            // Check if we should stop processing.
-            if let Some(captures) = fw_pattern.captures(&line) {
-                found_fw = true;
-                // Group 11 contains the firmware versin.
-                let fw_str = captures.get(11).unwrap().as_str();
-                self.controller_fw = fw_str.to_string();
-                info!("Found controller firmware: {:?}", fw_str); 
-            }
-            if found_fw {
-                // Have found one instance of firmware version.
-                // Don't need to look any further as for now we are only
-                // looking for the first instance.
-                break;
-            }
-        }
-        if found_fw == false {
-            self.controller_fw = "Not defined.".to_string();
-            info!("Failed to find controller firmware version."); 
+        if sources.is_empty() {
+            self.processing_status = "No files to load.".to_string();
+            return;
         }
+        self.sources = sources;
+        self.process_files(ctx);
+    }
 
-        // Initialise file reader again.
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        info!("Searching file for controller events.");
-
-        // Get the controller events
-        // let ev_pattern = Regex::new(r"([0-9]{1,2}/[0-9]{2}/[0-9]{4}) ([0-9]{1,2}:[0-9]{2}:[0-9]{2})(?:\.[0-9]{3})? EVENT ([0-9]+) ([0-9]+) ([-0-9]+)/([0-9]+)/([0-9]+)/([0-9]+)/([0-9]+) ([A-Z_]+) (.+)$")?;
-        // Extra string before EVENT.
-        let ev_pattern = Regex::new(r"([0-9]{1,2}/[0-9]{2}/[0-9]{4}) ([0-9]{1,2}:[0-9]{2}:[0-9]{2})(?:\.[0-9]{3})? (?:\S+ )?EVENT ([0-9]+) ([0-9]+) ([-0-9]+)/([0-9]+)/([0-9]+)/([0-9]+)/([0-9]+) ([A-Z_]+) (.+)$")?;
-
-        // Trip number for SIGNON event so that it can
-        // be copied to the other events in the trip.
-        let mut trip_num_id = "".to_string();
-
-        // Process file line by line.
-        for line_result in reader.lines() {
-            let line = line_result?;
-            
-            // Check for event pattern.
-            if let Some(captures) = ev_pattern.captures(&line) {
-                
-                // Extract key fields for logging.
-                let date = captures.get(1).unwrap().as_str();
-                let time = captures.get(2).unwrap().as_str();
-                let unix_time = captures.get(4).unwrap().as_str();
-                let event_type = captures.get(10).unwrap().as_str();
-                let event_detail = captures.get(11).unwrap().as_str();
-                let mut on_trip = true;
-                let mut ev_supported = true;
-                let ev_key_vals = ungroup_event_data(event_type.to_string(), event_detail, &mut on_trip, &mut ev_supported);
-                let trip_id = captures.get(3).unwrap().as_str();
-
-                // Get the gps location from the event data.
-                // While gps location is included in the event string,
-                // it's not part of the event detail.
-                let gps_latitude = captures.get(5)
-                    .expect("Latitude capture group not found.")
-                    .as_str()
-                    .parse::<f64>()
-                    .expect("Failed to parse latitude as f64");
-                let gps_longitude = captures.get(6)
-                    .expect("Longitude capture group not found.")
-                    .as_str()
-                    .parse::<f64>()
-                    .expect("Failed to parse longitude as f64");
-                let gps_locn = GpsLocation {
-                    lat: gps_latitude / 10_000_000.0,
-                    lon: gps_longitude / 10_000_000.0,
-                };
-
-                // Get the gps RSSI from the event data.
-                let gps_rssi = captures.get(8)
-                    .expect("GPS RSSI capture group not found.")
-                    .as_str()
-                    .parse::<u32>()
-                    .expect("Failed to parse gps rssi as u32");
-
-                // Get the gps speed from the event data.
-                let gps_speed = captures.get(9)
-                    .expect("GPS speed capture group not found.")
-                    .as_str()
-                    .parse::<u32>()
-                    .expect("Failed to parse gps speed as u32");
-
-                // Keep track of on-trip state.
-                // SIGNON sets TRIP clears.
-                if event_type == "SIGNON" { 
-                    // Save the trip number to apply to other events.
-                    trip_num_id = trip_id.to_string();   
-                }   
-
-                // Create and populate the struct.
-                // Initialise events to be supported; change later if not.
-                let ev_data = ScrapedData {
-                    date_time: format!("{} {}", date, time),
-                    unix_time: unix_time.parse().expect("Invalid Unix time string"),
-                    on_trip: on_trip,
-                    trip_num: trip_num_id.clone(),
-                    event_type: event_type.to_string(),
-                    ev_detail: ev_key_vals,
-                    ev_supported: ev_supported,
-                    gps_locn: gps_locn,
-                    gps_rssi: gps_rssi,
-                    gps_speed: gps_speed,
-                };
+    // Load files picked from a zip archive.
+    pub fn load_zip_members(&mut self, ctx: &egui::Context, members: Vec<String>) {
+        let Some(picker) = self.zip_picker.take() else {
+            return;
+        };
 
-                // Push the struct onto the vector.
-                self.scrapings.push(ev_data);
+        info!("Loading {:?} from zip archive.", members);
+        self.sources = members.into_iter()
+            .map(|member| LogSource {
+                path: picker.path.clone(),
+                member: Some(member),
+            })
+            .collect();
+        self.process_files(ctx);
+    }
 
-                // Clear on trip flag after TRIP event.
-                // This makes TRIP still part of the trip.
-                if event_type == "TRIP" {
-                   // Clear the saved trip number as
-                   // following events are out of trip.
-                   trip_num_id = "".to_string();
-                }
-            }
+    // Everything scraped, as a parsed log, for saving.
+    pub fn parsed_log(&self) -> ParsedLog {
+        let detail = |value: &str| match value {
+            "" | "Not defined." => None,
+            value => Some(value.to_string()),
+        };
+        ParsedLog {
+            controller_id: detail(&self.controller_id),
+            controller_fw: detail(&self.controller_fw),
+            controller_history: self.controller_history.clone(),
+            scrapings: self.scrapings.clone(),
+            diagnostics: self.diagnostics.clone(),
         }
-        Ok(0)
-    }
-       
-    // Method to get path and filename for display.
-    // Not currently used.
-    pub fn _get_selected_file(&self) -> Option<&PathBuf> {
-        self.selected_file.as_ref()
     }
 
-    // Method to get just the filename for display.
-    pub fn get_selected_filename(&self) -> Option<String> {
-        self.selected_file.as_ref()
-            .and_then(|path| path.file_name())
-            .and_then(|name| name.to_str())
-            .map(|s| s.to_string())
+    // Trip number, as logged, of the trip with the given key.
+    pub fn trip_num(&self, trip_key: &str) -> Option<&str> {
+        self.scrapings.iter()
+            .find(|scraped| scraped.trip_key == trip_key)
+            .map(|scraped| scraped.trip_num.as_str())
     }
 
-    // Get processing status for display.
-    pub fn get_processing_status(&self) -> &str {
-        &self.processing_status
+    // Method to load the selected files again.
+    // Required when the timestamp format is changed.
+    pub fn reload_file(&mut self, ctx: &egui::Context) {
+        if !self.sources.is_empty() && self.zip_picker.is_none() {
+            info!("Reloading files: {:?}", self.sources);
+            self.process_files(ctx);
+        }
     }
-}
-
-// Function to expand on the scraped data.
-fn ungroup_event_data(event_type: String, sub_data: &str, on_trip: &mut bool, ev_supported: &mut bool) -> Vec<(String, String)> {
-    // Initialise result vector.
-    let mut result = Vec::new();
-
-    // Search for the event sub-data for the SIGNON event.
-    match event_type.as_str() {
-        "SIGNON" => {
-            let sub_signon_pattern = Regex::new(r"([-\*\+0-9]+) ([0-9a-fA-F]+) (.+?) ([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid SIGNON regex pattern");
-
-            if let Some(captures) = sub_signon_pattern.captures(sub_data) {
-                if let Some(driver_id) = captures.get(1) {
-                    result.push(("Operator id".to_string(), driver_id.as_str().to_string()));
-                }
-                if let Some(card_id) = captures.get(2) {
-                    result.push(("Card id".to_string(), card_id.as_str().to_string()));
-                }
-                if let Some(sign_stat) = captures.get(3) {
-                    result.push(("Result".to_string(), sign_stat.as_str().to_string()));
-                }
-                if let Some(bits_read) = captures.get(4) {
-                    result.push(("Bits read".to_string(), bits_read.as_str().to_string()));
-                }
-                if let Some(keyboard) = captures.get(5) {
-                    result.push(("Keyboard".to_string(), keyboard.as_str().to_string()));
-                }
-                if let Some(card_reader) = captures.get(6) {
-                    result.push(("Card reader".to_string(), card_reader.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(7) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                    warn!("Failed to extract sub-data from SIGNON");
-            }
-        },
-
-        // Search for the event sub-data for the CHECKLIST event.
-        "CHECKLIST" => {
-            let sub_checklist_pattern = Regex::new(r"([0-9]+) (OK|CANCEL|NOFILE) ([0-9]+) ([0-9]+) ([0-9]+) ([\-a-zA-Z]+) v:(.+?)$")
-                .expect("Invalid CHECKLIST regex pattern");
-
-            if let Some(captures) = sub_checklist_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(chk_result) = captures.get(2) {
-                    result.push(("Result".to_string(), chk_result.as_str().to_string()));
-                }
-                if let Some(failed_q) = captures.get(3) {
-                    result.push(("Failed questions".to_string(), failed_q.as_str().to_string()));
-                }
-                if let Some(chklist_dur) = captures.get(4) {
-                    result.push(("Checklist duration".to_string(), chklist_dur.as_str().to_string()));
-                }
-                if let Some(chklist_ver) = captures.get(5) {
-                    result.push(("Checklist version".to_string(), chklist_ver.as_str().to_string()));
-                }
-                if let Some(chklist_type) = captures.get(6) {
-                    result.push(("Checklist type".to_string(), chklist_type.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(7) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from CHECKLIST");
-            }
-        },
-
-        // Search for the event sub-data for the CLFAIL event.
-        "CLFAIL" => {
-            let sub_clfail_pattern = Regex::new(r"([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid CLFAIL regex pattern");
-
-            if let Some(captures) = sub_clfail_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(chk_fail_q) = captures.get(2) {
-                    result.push(("Failded question".to_string(), chk_fail_q.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from CLFAIL");
-            }
-        },
-
-        // Search for the event sub-data for the CONFIG event.
-        "CONFIG" => {
-                info!("CONFIG event found, no sub-data applicable.");
-        },
-
-        // Search for the event sub-data for the CRITICALOUTPUTSET event.
-        "CRITICALOUTPUTSET" => {
-            let sub_co_pattern = Regex::new(r"([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid CRITICALOUTPUTSET regex pattern");
-
-            if let Some(captures) = sub_co_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(speed) = captures.get(2) {
-                    result.push(("Speed".to_string(), speed.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from CRITICALOUTPUTSET");
-            }
-        },
-
-        // Search for the event sub-data for the DEBUG event.
-        // Loosely one attribute after the DEBUG event name.
-        "DEBUG" => {
-            let sub_debug_pattern = Regex::new(r"(.+)$")
-                .expect("Invalid DEBUG regex pattern");
-
-            if let Some(captures) = sub_debug_pattern.captures(sub_data) {
-                if let Some(error) = captures.get(1) {
-                    result.push(("Debug error".to_string(), error.as_str().to_string()));
-                }
-            } else {
-                warn!("Failed to extract sub-data from DEBUG");
-            }
-        },
-
-        // Search for the event sub-data for the ENGINEOVERSPEED event.
-        "ENGINEOVERSPEED" => {
-            let sub_engine_overspeed_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid ENGINEOVERSPEED regex pattern");
-
-            if let Some(captures) = sub_engine_overspeed_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(max_rpm) = captures.get(3) {
-                    result.push(("Max RPM".to_string(), max_rpm.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from ENGINEOVERSPEED");
-            }
-        },
-
-         // Search for the event sub-data for the ENGINETEMP event.
-        "ENGINETEMP" => {
-            let sub_enginetemp_pattern = Regex::new(r"([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid ENGINETEMP regex pattern");
-
-            if let Some(captures) = sub_enginetemp_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from ENGINETEMP");
-            }
-        },
-
-         // Search for the event sub-data for the HARDWARE event.
-        "HARDWARE" => {
-            let sub_hardware_pattern = Regex::new(r"(.*) v:(.+?)$")
-                .expect("Invalid HARDWARE regex pattern");
-
-            if let Some(captures) = sub_hardware_pattern.captures(sub_data) {
-                if let Some(eq_fail) = captures.get(1) {
-                    result.push(("Equipment fault".to_string(), eq_fail.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(2) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-
-                // The HARDWARE event only occurs out of trip.
-                // Setting HARDWARE on_trip flag to false.
-                *on_trip = false;
-
-            } else {
-                warn!("Failed to extract sub-data from HARDWARE");
-            }
-        },
-
-       // Search for the event sub-data for the IMPACT event.
-        "IMPACT" => {
-            let sub_impact_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([\-a-zA-Z]+) v:(.+?)$")
-                .expect("Invalid IMPACT regex pattern");
 
-            if let Some(captures) = sub_impact_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(fwd_g) = captures.get(2) {
-                    result.push(("Forward g".to_string(), fwd_g.as_str().to_string()));
-                }
-                if let Some(rev_g) = captures.get(3) {
-                    result.push(("Reverse g".to_string(), rev_g.as_str().to_string()));
-                }
-                if let Some(left_g) = captures.get(4) {
-                    result.push(("Left g".to_string(), left_g.as_str().to_string()));
-                }
-                if let Some(right_g) = captures.get(5) {
-                    result.push(("Right g".to_string(), right_g.as_str().to_string()));
-                }
-                if let Some(max_g1) = captures.get(6) {
-                    result.push(("Max G1".to_string(), max_g1.as_str().to_string()));
-                }
-                if let Some(max_g2) = captures.get(7) {
-                    result.push(("Max G2".to_string(), max_g2.as_str().to_string()));
-                }
-                if let Some(severity) = captures.get(8) {
-                    result.push(("Severity".to_string(), severity.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(9) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from IMPACT");
-            }
-        },
+    // Parser for the loaded files, or a live connection.
+    // CSV exports are read with the columns from the settings.
+    // Other lines are scraped with the custom rules.
+    fn new_parser(&self) -> LogParser {
+        let parser = LogParser::with_registry(EVENT_REGISTRY.clone())
+            .with_timestamp_format(self.timestamp_format)
+            .with_rules(RULES.clone());
+        let controller = Box::new(ControllerFormat::new(&EVENT_REGISTRY));
+        let csv = Box::new(CsvFormat::new(self.csv_mapping.clone()));
+        match self.log_format {
+            LogFormatChoice::Detect => parser.with_formats(vec![controller, csv]),
+            LogFormatChoice::Controller => parser.with_format(controller),
+            LogFormatChoice::Csv => parser.with_format(csv),
+        }
+    }
 
-        // Search for the event sub-data for the INPUT event.
-        "INPUT" => {
-            let sub_input_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid INPUT regex pattern");
+    // Connect to a controller and scrape its log as it arrives.
+    // Runs until disconnected, reconnecting if the connection drops.
+    pub fn connect(&mut self, ctx: &egui::Context, options: IngestOptions) {
+        self.reinitialize_data();
+        self.sources.clear();
+        self.following = false;
+
+        info!("Connecting to {}.", options.source);
+        let source_name = options.source.to_string();
+        self.processing_status = format!("Connecting to {}...", source_name);
+        self.live_source = Some(source_name);
+
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.file_receiver = Some(receiver);
+        self.cancel_load = Some(cancel.clone());
+
+        let parser = self.new_parser().with_source(&options.source.to_string());
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            let result = run_ingest(&options, parser, &cancel, |event| {
+                let sent = sender.send(FileDialogMessage::Live(Box::new(event))).is_ok();
+                ctx.request_repaint();
+                sent
+            });
+            if let Err(e) = result {
+                let _ = sender.send(FileDialogMessage::Failed(e.to_string()));
+                ctx.request_repaint();
+            }
+        });
+    }
 
-            if let Some(captures) = sub_input_pattern.captures(sub_data) {
-                if let Some(input_num) = captures.get(1) {
-                    result.push(("Input".to_string(), input_num.as_str().to_string()));
-                }
-                if let Some(input_state) = captures.get(2) {
-                    result.push(("State".to_string(), input_state.as_str().to_string()));
-                }
-                if let Some(active_time) = captures.get(3) {
-                    result.push(("Duration".to_string(), active_time.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from INPUT");
-            }
-        },
+    // Check if connected to a controller.
+    pub fn is_live(&self) -> bool {
+        self.live_source.is_some() && self.file_receiver.is_some()
+    }
 
-         // Search for the event sub-data for the LOWCOOLANT event.
-        "LOWCOOLANT" => {
-            let sub_lowcoolant_pattern = Regex::new(r"([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid LOWCOOLANT regex pattern");
+    // Stop reading from the controller, keeping what was scraped.
+    pub fn disconnect(&mut self) {
+        if let Some(source_name) = &self.live_source {
+            info!("Disconnecting from {}.", source_name);
+            self.processing_status = format!("Disconnected from {}, {} events.", source_name, self.scrapings.len());
+        }
+        self.cancel_loading();
+        self.finish_loading();
+    }
 
-            if let Some(captures) = sub_lowcoolant_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from LOWCOOLANT");
-            }
-        },
+    // Check if the selected file can be followed.
+    // Only a single uncompressed file can be.
+    pub fn can_follow(&self) -> bool {
+        match self.sources.as_slice() {
+            [source] => source.member.is_none()
+                && !is_dump_file(&source.name())
+                && matches!(log_file_kind(&source.path), Ok(LogFileKind::Plain)),
+            _ => false,
+        }
+    }
 
-         // Search for the event sub-data for the OFFSEAT event.
-        "OFFSEAT" => {
-            let sub_off_seat_pattern = Regex::new(r"([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid OFFSEAT regex pattern");
+    // Start or stop following the selected file.
+    // Following reads the file again from the start,
+    // then keeps adding lines as they are written.
+    pub fn set_following(&mut self, ctx: &egui::Context, following: bool) {
+        if following == self.following {
+            return;
+        }
 
-            if let Some(captures) = sub_off_seat_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OFFSEAT");
-            }
-        },
+        if following {
+            if !self.can_follow() {
+                return;
+            }
+            info!("Following file: {:?}", self.sources);
+            self.following = true;
+            self.process_files(ctx);
+        } else {
+            info!("Stopped following file.");
+            self.cancel_loading();
+            self.finish_loading();
+            self.following = false;
+            self.processing_status = format!("Stopped following file, {} events.", self.scrapings.len());
+        }
+    }
 
-         // Search for the event sub-data for the OILPRESSURE event.
-        "OILPRESSURE" => {
-            let sub_oilpressure_pattern = Regex::new(r"([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid OILPRESSURE regex pattern");
+    // Method to scrape the selected files.
+    // Parsing is done on a worker thread so that the gui stays responsive.
+    // Results are picked up by poll_loading.
+    fn process_files(&mut self, ctx: &egui::Context) {
+        // First initialize scraped data.
+        self.reinitialize_data();
 
-            if let Some(captures) = sub_oilpressure_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OILPRESSURE");
-            }
-        },
-
-        // Search for the event sub-data for the OOS PM event.
-        "OOS PM" => {
-            let sub_oospm_pattern = Regex::new(r" v:(.+?)$")
-                .expect("Invalid OS PMOS regex pattern");
-
-            if let Some(captures) = sub_oospm_pattern.captures(sub_data) {
-                if let Some(battery) = captures.get(1) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OOS PMS");
-            }
-        },
+        info!("Processing files: {:?}", self.sources);
 
-        // Search for the event sub-data for the OOS UPM event.
-        "OOS UPM" => {
-            let sub_oosupm_pattern = Regex::new(r"[0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid OOS UPM regex pattern");
+        // Initialise timer for proocessing.
+        self.processing_start = Some(Instant::now());
+        self.load_progress = Some(LoadProgress::default());
+
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.file_receiver = Some(receiver);
+        self.cancel_load = Some(cancel.clone());
+
+        let sources = self.sources.clone();
+        let ctx = ctx.clone();
+        let parser = self.new_parser();
+
+        // Following file runs until cancelled.
+        if self.following {
+            let source = sources[0].clone();
+            thread::spawn(move || {
+                let message = match follow_worker(&source, &parser, &sender, &cancel, &ctx) {
+                    Ok(()) => FileDialogMessage::Cancelled,
+                    Err(e) => FileDialogMessage::Failed(e.to_string()),
+                };
+                let _ = sender.send(message);
+                ctx.request_repaint();
+            });
+            return;
+        }
 
-            if let Some(captures) = sub_oosupm_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(reason) = captures.get(2) {
-                    result.push(("Reason".to_string(), reason.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OOS UPM");
-            }
-        },
+        thread::spawn(move || {
+            let message = match load_worker(&sources, &parser, &sender, &cancel, &ctx) {
+                Ok(Some(parsed)) => FileDialogMessage::Loaded(Box::new(parsed)),
+                Ok(None) => FileDialogMessage::Cancelled,
+                Err(e) => FileDialogMessage::Failed(e.to_string()),
+            };
+            // Receiver may have gone if a new file was loaded.
+            let _ = sender.send(message);
+            ctx.request_repaint();
+        });
+    }
 
-        // Search for the event sub-data for the OVERLOAD event.
-        "OVERLOAD" => {
-            let sub_overload_pattern = Regex::new(r"([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid OVERLOAD regex pattern");
+    // Check for messages from the load worker.
+    // Call once per frame.
+    pub fn poll_loading(&mut self) {
+        let Some(receiver) = &self.file_receiver else {
+            return;
+        };
 
-            if let Some(captures) = sub_overload_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+        let messages: Vec<FileDialogMessage> = receiver.try_iter().collect();
+        for message in messages {
+            match message {
+                FileDialogMessage::Progress(progress) => {
+                    self.load_progress = Some(progress);
+                }
+                FileDialogMessage::Loaded(parsed) => {
+                    self.finish_loading();
+                    self.controller_id = parsed.controller_id.unwrap_or_else(|| "Not defined.".to_string());
+                    self.controller_fw = parsed.controller_fw.unwrap_or_else(|| "Not defined.".to_string());
+                    self.controller_history = parsed.controller_history;
+                    self.scrapings = parsed.scrapings;
+                    self.diagnostics = parsed.diagnostics;
+                    if self.diagnostics.is_empty() {
+                        self.processing_status = format!("Successfully completed processing in {:?}.", self.processing_duration);
+                    } else {
+                        self.processing_status = format!("Completed processing in {:?} with {} parse issues.", self.processing_duration, self.diagnostics.len());
                     }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OVERLOAD");
-            }
-        },
-
-        // Search for the event sub-data for the OVERSPEED event.
-        "OVERSPEED" => {
-            let sub_overspeed_pattern = Regex::new(r"([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid OVERSPEED regex pattern");
-
-            if let Some(captures) = sub_overspeed_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(3) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+                    info!("Completed processing in {:?} with {} parse issues", self.processing_duration, self.diagnostics.len());
+                }
+                FileDialogMessage::Followed(update) => {
+                    // First update finishes the initial load.
+                    if self.load_progress.take().is_some()
+                        && let Some(start) = self.processing_start.take()
+                    {
+                        self.processing_duration = start.elapsed();
                     }
-                }
-            } else {
-                warn!("Failed to extract sub-data from OVERSPEED");
-            }
-        },
-
-        // Search for the event sub-data for the POWERDOWN event.
-        "POWERDOWN" => {
-                info!("POWERDOWN event found, no sub-data applicable.");
-        },
-
-        // Search for the event sub-data for the REPORT event.
-        "REPORT" => {
-            let sub_report_pattern = Regex::new(r"(\*|[0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid REPORT regex pattern");
-
-            if let Some(captures) = sub_report_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(speed) = captures.get(2) {
-                    result.push(("Speed".to_string(), speed.as_str().to_string()));
-                }
-                if let Some(dirn) = captures.get(3) {
-                    result.push(("Direction".to_string(), dirn.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+                    if update.restarted {
+                        info!("Followed file restarted.");
+                        self.controller_id = "".to_string();
+                        self.controller_fw = "".to_string();
+                        self.controller_history.clear();
+                        self.scrapings.clear();
+                        self.diagnostics.clear();
                     }
-                }
-            } else {
-                warn!("Failed to extract sub-data from REPORT");
-            }
-        },
- 
-        // Search for the event sub-data for the SERVICE event.
-        "SERVICE" => {
-            let sub_service_pattern = Regex::new(r" v:(.+?)$")
-                .expect("Invalid SERVICE regex pattern");
-
-            if let Some(captures) = sub_service_pattern.captures(sub_data) {
-                if let Some(battery) = captures.get(1) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+                    let log = update.log;
+                    if let Some(controller_id) = log.controller_id {
+                        self.controller_id = controller_id;
                     }
-                }
-            } else {
-                warn!("Failed to extract sub-data from SERVICE");
-            }
-        },
-
-        // Search for the event sub-data for the SWSTART event.
-        // NOTE that the SWSTART event occurs outside of trips.
-        "SWSTART" => {
-            let sub_swstart_pattern = Regex::new(r"([.0-9]+ .*) v:(.+?)$")
-                .expect("Invalid SWSTART regex pattern");
-
-            if let Some(captures) = sub_swstart_pattern.captures(sub_data) {
-                if let Some(firmware) = captures.get(1) {
-                    result.push(("Firmware".to_string(), firmware.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(2) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+                    if let Some(controller_fw) = log.controller_fw {
+                        self.controller_fw = controller_fw;
                     }
-                }
-
-                // The SWSTART event only occurs out of trip.
-                // Setting SWSSTART on_trip flag to false.
-                *on_trip = false;
-
-            } else {
-                warn!("Failed to extract sub-data from SWSTART");
-            }
-        },
-
-        // Search for the event sub-data for the UNBUCKLED event.
-        "UNBUCKLED" => {
-            let sub_unbuckled_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([DP]) v:(.+?)$")
-                .expect("Invalid UNBUCKLED regex pattern");
-
-            if let Some(captures) = sub_unbuckled_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(seat_owner) = captures.get(3) {
-                    result.push(("Seat owner".to_string(), seat_owner.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+                    self.controller_history.extend(log.controller_history);
+                    self.scrapings.extend(log.scrapings);
+                    self.diagnostics.extend(log.diagnostics);
+                    self.processing_status = format!("Following file, {} events.", self.scrapings.len());
+                }
+                FileDialogMessage::Live(event) => {
+                    let source_name = self.live_source.clone().unwrap_or_default();
+                    match *event {
+                        IngestEvent::Connected => {
+                            info!("Connected to {}.", source_name);
+                            self.processing_status = format!("Connected to {}, {} events.", source_name, self.scrapings.len());
+                        }
+                        IngestEvent::Disconnected(reason) => {
+                            info!("Disconnected from {}: {}", source_name, reason);
+                            self.processing_status = format!("Disconnected from {}: {} Reconnecting...", source_name, reason);
+                        }
+                        IngestEvent::Parsed(log) => {
+                            if let Some(controller_id) = log.controller_id {
+                                self.controller_id = controller_id;
+                            }
+                            if let Some(controller_fw) = log.controller_fw {
+                                self.controller_fw = controller_fw;
+                            }
+                            self.controller_history.extend(log.controller_history);
+                            self.scrapings.extend(log.scrapings);
+                            self.diagnostics.extend(log.diagnostics);
+                            self.processing_status = format!("Connected to {}, {} events.", source_name, self.scrapings.len());
+                        }
                     }
                 }
-            } else {
-                warn!("Failed to extract sub-data from UNBUCKLED");
-            }
-        },
-
-        // Search for the event sub-data for the XSIDLE event.
-        "XSIDLE" => {
-            let sub_xsidle_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid XSIDLE regex pattern");
-
-            if let Some(captures) = sub_xsidle_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(max_idle) = captures.get(2) {
-                    result.push(("Max idle".to_string(), max_idle.as_str().to_string()));
+                FileDialogMessage::Failed(e) => {
+                    self.finish_loading();
+                    self.following = false;
+                    self.processing_status = format!("Error processing file: {}", e);
+                    info!("File processing error: {}", e);
                 }
-                if let Some(xsidle_reason) = captures.get(3) {
-                    result.push(("Excess idle reason".to_string(), xsidle_reason.as_str().to_string()));
+                FileDialogMessage::Cancelled => {
+                    self.finish_loading();
+                    self.following = false;
+                    self.processing_status = "File loading cancelled.".to_string();
+                    info!("File loading cancelled.");
                 }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                    else {
-                        result.push(("Battery voltage".to_string(), "?".to_string()));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from XSIDLE");
+                FileDialogMessage::FileSelected(_) | FileDialogMessage::DialogClosed => {}
             }
-        },
-
-        // Search for the event sub-data for the XSIDLESTART event.
-        "XSIDLESTART" => {
-            let sub_xsidlest_pattern = Regex::new(r"([0-9]+) v:(.+?)$")
-                .expect("Invalid XSIDLESTART regex pattern");
+        }
+    }
 
-            if let Some(captures) = sub_xsidlest_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(2) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                    else {
-                        result.push(("Battery voltage".to_string(), "?".to_string()));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from XSIDLESTART");
-            }
-        },
+    // Ask the load worker to stop.
+    pub fn cancel_loading(&mut self) {
+        if let Some(cancel) = &self.cancel_load {
+            info!("Cancelling file load.");
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
 
-        // Search for the event sub-data for the ZONE_OK event.
-        "ZONE_OK" => {
-            let zone_ok_pattern = Regex::new(r"([0-9]+) ([0-9]+) (.*) v:(.+?)$")
-                .expect("Invalid ZONE_OK regex pattern");
+    // Tidy up after the load worker has finished.
+    fn finish_loading(&mut self) {
+        if let Some(start) = self.processing_start.take() {
+            self.processing_duration = start.elapsed();
+        }
+        self.file_receiver = None;
+        self.cancel_load = None;
+        self.load_progress = None;
+    }
 
-            if let Some(captures) = zone_ok_pattern.captures(sub_data) {
-                if let Some(zones_loaded) = captures.get(1) {
-                    result.push(("Zones loaded".to_string(), zones_loaded.as_str().to_string()));
-                }
-                if let Some(max_zones) = captures.get(2) {
-                    result.push(("Max zones".to_string(), max_zones.as_str().to_string()));
-                }
-                if let Some(firmware) = captures.get(3) {
-                    result.push(("GPS firmware version".to_string(), firmware.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(4) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
+    // Method to get path and filename for display.
+    // Not currently used.
+    pub fn _get_selected_file(&self) -> Option<&PathBuf> {
+        self.sources.first().map(|source| &source.path)
+    }
 
-                // The ZONE_OK event only occurs out of trip.
-                // Setting ZONE_OK on_trip flag to false.
-                *on_trip = false;
+    // Method to get just the filenames for display.
+    // Live connections are shown by their address.
+    pub fn get_selected_filename(&self) -> Option<String> {
+        if let Some(source_name) = &self.live_source {
+            return Some(format!("Live: {}", source_name));
+        }
+        if self.sources.is_empty() {
+            return None;
+        }
+        Some(self.sources.iter().map(LogSource::name).collect::<Vec<_>>().join(", "))
+    }
 
-            } else {
-                warn!("Failed to extract sub-data from ZONE_OK");
+    // Controller ids found in the log, in the order first found.
+    pub fn controller_ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+        for change in &self.controller_history {
+            if let ControllerDetail::Id(id) = &change.detail
+                && !ids.contains(&id.as_str())
+            {
+                ids.push(id);
             }
-        },
-
-        // Search for the event sub-data for the ZONECHANGE event.
-        "ZONECHANGE" => {
-            let sub_zone_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid ZONECHANGE regex pattern");
+        }
+        ids
+    }
 
-            if let Some(captures) = sub_zone_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(from_zone) = captures.get(2) {
-                    result.push(("From zone".to_string(), from_zone.as_str().to_string()));
-                }
-                if let Some(to_zone) = captures.get(3) {
-                    result.push(("To zone".to_string(), to_zone.as_str().to_string()));
-                }
-                if let Some(zone_output) = captures.get(4) {
-                    result.push(("Zone output".to_string(), zone_output.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(5) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from ZONECHANGE");
+    // Firmware versions found in the log, in the order first found.
+    pub fn firmware_versions(&self) -> Vec<&str> {
+        let mut versions: Vec<&str> = Vec::new();
+        for change in &self.controller_history {
+            if let ControllerDetail::Firmware(firmware) = &change.detail
+                && !versions.contains(&firmware.as_str())
+            {
+                versions.push(firmware);
             }
-        },
+        }
+        versions
+    }
 
-        // Search for the event sub-data for the ZONEOVERSPEED event.
-        "ZONEOVERSPEED" => {
-            let sub_zone_overspeed_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) v:(.+?)$")
-                .expect("Invalid ZONEOVERSPEED regex pattern");
+    // Get processing status for display.
+    pub fn get_processing_status(&self) -> &str {
+        &self.processing_status
+    }
+}
 
-            if let Some(captures) = sub_zone_overspeed_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(duration) = captures.get(2) {
-                    result.push(("Duration".to_string(), duration.as_str().to_string()));
-                }
-                if let Some(max_speed) = captures.get(3) {
-                    result.push(("Maximum speed".to_string(), max_speed.as_str().to_string()));
-                }
-                if let Some(zone_output) = captures.get(4) {
-                    result.push(("Zone output".to_string(), zone_output.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(5) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from ZONEOVERSPEED");
-            }
-        },
+// Implement Default for way to create a 'blank' instance.
+impl Default for Scraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        // Search for the event sub-data for the ZONETRANSITION event.
-        "ZONETRANSITION" => {
-            let sub_trans_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) (ENTRY|EXIT) v:(.+?)$")
-                .expect("Invalid ZONETRANSITION regex pattern");
+// Load worker.
+// Streams the files through the parser, sending progress as it goes.
+// Compressed files are decompressed as they are read.
+// Each file is read with a copy of the parser, so each detects its own format.
+// Several files are merged into one timeline.
+// Returns None if the load was cancelled.
+fn load_worker(
+    sources: &[LogSource],
+    parser: &LogParser,
+    sender: &mpsc::Sender<FileDialogMessage>,
+    cancel: &AtomicBool,
+    ctx: &egui::Context,
+) -> std::io::Result<Option<ParsedLog>> {
+    // Get the size of all the files first, for the progress bar.
+    let mut total_bytes: u64 = 0;
+    for source in sources {
+        total_bytes += read_log_file(&source.path, source.member.as_deref(), |_, size| Ok(size))?;
+    }
 
-            if let Some(captures) = sub_trans_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(from_zone) = captures.get(2) {
-                    result.push(("From zone".to_string(), from_zone.as_str().to_string()));
-                }
-                if let Some(to_zone) = captures.get(3) {
-                    result.push(("To zone".to_string(), to_zone.as_str().to_string()));
-                }
-                if let Some(to_zone_output) = captures.get(4) {
-                    result.push(("Zone output".to_string(), to_zone_output.as_str().to_string()));
-                }
-                if let Some(transition) = captures.get(5) {
-                    result.push(("Transition".to_string(), transition.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(6) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
+    let mut logs = Vec::new();
+    let mut bytes_read: u64 = 0;
+    let mut events: usize = 0;
+    let mut last_progress = Instant::now();
+
+    for source in sources {
+        let parsed = read_log_file(&source.path, source.member.as_deref(), |reader, size| {
+            // Dumps are read whole, without parsing.
+            if is_dump_file(&source.name()) {
+                bytes_read += size;
+                return read_dump(reader).map(Some);
+            }
+
+            let mut parser = parser.clone().with_source(&source.name());
+            let mut line = Vec::new();
+
+            loop {
+                if cancel.load(Ordering::Relaxed) {
+                    return Ok(None);
+                }
+
+                line.clear();
+                let num_bytes = reader.read_until(b'\n', &mut line)?;
+                if num_bytes == 0 {
+                    break;
+                }
+                bytes_read += num_bytes as u64;
+                parser.parse_line_bytes(&line);
+
+                // Don't flood the gui with progress messages.
+                if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                    last_progress = Instant::now();
+                    // Sizes of gzip files are only estimates.
+                    let progress = LoadProgress {
+                        bytes_read,
+                        total_bytes: total_bytes.max(bytes_read),
+                        events: events + parser.event_count(),
+                    };
+                    if sender.send(FileDialogMessage::Progress(progress)).is_err() {
+                        // Nobody is listening any more.
+                        return Ok(None);
                     }
+                    ctx.request_repaint();
                 }
-            } else {
-                warn!("Failed to extract sub-data from ZONETRANSITION");
             }
-        },
 
-        // Search for the event sub-data for the TRIP event.
-        "TRIP" => {
-            let sub_trip_pattern = Regex::new(r"([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+) ([0-9]+)(.*) v:(.+?)$")
-                .expect("Invalid TRIP regex pattern");
+            Ok(Some(parser.finish()))
+        })?;
 
-            if let Some(captures) = sub_trip_pattern.captures(sub_data) {
-                if let Some(trip_id) = captures.get(1) {
-                    result.push(("Trip id".to_string(), trip_id.as_str().to_string()));
-                }
-                if let Some(time_fwd) = captures.get(2) {
-                    result.push(("Time fwd".to_string(), time_fwd.as_str().to_string()));
-                }
-                if let Some(time_rev) = captures.get(3) {
-                    result.push(("Time rev".to_string(), time_rev.as_str().to_string()));
-                }
-                if let Some(time_idle) = captures.get(4) {
-                    result.push(("Time idle".to_string(), time_idle.as_str().to_string()));
-                }
-                if let Some(max_idle) = captures.get(5) {
-                    result.push(("Max idle".to_string(), max_idle.as_str().to_string()));
-                }
-                if let Some(time_on_seat) = captures.get(6) {
-                    result.push(("Time on seat".to_string(), time_on_seat.as_str().to_string()));
-                }
-                if let Some(battery) = captures.get(8) {
-                    if let Ok(voltage_tens) = battery.as_str().parse::<f32>() {
-                        let voltage_volts = voltage_tens / 10.0;
-                        result.push(("Battery voltage".to_string(), format!("{:.1}", voltage_volts)));
-                    }
-                }
-            } else {
-                warn!("Failed to extract sub-data from TRIP");
-            }
-        },
-        _ => {
-            // Events not currently supported.
-            // Only appear if show out of trip or supported flag set.
-            // Event and attributes will not be formatted.
-            // Setting unsupported flag to false.
-            *ev_supported = false;
-        }
+        let Some(parsed) = parsed else {
+            return Ok(None);
+        };
+        events += parsed.scrapings.len();
+        logs.push((source.name(), parsed));
     }
 
-    result
+    // A single file doesn't need merging.
+    if logs.len() == 1 {
+        return Ok(logs.pop().map(|(_, parsed)| parsed));
+    }
+    Ok(Some(merge_logs(logs)))
 }
 
-// Implement Default for way to create a 'blank' instance.
-impl Default for Scraper {
-    fn default() -> Self {
-        Self::new()
+// Follow worker.
+// Polls the file for new lines until cancelled.
+// The file can go missing for a while when it is rolled over.
+fn follow_worker(
+    source: &LogSource,
+    parser: &LogParser,
+    sender: &mpsc::Sender<FileDialogMessage>,
+    cancel: &AtomicBool,
+    ctx: &egui::Context,
+) -> std::io::Result<()> {
+    let parser = parser.clone().with_source(&source.name());
+    let mut follower = LogFollower::new(&source.path, parser);
+
+    while !cancel.load(Ordering::Relaxed) {
+        match follower.poll() {
+            Ok(Some(update)) => {
+                if sender.send(FileDialogMessage::Followed(Box::new(update))).is_err() {
+                    // Nobody is listening any more.
+                    return Ok(());
+                }
+                ctx.request_repaint();
+            }
+            Ok(None) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        thread::sleep(FOLLOW_INTERVAL);
     }
+
+    Ok(())
 }
@@ -1,9 +1,14 @@
 // Module to handle initialization of application settings and details.
 
 use lazy_static::lazy_static;
-use std::fs::File;
+use log::{info, warn};
+use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+
+use scraper::rules::DEFAULT_RULES;
+use scraper::schema::DEFAULT_EVENT_SCHEMA;
+use scraper::{EventRegistry, EventSchema, RuleSchema, RuleSet};
 
 use crate::settings::{Settings, Details};
 
@@ -24,6 +29,23 @@ lazy_static! {
     };
 }
 
+// Create a global variable for the event definitions.
+// Loaded from events.yml so that new events can be supported
+// without a new release.
+lazy_static! {
+    pub static ref EVENT_REGISTRY: Arc<EventRegistry> = {
+        Arc::new(EventRegistry::new(&load_event_schema()))
+    };
+}
+
+// Create a global variable for the custom scrape rules.
+// Loaded from rules.yml so that other log lines can be scraped.
+lazy_static! {
+    pub static ref RULES: Arc<RuleSet> = {
+        Arc::new(RuleSet::new(&load_rule_schema()))
+    };
+}
+
 /// Load settings from file or create default settings.
 fn load_settings() -> Settings {
     match File::open("settings.yml") {
@@ -47,20 +69,83 @@ fn load_settings() -> Settings {
         // Settings file not found - create it with defaults.
         Err(_) => {
             let default_settings = Settings::default();
-            
-            // Try to create the settings file.
-            if let Ok(yaml) = serde_yaml::to_string(&default_settings) {
-                if let Ok(mut file) = File::create("settings.yml") {
-                    let content = format!(
-                        "# User settings for Scraper application.\n\
-                         # Font sizes must be between 12.0 and 20.0\n\n\
-                         {}", 
-                        yaml
-                    );
-                    let _ = file.write_all(content.as_bytes());
+            save_settings(&default_settings);
+            default_settings
+        }
+    }
+}
+
+/// Change the settings and save them to file,
+/// so that changes made in the application are kept.
+pub fn update_settings(change: impl FnOnce(&mut Settings)) {
+    let mut settings = SETTINGS.lock().unwrap();
+    change(&mut settings);
+    save_settings(&settings);
+}
+
+/// Save settings to file.
+fn save_settings(settings: &Settings) {
+    if let Ok(yaml) = serde_yaml::to_string(settings)
+        && let Ok(mut file) = File::create("settings.yml")
+    {
+        let content = format!(
+            "# User settings for Scraper application.\n\
+             # Font sizes must be between 12.0 and 20.0\n\
+             # Date order is dmy, mdy or ymd\n\
+             # Time zones are names like UTC or America/Chicago\n\n\
+             {}", 
+            yaml
+        );
+        if let Err(e) = file.write_all(content.as_bytes()) {
+            warn!("Failed to save settings: {}", e);
+        }
+    }
+}
+
+/// Load event definitions from file or use the built in definitions.
+fn load_event_schema() -> EventSchema {
+    match fs::read_to_string("events.yml") {
+        Ok(contents) => {
+            match EventSchema::from_yaml(&contents) {
+                Ok(schema) => {
+                    info!("Loaded {} event definitions from events.yml.", schema.events.len());
+                    schema
+                }
+                // Event definitions invalid.
+                Err(e) => {
+                    warn!("Invalid events.yml, using built in event definitions: {}", e);
+                    EventSchema::default()
                 }
             }
-            default_settings
+        }
+        // Event definitions file not found - create it with defaults.
+        Err(_) => {
+            let _ = fs::write("events.yml", DEFAULT_EVENT_SCHEMA);
+            EventSchema::default()
+        }
+    }
+}
+
+/// Load custom scrape rules from file or use the built in (empty) rules.
+fn load_rule_schema() -> RuleSchema {
+    match fs::read_to_string("rules.yml") {
+        Ok(contents) => {
+            match RuleSchema::from_yaml(&contents) {
+                Ok(schema) => {
+                    info!("Loaded {} scrape rules from rules.yml.", schema.rules.len());
+                    schema
+                }
+                // Rules invalid.
+                Err(e) => {
+                    warn!("Invalid rules.yml, no scrape rules used: {}", e);
+                    RuleSchema::default()
+                }
+            }
+        }
+        // Rules file not found - create it with the example.
+        Err(_) => {
+            let _ = fs::write("rules.yml", DEFAULT_RULES);
+            RuleSchema::default()
         }
     }
 }
@@ -69,7 +154,7 @@ fn load_settings() -> Settings {
 fn create_details() -> Details {
     Details {
         program_name:               "Scraper".to_string(),
-        program_ver:                "0.6.0".to_string(),
+        program_ver:                "0.7.0".to_string(),
         program_date:               "2025".to_string(),
         program_devs:               vec!["mdc".to_string()],
         program_web:                "galacticwingcommander".to_string(),
@@ -104,5 +189,11 @@ fn create_details() -> Details {
 
         changelog_win_width:        300.0,
         changelog_win_height:       400.0,
+
+        parse_issues_win_width:     600.0,
+        parse_issues_win_height:    400.0,
+
+        controller_history_win_width:   500.0,
+        controller_history_win_height:  300.0,
     }
 }
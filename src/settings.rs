@@ -1,10 +1,27 @@
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+use scraper::{CsvMapping, DateOrder};
+
 // Settings that the user can control.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub trip_font_size:         f32,
     pub event_font_size:        f32,
+    #[serde(default)]
+    pub date_order:             DateOrder,
+    #[serde(default = "default_time_zone")]
+    pub source_time_zone:       String,
+    #[serde(default = "default_time_zone")]
+    pub display_time_zone:      String,
+    #[serde(default)]
+    pub csv_columns:            CsvMapping,
+}
+
+// Time zones default to UTC,
+// for settings files from before time zones were added.
+fn default_time_zone() -> String {
+    "UTC".to_string()
 }
 
 // Fumction to clamp settings to limits
@@ -14,6 +31,24 @@ impl Settings {
     pub fn validate(&mut self) {
         self.trip_font_size = self.trip_font_size.clamp(12.0, 20.0);
         self.event_font_size = self.event_font_size.clamp(12.0, 20.0);
+
+        // Time zones must be IANA names, e.g. America/Chicago.
+        if self.source_time_zone.parse::<Tz>().is_err() {
+            self.source_time_zone = default_time_zone();
+        }
+        if self.display_time_zone.parse::<Tz>().is_err() {
+            self.display_time_zone = default_time_zone();
+        }
+    }
+
+    // Time zone that logs are assumed to be in.
+    pub fn source_tz(&self) -> Tz {
+        self.source_time_zone.parse().unwrap_or(Tz::UTC)
+    }
+
+    // Time zone that times are displayed in.
+    pub fn display_tz(&self) -> Tz {
+        self.display_time_zone.parse().unwrap_or(Tz::UTC)
     }
 }
 
@@ -24,6 +59,10 @@ impl Default for Settings {
         Settings {
             trip_font_size:     16.0,
             event_font_size:    13.0,
+            date_order:         DateOrder::default(),
+            source_time_zone:   default_time_zone(),
+            display_time_zone:  default_time_zone(),
+            csv_columns:        CsvMapping::default(),
         }
     }
 }
@@ -67,4 +106,10 @@ pub struct Details {
 
     pub changelog_win_width:        f32,
     pub changelog_win_height:       f32,
+
+    pub parse_issues_win_width:     f32,
+    pub parse_issues_win_height:    f32,
+
+    pub controller_history_win_width:   f32,
+    pub controller_history_win_height:  f32,
 }
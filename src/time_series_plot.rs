@@ -1,10 +1,14 @@
 use log::warn;
 
+use std::io;
+use std::path::Path;
+
 use eframe::egui;
 
 use crate::colours;
 use crate::helpers_ts;
 use crate::dataset_ts;
+use crate::plot_image::{self, ImageFormat};
 use crate::scraper::Scraper;
 
 // SinglePoint struct.
@@ -86,6 +90,7 @@ const TICK_HYSTERESIS: f32 = 20.0;
 
 // Function to plot time series data.
 // This is called from the ui.rs file where the UI panel is defined and created.
+#[allow(clippy::too_many_arguments)]
 pub fn plot_time_series_data(
     ui: &mut egui::Ui,
     scraper: &Scraper,
@@ -94,6 +99,7 @@ pub fn plot_time_series_data(
     dark_mode: &bool,
     plot_battery: bool,
     plot_speed: bool,
+    hide_poor_gps: bool,
 ) {
     // Create a fixed top panel for info.
     egui::TopBottomPanel::top("info_panel").show_inside(ui, |ui| {
@@ -103,13 +109,13 @@ pub fn plot_time_series_data(
             // Button controls.
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 // Reset button - only show when there's actual pan/zoom state to reset.
-                if !plot_state.auto_bounds || plot_state.zoom_factor != 1.0 || plot_state.pan_offset != 0.0 {
-                    if ui.button("Reset View").clicked() {
-                        plot_state.x_range = None;
-                        plot_state.auto_bounds = true;
-                        plot_state.pan_offset = 0.0;
-                        plot_state.zoom_factor = 1.0;
-                    }
+                if (!plot_state.auto_bounds || plot_state.zoom_factor != 1.0 || plot_state.pan_offset != 0.0)
+                    && ui.button("Reset View").clicked()
+                {
+                    plot_state.x_range = None;
+                    plot_state.auto_bounds = true;
+                    plot_state.pan_offset = 0.0;
+                    plot_state.zoom_factor = 1.0;
                 }
                 
                 // Toggle button for time cursor functionality.
@@ -219,7 +225,7 @@ pub fn plot_time_series_data(
         ui.horizontal(|ui| {
             match selected_trip {
                 Some(trip_id) if !trip_id.is_empty() => {
-                    ui.label(format!("Current trip ID: {}", trip_id));
+                    ui.label(format!("Current trip ID: {}", scraper.trip_num(trip_id).unwrap_or(trip_id)));
                 }
                 _ => (),
             }
@@ -232,11 +238,7 @@ pub fn plot_time_series_data(
                         // Show delta time and difference if delta is enabled.
                         if plot_state.delta_enabled {
                             if let Some(delta_time) = plot_state.delta_time {
-                                let time_diff = if delta_time > cursor_time {
-                                    delta_time - cursor_time
-                                } else {
-                                    cursor_time - delta_time
-                                };
+                                let time_diff = delta_time.abs_diff(cursor_time);
                                 
                                 ui.label(egui::RichText::new(format!(
                                     "Cursor: {}    |    Delta: {}    |    Δ: {}",
@@ -298,7 +300,7 @@ pub fn plot_time_series_data(
                     }
         
                     // If trip selected, and not empty, get datasets to plot.
-                    let datasets = dataset_ts::create_time_series_datasets(scraper, trip_id, plot_battery, plot_speed);
+                    let datasets = dataset_ts::create_time_series_datasets(scraper, trip_id, plot_battery, plot_speed, hide_poor_gps);
 
                     // Calculate overall time range for all datasets.
                     let (time_min, time_max) = helpers_ts::calculate_time_range(&datasets);
@@ -309,12 +311,7 @@ pub fn plot_time_series_data(
 
                     for dataset in datasets {
                         // Here's the space allocation for a single plot.
-                        // There are 2 sizes for plots - normal and tall.
-                        // The dataset attribute 'tall_chart' signifies which height to use.
-                        let mut plot_size = egui::vec2(ui.available_width(), PLOT_HEIGHT);
-                        if dataset.tall_chart == true {
-                            plot_size = egui::vec2(ui.available_width(), PLOT_HEIGHT_TALL);
-                        }
+                        let plot_size = egui::vec2(ui.available_width(), chart_height(&dataset));
                         let (plot_response, painter) = ui.allocate_painter(plot_size, egui::Sense::click_and_drag());
 
                         if plot_state.cursor_enabled {
@@ -448,6 +445,53 @@ pub fn plot_time_series_data(
     });
 }
 
+// Height of a chart.
+// There are 2 sizes for plots - normal and tall.
+// The dataset attribute 'tall_chart' signifies which height to use.
+fn chart_height(dataset: &TimeSeriesData) -> f32 {
+    if dataset.tall_chart {
+        PLOT_HEIGHT_TALL
+    } else {
+        PLOT_HEIGHT
+    }
+}
+
+// Save the charts for a trip as an image.
+// All the charts are drawn one under the other, at the given width,
+// with the current pan/zoom and the time and delta cursors if enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn save_plot_image(
+    path: &Path,
+    format: ImageFormat,
+    scale: f32,
+    width: f32,
+    scraper: &Scraper,
+    trip_id: &str,
+    plot_state: &PlotState,
+    dark_mode: bool,
+    plot_battery: bool,
+    plot_speed: bool,
+    hide_poor_gps: bool,
+) -> io::Result<()> {
+    let datasets = dataset_ts::create_time_series_datasets(scraper, trip_id, plot_battery, plot_speed, hide_poor_gps);
+    if datasets.is_empty() {
+        return Err(io::Error::other("No time series plots to save."));
+    }
+    let (time_min, time_max) = helpers_ts::calculate_time_range(&datasets);
+    let height: f32 = datasets.iter().map(|dataset| chart_height(dataset) + SPACE_BETWEEN_PLOTS).sum();
+
+    plot_image::save_plot_image(path, format, egui::vec2(width, height), scale, dark_mode, |painter| {
+        // Drawing updates the tick spacing, so draw with a copy of the state.
+        let mut plot_state = plot_state.clone();
+        let mut top = 0.0;
+        for dataset in &datasets {
+            let rect = egui::Rect::from_min_size(egui::pos2(0.0, top), egui::vec2(width, chart_height(dataset)));
+            draw_plot_with_axes(painter, &rect, dataset, time_min, time_max, &mut plot_state, dark_mode);
+            top = rect.max.y + SPACE_BETWEEN_PLOTS;
+        }
+    })
+}
+
 // Helper function to draw a plot with axes.
 fn draw_plot_with_axes(
     painter: &egui::Painter,
@@ -564,63 +608,63 @@ fn draw_plot_with_axes(
     }
 
     // Draw delta cursor line if enabled and positioned.
-    if plot_state.delta_enabled {
-        if let Some(delta_time) = plot_state.delta_time {
-            // Only draw if cursor is within visible time range.
-            if delta_time >= panned_time_min && delta_time <= panned_time_max {
-                // Calculate cursor X position.
-                let x_ratio = (delta_time as f64 - panned_time_min as f64) / (panned_time_max as f64 - panned_time_min as f64);
-                let delta_x = plot_rect.min.x + (x_ratio as f32 * plot_rect.width());
-                
-                // Choose delta cursor colour (different from main cursor).
-                let delta_colour = colours::ts_delta_cursor_colour(dark_mode);
-                
-                // Draw vertical delta cursor line (dashed for distinction).
-                let dash_length = 4.0;
-                let gap_length = 2.0;
-                let mut y = plot_rect.min.y;
-                
-                while y < plot_rect.max.y {
-                    let segment_end = (y + dash_length).min(plot_rect.max.y);
-                    painter.line_segment(
-                        [egui::pos2(delta_x, y), 
-                        egui::pos2(delta_x, segment_end)],
-                        egui::Stroke::new(1.0, delta_colour),
-                    );
-                    y = segment_end + gap_length;
-                }
-                
-                // Draw time label at the top of the delta cursor.
-                let time_text = helpers_ts::unix_time_to_hms(delta_time);
-                let label_bg = colours::ts_delta_cursor_label_colour(dark_mode);
-                let cursor_label_text_colour = colours::cursor_label_text_colour(dark_mode);
-                
-                // Calculate label size and position.
-                let font_id = egui::FontId::proportional(10.0);
-                let galley = painter.layout_no_wrap(time_text.clone(), font_id.clone(), cursor_label_text_colour);
-                let label_width = galley.rect.width() + 8.0;
-                let label_height = galley.rect.height() + 4.0;
-                
-                // Position label above plot, between the top of the chart and the chart title.
-                let label_rect = egui::Rect::from_center_size(
-                    egui::pos2(delta_x, plot_rect.min.y - 10.0),
-                    egui::vec2(label_width, label_height)
-                );
-                
-                // Draw label background.
-                painter.rect_filled(label_rect, 3.0, label_bg);
-                painter.rect_stroke(label_rect, 3.0, egui::Stroke::new(1.0, delta_colour), egui::epaint::StrokeKind::Inside);
-                
-                // Draw label text.
-                painter.text(
-                    label_rect.center(),
-                    egui::Align2::CENTER_CENTER,
-                    time_text,
-                    font_id,
-                    cursor_label_text_colour,
+    if plot_state.delta_enabled
+        && let Some(delta_time) = plot_state.delta_time
+    {
+        // Only draw if cursor is within visible time range.
+        if delta_time >= panned_time_min && delta_time <= panned_time_max {
+            // Calculate cursor X position.
+            let x_ratio = (delta_time as f64 - panned_time_min as f64) / (panned_time_max as f64 - panned_time_min as f64);
+            let delta_x = plot_rect.min.x + (x_ratio as f32 * plot_rect.width());
+            
+            // Choose delta cursor colour (different from main cursor).
+            let delta_colour = colours::ts_delta_cursor_colour(dark_mode);
+            
+            // Draw vertical delta cursor line (dashed for distinction).
+            let dash_length = 4.0;
+            let gap_length = 2.0;
+            let mut y = plot_rect.min.y;
+            
+            while y < plot_rect.max.y {
+                let segment_end = (y + dash_length).min(plot_rect.max.y);
+                painter.line_segment(
+                    [egui::pos2(delta_x, y), 
+                    egui::pos2(delta_x, segment_end)],
+                    egui::Stroke::new(1.0, delta_colour),
                 );
-                
+                y = segment_end + gap_length;
             }
+            
+            // Draw time label at the top of the delta cursor.
+            let time_text = helpers_ts::unix_time_to_hms(delta_time);
+            let label_bg = colours::ts_delta_cursor_label_colour(dark_mode);
+            let cursor_label_text_colour = colours::cursor_label_text_colour(dark_mode);
+            
+            // Calculate label size and position.
+            let font_id = egui::FontId::proportional(10.0);
+            let galley = painter.layout_no_wrap(time_text.clone(), font_id.clone(), cursor_label_text_colour);
+            let label_width = galley.rect.width() + 8.0;
+            let label_height = galley.rect.height() + 4.0;
+            
+            // Position label above plot, between the top of the chart and the chart title.
+            let label_rect = egui::Rect::from_center_size(
+                egui::pos2(delta_x, plot_rect.min.y - 10.0),
+                egui::vec2(label_width, label_height)
+            );
+            
+            // Draw label background.
+            painter.rect_filled(label_rect, 3.0, label_bg);
+            painter.rect_stroke(label_rect, 3.0, egui::Stroke::new(1.0, delta_colour), egui::epaint::StrokeKind::Inside);
+            
+            // Draw label text.
+            painter.text(
+                label_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                time_text,
+                font_id,
+                cursor_label_text_colour,
+            );
+            
         }
     }
 
@@ -897,59 +941,60 @@ fn draw_plot_with_axes(
     plot_data_points(painter, &plot_rect, dataset, panned_time_min, panned_time_max, y_min, y_max, dark_mode);
 
     // Draw cursor line if enabled and positioned.
-    if plot_state.cursor_enabled {
-        if let Some(cursor_time) = plot_state.cursor_time {
-            // Only draw if cursor is within visible time range.
-            if cursor_time >= panned_time_min && cursor_time <= panned_time_max {
-                // Calculate cursor X position.
-                let x_ratio = (cursor_time as f64 - panned_time_min as f64) / (panned_time_max as f64 - panned_time_min as f64);
-                let cursor_x = plot_rect.min.x + (x_ratio as f32 * plot_rect.width());
-                
-                // Choose cursor colour (bright and visible).
-                let cursor_colour = colours::ts_cursor_colour(dark_mode);
-                
-                // Draw vertical cursor line (thin).
-                painter.line_segment(
-                    [egui::pos2(cursor_x, plot_rect.min.y), 
-                     egui::pos2(cursor_x, plot_rect.max.y)],
-                    egui::Stroke::new(1.0, cursor_colour),
-                );
-                
-                // Draw time label at the bottom of the cursor (between plot and time scale).
-                let time_text = helpers_ts::unix_time_to_hms(cursor_time);
-                let label_bg = colours::ts_cursor_label_colour(dark_mode);
-                let cursor_label_text_colour = colours::cursor_label_text_colour(dark_mode);
-                
-                // Calculate label size and position.
-                let font_id = egui::FontId::proportional(10.0);
-                let galley = painter.layout_no_wrap(time_text.clone(), font_id.clone(), cursor_label_text_colour);
-                let label_width = galley.rect.width() + 8.0;
-                let label_height = galley.rect.height() + 4.0;
-                
-                // Position label below plot, between the X-axis and time labels.
-                let label_rect = egui::Rect::from_center_size(
-                    egui::pos2(cursor_x, plot_rect.max.y + 10.0),
-                    egui::vec2(label_width, label_height)
-                );
-                
-                // Draw label background.
-                painter.rect_filled(label_rect, 3.0, label_bg);
-                painter.rect_stroke(label_rect, 3.0, egui::Stroke::new(1.0, cursor_colour), egui::epaint::StrokeKind::Inside);
-                
-                // Draw label text.
-                painter.text(
-                    label_rect.center(),
-                    egui::Align2::CENTER_CENTER,
-                    time_text,
-                    font_id,
-                    cursor_label_text_colour,
-                );
-            }
+    if plot_state.cursor_enabled
+        && let Some(cursor_time) = plot_state.cursor_time
+    {
+        // Only draw if cursor is within visible time range.
+        if cursor_time >= panned_time_min && cursor_time <= panned_time_max {
+            // Calculate cursor X position.
+            let x_ratio = (cursor_time as f64 - panned_time_min as f64) / (panned_time_max as f64 - panned_time_min as f64);
+            let cursor_x = plot_rect.min.x + (x_ratio as f32 * plot_rect.width());
+            
+            // Choose cursor colour (bright and visible).
+            let cursor_colour = colours::ts_cursor_colour(dark_mode);
+            
+            // Draw vertical cursor line (thin).
+            painter.line_segment(
+                [egui::pos2(cursor_x, plot_rect.min.y), 
+                 egui::pos2(cursor_x, plot_rect.max.y)],
+                egui::Stroke::new(1.0, cursor_colour),
+            );
+            
+            // Draw time label at the bottom of the cursor (between plot and time scale).
+            let time_text = helpers_ts::unix_time_to_hms(cursor_time);
+            let label_bg = colours::ts_cursor_label_colour(dark_mode);
+            let cursor_label_text_colour = colours::cursor_label_text_colour(dark_mode);
+            
+            // Calculate label size and position.
+            let font_id = egui::FontId::proportional(10.0);
+            let galley = painter.layout_no_wrap(time_text.clone(), font_id.clone(), cursor_label_text_colour);
+            let label_width = galley.rect.width() + 8.0;
+            let label_height = galley.rect.height() + 4.0;
+            
+            // Position label below plot, between the X-axis and time labels.
+            let label_rect = egui::Rect::from_center_size(
+                egui::pos2(cursor_x, plot_rect.max.y + 10.0),
+                egui::vec2(label_width, label_height)
+            );
+            
+            // Draw label background.
+            painter.rect_filled(label_rect, 3.0, label_bg);
+            painter.rect_stroke(label_rect, 3.0, egui::Stroke::new(1.0, cursor_colour), egui::epaint::StrokeKind::Inside);
+            
+            // Draw label text.
+            painter.text(
+                label_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                time_text,
+                font_id,
+                cursor_label_text_colour,
+            );
         }
     }
 }
 
 // Helper function to plot the actual data points.
+#[allow(clippy::too_many_arguments)]
 fn plot_data_points(
     painter: &egui::Painter,
     plot_rect: &egui::Rect,
@@ -1153,10 +1198,10 @@ fn plot_data_points(
                 // Determine baseline Y position for this trace.
                 let baseline_value = if is_active_high {
                     // For active HIGH, baseline is the low value (first point after trip start).
-                    trace.get(0).map(|p| p.point_value).unwrap_or(0.0)
+                    trace.first().map(|p| p.point_value).unwrap_or(0.0)
                 } else {
                     // For active LOW, baseline is the high value (first point after trip start).
-                    trace.get(0).map(|p| p.point_value).unwrap_or(0.0)
+                    trace.first().map(|p| p.point_value).unwrap_or(0.0)
                 };
                 
                 let baseline_y_normalized = (baseline_value as f64 - y_min as f64) / y_range;
@@ -1429,11 +1474,9 @@ fn plot_data_points(
     // Optional.
     // Draw small circles at data points.
     // But not for digital plots as doesn't look as nice.
-    if SHOW_MARKERS {
-        if dataset.data_type != "Digital" {
-            for point in &screen_points {
-                painter.circle_filled(*point, 2.0, line_colour);
-            }
+    if SHOW_MARKERS && dataset.data_type != "Digital" {
+        for point in &screen_points {
+            painter.circle_filled(*point, 2.0, line_colour);
         }
     }
 }
@@ -0,0 +1,139 @@
+// Log timestamp parsing.
+// Dates can be logged day, month or year first, with 12 or 24 hour
+// times, in the time zone of the controller.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+// Order of the day, month and year in logged dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateOrder {
+    #[default]
+    Dmy,
+    Mdy,
+    Ymd,
+}
+
+// How to read timestamps in a log.
+// The source time zone is the time zone the controller logs in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestampFormat {
+    pub date_order: DateOrder,
+    pub source_tz: Tz,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self {
+            date_order: DateOrder::Dmy,
+            source_tz: Tz::UTC,
+        }
+    }
+}
+
+impl DateOrder {
+    // strftime format for displaying dates in this order.
+    pub fn date_format(&self) -> &'static str {
+        match self {
+            Self::Dmy => "%d/%m/%Y",
+            Self::Mdy => "%m/%d/%Y",
+            Self::Ymd => "%Y/%m/%d",
+        }
+    }
+
+    // Name for menus.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Dmy => "DD/MM/YYYY",
+            Self::Mdy => "MM/DD/YYYY",
+            Self::Ymd => "YYYY/MM/DD",
+        }
+    }
+}
+
+impl TimestampFormat {
+    // Parse a logged date and time, as local time of the controller.
+    // Times may have fractional seconds, and may be 12 hour with AM or PM.
+    pub fn parse_local(&self, date: &str, time: &str) -> Option<NaiveDateTime> {
+        Some(NaiveDateTime::new(self.parse_date(date)?, parse_time(time)?))
+    }
+
+    // Parse a logged date and time to UTC.
+    // Ambiguous local times (when clocks go back) take the earlier time.
+    pub fn parse_utc(&self, date: &str, time: &str) -> Option<DateTime<Utc>> {
+        let local = self.parse_local(date, time)?;
+        self.source_tz.from_local_datetime(&local)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+
+    fn parse_date(&self, date: &str) -> Option<NaiveDate> {
+        let parts: Vec<u32> = date.split(['/', '-', '.'])
+            .map(|part| part.parse().ok())
+            .collect::<Option<_>>()?;
+        let [a, b, c] = parts[..] else {
+            return None;
+        };
+
+        let (year, month, day) = match self.date_order {
+            DateOrder::Dmy => (c, b, a),
+            DateOrder::Mdy => (c, a, b),
+            DateOrder::Ymd => (a, b, c),
+        };
+        NaiveDate::from_ymd_opt(year as i32, month, day)
+    }
+}
+
+// Parse a time of day, as 24 hour or 12 hour with AM or PM.
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    let time = time.trim();
+    let upper = time.to_ascii_uppercase();
+    let (clock, pm) = if let Some(clock) = upper.strip_suffix("PM") {
+        (clock.trim_end(), Some(true))
+    } else if let Some(clock) = upper.strip_suffix("AM") {
+        (clock.trim_end(), Some(false))
+    } else {
+        (upper.as_str(), None)
+    };
+
+    let mut parts = clock.splitn(3, ':');
+    let mut hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = parts.next()?.parse().ok()?;
+    let seconds = parts.next()?;
+    let (second, nano) = match seconds.split_once('.') {
+        Some((second, fraction)) => {
+            // Fractions are scaled to nanoseconds.
+            if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let digits = &fraction[..fraction.len().min(9)];
+            let nano: u32 = digits.parse().ok()?;
+            (second.parse().ok()?, nano * 10u32.pow(9 - digits.len() as u32))
+        }
+        None => (seconds.parse().ok()?, 0),
+    };
+
+    // 12 AM is midnight and 12 PM is midday.
+    if let Some(pm) = pm {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = match (hour, pm) {
+            (12, false) => 0,
+            (12, true) => 12,
+            (hour, true) => hour + 12,
+            (hour, false) => hour,
+        };
+    }
+
+    NaiveTime::from_hms_nano_opt(hour, minute, second, nano)
+}
+
+// Format a timestamp for display in the given time zone.
+pub fn format_timestamp(timestamp: &DateTime<Utc>, tz: Tz, date_order: DateOrder) -> String {
+    timestamp.with_timezone(&tz)
+        .format(&format!("{} %H:%M:%S", date_order.date_format()))
+        .to_string()
+}
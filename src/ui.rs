@@ -2,16 +2,24 @@
 
 use log::info;
 
+use chrono_tz::{Tz, TZ_VARIANTS};
 use eframe::{egui};
 use egui::epaint::{CornerRadius};
 
+use scraper::{DateOrder, IngestOptions, IngestSource};
+
 use crate::gps_plot;
 use crate::time_series_plot;
 use crate::colours;
 use crate::app::MyApp;
 use crate::help_content;
 use crate::changelog_content;
-use crate::setting_up::DETAILS;
+use crate::parse_issues;
+use crate::controller_history;
+use crate::exporting::{self, ExportKind, PlotImage, TripScope};
+use crate::plot_image::{ImageFormat, IMAGE_SCALES};
+use crate::setting_up::{update_settings, DETAILS, SETTINGS};
+use crate::scraper::LogFormatChoice;
 
 // Function to draw the menu bar.
 pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
@@ -25,6 +33,64 @@ pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
                     app.scraper.load_file(ctx, &mut app.selected_id);
                     ui.close_menu();
                 }
+                if app.scraper.is_live() {
+                    if ui.button("Disconnect").clicked() {
+                        app.scraper.disconnect();
+                        ui.close_menu();
+                    }
+                } else if ui.button("Connect...").clicked() {
+                    info!("Connect button clicked.");
+                    app.show_connect = true;
+                    ui.close_menu();
+                }
+                ui.separator();
+                // Only single uncompressed files can be followed.
+                let mut following = app.scraper.following;
+                let can_follow = app.scraper.following || app.scraper.can_follow();
+                if ui.add_enabled(can_follow, egui::Checkbox::new(&mut following, "Follow"))
+                    .on_hover_text("Keep adding events as they are written to the file.")
+                    .on_disabled_hover_text("Only a single uncompressed log file can be followed.")
+                    .changed()
+                {
+                    app.scraper.set_following(ctx, following);
+                }
+                ui.separator();
+                // CSV exports follow the Show menu settings.
+                ui.add_enabled_ui(!app.scraper.scrapings.is_empty(), |ui| {
+                    ui.menu_button("Export", |ui| {
+                        let export = |ui: &mut egui::Ui, app: &mut MyApp, kind: ExportKind| {
+                            if ui.add_enabled(kind.is_enabled(app), egui::Button::new(kind.label())).clicked() {
+                                info!("Export {:?} clicked.", kind);
+                                exporting::export(app, kind);
+                                ui.close_menu();
+                            }
+                        };
+                        for kind in ExportKind::TABLES {
+                            export(ui, app, kind);
+                        }
+                        ui.separator();
+                        for (name, kind) in ExportKind::MAPS {
+                            ui.menu_button(name, |ui| {
+                                export(ui, app, kind(TripScope::Selected));
+                                export(ui, app, kind(TripScope::All));
+                            });
+                        }
+                    });
+                });
+                ui.separator();
+                // Changing the log format reloads the files.
+                ui.menu_button(format!("Format: {}", app.scraper.log_format.label()), |ui| {
+                    let mut log_format = app.scraper.log_format;
+                    for choice in [LogFormatChoice::Detect, LogFormatChoice::Controller, LogFormatChoice::Csv] {
+                        ui.radio_value(&mut log_format, choice, choice.label());
+                    }
+                    if log_format != app.scraper.log_format {
+                        info!("Log format changed to {}.", log_format.label());
+                        app.scraper.log_format = log_format;
+                        app.scraper.reload_file(ctx);
+                        ui.close_menu();
+                    }
+                });
             });
 
             // Show menu.
@@ -34,6 +100,7 @@ pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
                 ui.checkbox(&mut app.show_debug_events, "Debug events");
                 ui.separator();                
                 ui.checkbox(&mut app.show_gps_events, "GPS event data");
+                ui.checkbox(&mut app.show_raw_lines, "Raw log lines");
                 ui.separator();                
                 ui.checkbox(&mut app.show_oot_events, "Out of trip events");
                 ui.checkbox(&mut app.show_unsupported_events, "Unsupported events");
@@ -71,6 +138,8 @@ pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
                 ui.separator();
                 ui.checkbox(&mut app.plot_battery, "Plot Battery Voltage");
                 ui.checkbox(&mut app.plot_speed, "Plot Speed");
+                ui.separator();
+                ui.checkbox(&mut app.hide_poor_gps, "Hide poor GPS fixes");
             });
 
             // View menu.
@@ -86,6 +155,49 @@ pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
                     app.dark_mode = !app.dark_mode;
                     ui.close_menu();
                 }
+                ui.separator();
+                if ui.button("Parse issues").clicked() {
+                    info!("Parse issues button clicked.");
+                    app.show_parse_issues = true;
+                    ui.close_menu();
+                }
+                if ui.button("Controller history").clicked() {
+                    info!("Controller history button clicked.");
+                    app.show_controller_history = true;
+                    ui.close_menu();
+                }
+            });
+
+            // Time menu.
+            // Date order and source time zone apply to the loaded file,
+            // so changing them reloads it. All are saved in the settings.
+            ui.menu_button("Time", |ui| {
+                ui.label("Log date order");
+                let mut date_order = app.scraper.timestamp_format.date_order;
+                for order in [DateOrder::Dmy, DateOrder::Mdy, DateOrder::Ymd] {
+                    ui.radio_value(&mut date_order, order, order.label());
+                }
+                if date_order != app.scraper.timestamp_format.date_order {
+                    info!("Log date order changed to {:?}.", date_order);
+                    app.scraper.timestamp_format.date_order = date_order;
+                    update_settings(|settings| settings.date_order = date_order);
+                    app.scraper.reload_file(ctx);
+                }
+                ui.separator();
+                let source_tz = app.scraper.timestamp_format.source_tz;
+                if let Some(tz) = time_zone_menu(ui, "Log time zone", source_tz) {
+                    info!("Log time zone changed to {}.", tz);
+                    app.scraper.timestamp_format.source_tz = tz;
+                    update_settings(|settings| settings.source_time_zone = tz.name().to_string());
+                    app.scraper.reload_file(ctx);
+                    ui.close_menu();
+                }
+                let display_tz = SETTINGS.lock().unwrap().display_tz();
+                if let Some(tz) = time_zone_menu(ui, "Display time zone", display_tz) {
+                    info!("Display time zone changed to {}.", tz);
+                    update_settings(|settings| settings.display_time_zone = tz.name().to_string());
+                    ui.close_menu();
+                }
             });
 
             // Help menu.
@@ -119,6 +231,22 @@ pub fn draw_menu_bar(app: &mut MyApp, ctx: &egui::Context) {
     });
 }
 
+// Sub menu to pick a time zone.
+// Returns the time zone if a different one was picked.
+fn time_zone_menu(ui: &mut egui::Ui, title: &str, current: Tz) -> Option<Tz> {
+    let mut picked = None;
+    ui.menu_button(format!("{}: {}", title, current.name()), |ui| {
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            for tz in TZ_VARIANTS {
+                if ui.selectable_label(tz == current, tz.name()).clicked() && tz != current {
+                    picked = Some(tz);
+                }
+            }
+        });
+    });
+    picked
+}
+
 // Function to draw the bottom status panel.
 // This is a strip at the bottom of the screen to show
 // controller details.
@@ -138,32 +266,50 @@ pub fn draw_bottom_panel(app: &mut MyApp, ctx: &egui::Context) {
                     
                     // Display selected file info if available.
                     if let Some(filename) = app.scraper.get_selected_filename() {
-                        ui.label("File:");
-                        ui.strong(filename);
+                        // Several files are merged, show them all.
+                        if app.scraper.sources.len() > 1 {
+                            let paths: Vec<String> = app.scraper.sources.iter()
+                                .map(|source| source.path.display().to_string())
+                                .collect();
+                            ui.label(format!("Files ({}):", app.scraper.sources.len()));
+                            ui.strong(filename).on_hover_text(paths.join("\n"));
+                        } else {
+                            ui.label("File:");
+                            ui.strong(filename);
+                        }
                         
                         // Add controller ID if available.
-                        if !app.scraper.controller_id.is_empty() {
-                            ui.separator();
-                            ui.label("Controller:");
+                        // If the controller was swapped show the first and last.
+                        let controller_ids = app.scraper.controller_ids();
+                        ui.separator();
+                        ui.label("Controller:");
+                        if let [first, .., last] = controller_ids.as_slice() {
+                            ui.strong(format!("{:0>6} to {:0>6} ({} controllers)", first, last, controller_ids.len()));
+                        } else if !app.scraper.controller_id.is_empty() {
                             ui.strong(format!("{:0>6}", app.scraper.controller_id));
-                        }
-                        else {
-                            ui.separator();
-                            ui.label("Controller:");
+                        } else {
                             ui.strong("Not defined.");
                         }
 
                         // Add controller firmware version if available.
-                        if !app.scraper.controller_fw.is_empty() {
-                            ui.separator();
-                            ui.label("Firmware:");
-                            ui.strong(format!("{}", app.scraper.controller_fw));
-                        }
-                        else {
-                            ui.separator();
-                            ui.label("Firmware:");
+                        // If the firmware was upgraded show the first and last.
+                        let firmware_versions = app.scraper.firmware_versions();
+                        ui.separator();
+                        ui.label("Firmware:");
+                        if let [first, .., last] = firmware_versions.as_slice() {
+                            ui.strong(format!("{} to {} ({} versions)", first, last, firmware_versions.len()));
+                        } else if !app.scraper.controller_fw.is_empty() {
+                            ui.strong(&app.scraper.controller_fw);
+                        } else {
                             ui.strong("Not defined.");
                         }
+
+                        // Shortcut to the controller history window.
+                        if !app.scraper.controller_history.is_empty()
+                            && ui.small_button("History").clicked()
+                        {
+                            app.show_controller_history = true;
+                        }
                     } else {
                         ui.label("No file selected.");
                     }
@@ -179,8 +325,37 @@ pub fn draw_bottom_panel(app: &mut MyApp, ctx: &egui::Context) {
                     );
 
                     // Program status.
+                    // While loading show progress and allow the load to be cancelled.
                     ui.label("Status:");
-                    ui.strong(app.scraper.get_processing_status());
+                    if let Some(progress) = app.scraper.load_progress {
+                        let fraction = if progress.total_bytes > 0 {
+                            // Size of gzip files is only an estimate.
+                            (progress.bytes_read as f32 / progress.total_bytes as f32).min(1.0)
+                        } else {
+                            0.0
+                        };
+                        ui.add(egui::ProgressBar::new(fraction)
+                            .desired_width(200.0)
+                            .text(format!("{:.1} / {:.1} MB, {} events",
+                                progress.bytes_read as f64 / 1_048_576.0,
+                                progress.total_bytes as f64 / 1_048_576.0,
+                                progress.events)));
+                        if ui.button("Cancel").clicked() {
+                            app.scraper.cancel_loading();
+                        }
+                    } else {
+                        ui.strong(app.scraper.get_processing_status());
+                        if app.scraper.is_live() && ui.small_button("Disconnect").clicked() {
+                            app.scraper.disconnect();
+                        }
+
+                        // Shortcut to the parse issues window.
+                        if !app.scraper.diagnostics.is_empty()
+                            && ui.small_button("Show issues").clicked()
+                        {
+                            app.show_parse_issues = true;
+                        }
+                    }
 
                     // Selected trip at any level.
                     ui.separator();
@@ -188,7 +363,7 @@ pub fn draw_bottom_panel(app: &mut MyApp, ctx: &egui::Context) {
                         
                         // Right justified so add labels from right to left.
                         if let Some(id) = &app.selected_id {
-                            ui.strong(format!("{:<10}", id));
+                            ui.strong(format!("{:<10}", app.scraper.trip_num(id).unwrap_or(id)));
                         } else {
                             ui.strong(format!("{:>10}", ""));
                         }                   
@@ -226,12 +401,130 @@ pub fn draw_central_panel(app: &mut MyApp, ctx: &egui::Context) {
             app.show_report_events,
             app.show_debug_events,
             app.show_gps_events,
+            app.show_raw_lines,
             &mut app.selected_id,
             app.dark_mode,
         );
     });
 }
 
+// Function to draw the zip archive file picker.
+// Shown when a zip archive with more than one file is opened.
+// Picking several files merges them.
+pub fn draw_zip_picker(app: &mut MyApp, ctx: &egui::Context) {
+    let dark_mode = app.dark_mode;
+    let Some(picker) = &mut app.scraper.zip_picker else {
+        return;
+    };
+
+    let mut load = false;
+    let mut cancelled = false;
+    let archive_name = picker.path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    egui::Window::new("Open from zip archive")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .frame(egui::Frame::window(&ctx.style()).stroke(egui::Stroke::new(3.0, colours::border_colour(dark_mode))))
+        .show(ctx, |ui| {
+            ui.label(format!("Select log files from {}:", archive_name));
+            ui.separator();
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                egui::Grid::new("zip_members_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (member, picked) in picker.members.iter().zip(picker.picked.iter_mut()) {
+                            ui.checkbox(picked, &member.name);
+                            ui.label(format!("{:.1} KB", member.size as f64 / 1024.0));
+                            ui.end_row();
+                        }
+                    });
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(picker.picked.contains(&true), egui::Button::new("Load")).clicked() {
+                    load = true;
+                }
+                if ui.button("Select all").clicked() {
+                    picker.picked.iter_mut().for_each(|picked| *picked = true);
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if load {
+        let members: Vec<String> = picker.members.iter()
+            .zip(&picker.picked)
+            .filter(|(_, picked)| **picked)
+            .map(|(member, _)| member.name.clone())
+            .collect();
+        app.selected_id = None;
+        app.scraper.load_zip_members(ctx, members);
+    } else if cancelled {
+        info!("Zip archive file selection cancelled.");
+        app.scraper.zip_picker = None;
+        app.scraper.processing_status = "No file selected.".to_string();
+    }
+}
+
+// Function to draw the connect dialog.
+// For reading the log live from a controller,
+// over a serial port or a TCP socket.
+pub fn draw_connect_dialog(app: &mut MyApp, ctx: &egui::Context) {
+    if !app.show_connect {
+        return;
+    }
+
+    let mut connect = false;
+    egui::Window::new("Connect to controller")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .frame(egui::Frame::window(&ctx.style()).stroke(egui::Stroke::new(3.0, colours::border_colour(app.dark_mode))))
+        .show(ctx, |ui| {
+            egui::Grid::new("connect_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Device or address:");
+                    ui.add(egui::TextEdit::singleline(&mut app.connect_address)
+                        .hint_text("/dev/ttyUSB0, COM3 or host:port"));
+                    ui.end_row();
+
+                    // Baud rate is only used for serial ports.
+                    ui.label("Baud rate:");
+                    ui.add(egui::DragValue::new(&mut app.connect_baud_rate).range(300..=4_000_000));
+                    ui.end_row();
+
+                    ui.checkbox(&mut app.connect_capture, "Save capture to:");
+                    ui.add_enabled(app.connect_capture, egui::TextEdit::singleline(&mut app.connect_capture_path));
+                    ui.end_row();
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!app.connect_address.trim().is_empty(), egui::Button::new("Connect")).clicked() {
+                    connect = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    app.show_connect = false;
+                }
+            });
+        });
+
+    if connect {
+        let mut options = IngestOptions::new(IngestSource::parse(&app.connect_address, app.connect_baud_rate));
+        if app.connect_capture && !app.connect_capture_path.trim().is_empty() {
+            options.capture = Some(app.connect_capture_path.trim().into());
+        }
+        app.show_connect = false;
+        app.selected_id = None;
+        app.scraper.connect(ctx, options);
+    }
+}
+
 // Function to draw the About dialog.
 pub fn draw_about_dialog(app: &mut MyApp, ctx: &egui::Context) {
     if app.show_about {
@@ -321,12 +614,8 @@ pub fn draw_help_panel(app: &mut MyApp, ctx: &egui::Context) {
                 // Draw border around the help window.
                 draw_viewport_border(ctx, app.dark_mode);
                 
-                // Determine the background color based on dark_mode.
-                let background_color = if app.dark_mode {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                } else {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                };
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
 
                 egui::CentralPanel::default()
                     .frame(egui::Frame::default()
@@ -396,12 +685,8 @@ pub fn draw_gps_plot_window(app: &mut MyApp, ctx: &egui::Context) {
                 // Draw border around the gps plot window.
                 draw_viewport_border(ctx, app.dark_mode);
 
-                // Determine the background colour based on dark_mode.
-                let background_color = if app.dark_mode {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                } else {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                };
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
 
                 egui::CentralPanel::default()
                     .frame(egui::Frame::default()
@@ -411,12 +696,16 @@ pub fn draw_gps_plot_window(app: &mut MyApp, ctx: &egui::Context) {
                     )
                     .show(ctx, |ui| {
                         ui.vertical(|ui| {
+                            // Size of the plot, for saving as an image.
+                            let plot_size = ui.available_size();
+
                             // Header section.
                             ui.horizontal(|ui| {
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                     if ui.button("Close").clicked() {
                                         app.show_gps_plot = false;
                                     }
+                                    export_image_menu(ui, app, PlotImage::Gps, plot_size);
                                 });
                             });
 
@@ -432,21 +721,21 @@ pub fn draw_gps_plot_window(app: &mut MyApp, ctx: &egui::Context) {
                                         // Pass the Option<HttpTiles> directly, and unwrap it safely within the function.
                                         // Ensure app.map_tiles is Some(HttpTiles) when this path is taken.
                                         if let Some(map_tiles) = &mut app.map_tiles {
-                                            gps_plot::plot_gps_data_with_tiles(ui, &app.scraper, &app.selected_id, &mut app.map_memory, map_tiles, &mut app.last_trip_id, &mut app.map_state);
+                                            gps_plot::plot_gps_data_with_tiles(ui, &app.scraper, &app.selected_id, &mut app.map_memory, map_tiles, &mut app.last_trip_id, &mut app.map_state, app.hide_poor_gps);
                                         } else {
                                             ui.label("Error: Street tiles not initialized.");
                                         }
                                     }
                                     else if app.use_satellite_tiles {
                                         if let Some(map_tiles) = &mut app.satellite_tiles {
-                                            gps_plot::plot_gps_data_with_tiles(ui, &app.scraper, &app.selected_id, &mut app.map_memory, map_tiles, &mut app.last_trip_id, &mut app.map_state);
+                                            gps_plot::plot_gps_data_with_tiles(ui, &app.scraper, &app.selected_id, &mut app.map_memory, map_tiles, &mut app.last_trip_id, &mut app.map_state, app.hide_poor_gps);
                                         } else {
                                             ui.label("Error: Satellite tiles not initialized.");
                                         }
                                     }
                                     else {
                                         // gps_plot::plot_gps_data(ui, &app.scraper, &app.selected_id);
-                                        gps_plot::plot_gps_data(ui, &app.scraper, &app.selected_id, &mut app.plot_view_state, &mut app.last_trip_id_plain );
+                                        gps_plot::plot_gps_data(ui, &app.scraper, &app.selected_id, &mut app.plot_view_state, &mut app.last_trip_id_plain, app.hide_poor_gps);
                                     }
                                 }
                             );
@@ -457,6 +746,26 @@ pub fn draw_gps_plot_window(app: &mut MyApp, ctx: &egui::Context) {
     }
 }
 
+// Export image menu for a plot window.
+// Plots of the selected trip are saved as PNG at the chosen scale, or as SVG.
+fn export_image_menu(ui: &mut egui::Ui, app: &mut MyApp, plot: PlotImage, plot_size: egui::Vec2) {
+    ui.add_enabled_ui(exporting::selected_trip(app).is_some(), |ui| {
+        ui.menu_button("Export image", |ui| {
+            ui.label("PNG resolution:");
+            for scale in IMAGE_SCALES {
+                ui.radio_value(&mut app.image_scale, scale, format!("{}x", scale));
+            }
+            ui.separator();
+            for format in [ImageFormat::Png, ImageFormat::Svg] {
+                if ui.button(format.label()).clicked() {
+                    ui.close_menu();
+                    exporting::export_plot_image(app, plot, format, plot_size);
+                }
+            }
+        });
+    });
+}
+
 // Function to draw the time series plot window as a separate viewport.
 pub fn draw_time_series_window(app: &mut MyApp, ctx: &egui::Context) {
     if app.show_time_series {
@@ -491,12 +800,8 @@ pub fn draw_time_series_window(app: &mut MyApp, ctx: &egui::Context) {
                 // Draw border around the time series plot window.
                 draw_viewport_border(ctx, app.dark_mode);
 
-                // Determine the background colour based on dark_mode.
-                let background_color = if app.dark_mode {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                } else {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                };
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
 
                 egui::CentralPanel::default()
                     .frame(egui::Frame::default()
@@ -506,12 +811,16 @@ pub fn draw_time_series_window(app: &mut MyApp, ctx: &egui::Context) {
                     )
                     .show(ctx, |ui| {
                         ui.vertical(|ui| {
+                            // Size of the plots, for saving as an image.
+                            let plot_size = ui.available_size();
+
                             // Header section.
                             ui.horizontal(|ui| {
                                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                     if ui.button("Close").clicked() {
                                         app.show_time_series = false;
                                     }
+                                    export_image_menu(ui, app, PlotImage::TimeSeries, plot_size);
                                 });
                             });
 
@@ -526,6 +835,7 @@ pub fn draw_time_series_window(app: &mut MyApp, ctx: &egui::Context) {
                                 &app.dark_mode,
                                 app.plot_battery,
                                 app.plot_speed,
+                                app.hide_poor_gps,
                             );
                         });
                     });
@@ -567,12 +877,8 @@ pub fn draw_changelog(app: &mut MyApp, ctx: &egui::Context) {
                 // Draw border around the changelog window.
                 draw_viewport_border(ctx, app.dark_mode);
                 
-                // Determine the background colour based on dark_mode.
-                let background_color = if app.dark_mode {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                } else {
-                    ctx.style().visuals.widgets.noninteractive.bg_fill
-                };
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
 
                 egui::CentralPanel::default()
                     .frame(egui::Frame::default()
@@ -599,6 +905,126 @@ pub fn draw_changelog(app: &mut MyApp, ctx: &egui::Context) {
     }
 }
 
+// Function to draw the parse issues window.
+// Shows problems found while parsing the log.
+pub fn draw_parse_issues(app: &mut MyApp, ctx: &egui::Context) {
+
+    // Lock the global DETAILS to obtain access to the Details object.
+    let details = DETAILS.lock().unwrap().clone();
+
+    if app.show_parse_issues {
+        // Create a detached window in its own viewport.
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("parse_issues_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Parse issues")
+                .with_inner_size([details.parse_issues_win_width, details.parse_issues_win_height])
+                .with_resizable(true),
+            |ctx, class| {
+                assert!(class == egui::ViewportClass::Immediate);
+
+                // Apply theme according to menu selection. This should be inside
+                // the closure to ensure it's re-evaluated every frame.
+                if app.dark_mode {
+                    ctx.set_visuals(egui::Visuals::dark());
+                } else {
+                    ctx.set_visuals(egui::Visuals::light());
+                }
+
+                // Check if close was requested via the window's X button.
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    app.show_parse_issues = false;
+                }
+
+                // Draw border around the parse issues window.
+                draw_viewport_border(ctx, app.dark_mode);
+
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
+
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::default()
+                        .stroke(egui::Stroke::new(2.0, colours::border_colour(app.dark_mode)))
+                        .inner_margin(egui::Margin::same(8))
+                        .fill(background_color)
+                    )
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            // Close parse issues window.
+                            ui.separator();
+                            if ui.button("Close").clicked() {
+                                app.show_parse_issues = false;
+                            }
+                        });
+                        ui.separator();
+
+                        parse_issues::draw_parse_issues_content(ui, app);
+                    });
+            },
+        );
+    }
+}
+
+// Function to draw the controller history window.
+// Shows every controller id and firmware version found in the log.
+pub fn draw_controller_history(app: &mut MyApp, ctx: &egui::Context) {
+
+    // Lock the global DETAILS to obtain access to the Details object.
+    let details = DETAILS.lock().unwrap().clone();
+
+    if app.show_controller_history {
+        // Create a detached window in its own viewport.
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("controller_history_window"),
+            egui::ViewportBuilder::default()
+                .with_title("Controller history")
+                .with_inner_size([details.controller_history_win_width, details.controller_history_win_height])
+                .with_resizable(true),
+            |ctx, class| {
+                assert!(class == egui::ViewportClass::Immediate);
+
+                // Apply theme according to menu selection. This should be inside
+                // the closure to ensure it's re-evaluated every frame.
+                if app.dark_mode {
+                    ctx.set_visuals(egui::Visuals::dark());
+                } else {
+                    ctx.set_visuals(egui::Visuals::light());
+                }
+
+                // Check if close was requested via the window's X button.
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    app.show_controller_history = false;
+                }
+
+                // Draw border around the controller history window.
+                draw_viewport_border(ctx, app.dark_mode);
+
+                // Background colour comes from the current (dark or light) visuals.
+                let background_color = ctx.style().visuals.widgets.noninteractive.bg_fill;
+
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::default()
+                        .stroke(egui::Stroke::new(2.0, colours::border_colour(app.dark_mode)))
+                        .inner_margin(egui::Margin::same(8))
+                        .fill(background_color)
+                    )
+                    .show(ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            // Close controller history window.
+                            ui.separator();
+                            if ui.button("Close").clicked() {
+                                app.show_controller_history = false;
+                            }
+                        });
+                        ui.separator();
+
+                        controller_history::draw_controller_history_content(ui, app);
+                    });
+            },
+        );
+    }
+}
+
 // Helper function to draw border around viewport windows.
 fn draw_viewport_border(ctx: &egui::Context, dark_mode: bool) {
     let screen_rect = ctx.screen_rect();
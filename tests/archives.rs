@@ -0,0 +1,89 @@
+// Tests for reading gzip and zip compressed logs.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use scraper::{log_file_kind, parse_log, read_log_file, zip_members, LogFileKind};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+mod common;
+use common::test_path;
+
+const LOG: &str = "\
+15/03/2024 08:00:00 EVENT 1234 1710489600 -274700000/1530200000/0/45/32 SERVICE v:240
+15/03/2024 08:00:05 EVENT 1234 1710489605 -274700000/1530200000/0/45/32 SERVICE v:241
+";
+
+#[test]
+fn plain_logs_are_read_as_is() {
+    let path = test_path("plain.log");
+    fs::write(&path, LOG).unwrap();
+
+    assert_eq!(log_file_kind(&path).unwrap(), LogFileKind::Plain);
+    let parsed = read_log_file(&path, None, |reader, total_bytes| {
+        assert_eq!(total_bytes, LOG.len() as u64);
+        parse_log(reader)
+    }).unwrap();
+    assert_eq!(parsed.scrapings.len(), 2);
+}
+
+#[test]
+fn gzip_logs_are_decompressed() {
+    // Renamed so that only the contents say it's gzip.
+    let path = test_path("gzip.log");
+    let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+    encoder.write_all(LOG.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+
+    assert_eq!(log_file_kind(&path).unwrap(), LogFileKind::Gzip);
+    let parsed = read_log_file(&path, None, |reader, total_bytes| {
+        assert_eq!(total_bytes, LOG.len() as u64);
+        parse_log(reader)
+    }).unwrap();
+    assert_eq!(parsed.scrapings.len(), 2);
+}
+
+#[test]
+fn multi_member_gzip_logs_are_read_whole() {
+    // Two gzip members, as written by appending to a gzip log.
+    let path = test_path("multi.log.gz");
+    let mut compressed = Vec::new();
+    for _ in 0..2 {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(LOG.as_bytes()).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+    }
+    fs::write(&path, &compressed).unwrap();
+
+    let parsed = read_log_file(&path, None, |reader, total_bytes| {
+        // Only a hint, the trailer has the size of the last member.
+        assert_eq!(total_bytes, LOG.len().max(compressed.len()) as u64);
+        parse_log(reader)
+    }).unwrap();
+    assert_eq!(parsed.scrapings.len(), 4);
+}
+
+#[test]
+fn zip_members_are_listed_and_read() {
+    let path = test_path("bundle.zip");
+    let mut writer = ZipWriter::new(File::create(&path).unwrap());
+    writer.add_directory("old/", SimpleFileOptions::default()).unwrap();
+    writer.start_file("scraper.log", SimpleFileOptions::default()).unwrap();
+    writer.write_all(LOG.as_bytes()).unwrap();
+    writer.start_file("old/scraper.bak", SimpleFileOptions::default()).unwrap();
+    writer.write_all(LOG.lines().next().unwrap().as_bytes()).unwrap();
+    writer.finish().unwrap();
+
+    assert_eq!(log_file_kind(&path).unwrap(), LogFileKind::Zip);
+    let names: Vec<String> = zip_members(&path).unwrap().into_iter().map(|m| m.name).collect();
+    assert_eq!(names, ["scraper.log", "old/scraper.bak"]);
+
+    let parsed = read_log_file(&path, Some("old/scraper.bak"), |reader, _| parse_log(reader)).unwrap();
+    assert_eq!(parsed.scrapings.len(), 1);
+
+    // A member must be picked for zip archives.
+    assert!(read_log_file(&path, None, |reader, _| parse_log(reader)).is_err());
+}
@@ -0,0 +1,35 @@
+// Fixtures shared by the integration tests.
+// Each test file uses some of them, so the others are unused there.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use scraper::{parse_log, ParsedLog};
+
+// Make an EVENT line at the given seconds past 08:00:00,
+// for the given trip, event type and sub-data.
+// The GPS speed is the seconds, so events can be told apart by speed.
+pub fn event_line(seconds: u64, trip: &str, event_type: &str, sub_data: &str) -> String {
+    format!(
+        "15/03/2024 08:{:02}:{:02} EVENT {} {} -274700000/1530200000/0/45/{} {} {}",
+        seconds / 60, seconds % 60, trip, 1710489600 + seconds, seconds, event_type, sub_data
+    )
+}
+
+// Make a SERVICE line at the given seconds past 08:00:00, in trip 7.
+pub fn service_line(seconds: u64) -> String {
+    event_line(seconds, "7", "SERVICE", "v:240")
+}
+
+// Parse log lines.
+pub fn parse(lines: &[String]) -> ParsedLog {
+    parse_log(lines.join("\n").as_bytes()).unwrap()
+}
+
+// Path for a test file, unique to this test run.
+pub fn test_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("scraper_tests_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
@@ -0,0 +1,43 @@
+// Tests for the history of controller ids and firmware versions.
+
+use scraper::{parse_log, ControllerDetail, EventKind};
+
+mod common;
+use common::event_line;
+
+#[test]
+fn every_controller_and_firmware_change_is_kept() {
+    let log = [
+        "15/03/2024 08:00:00 UNIT 123456".to_string(),
+        event_line(1, "0", "SWSTART", "0 3.1.4 v:240"),
+        event_line(2, "7", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(3, "7", "TRIP", "7 1 2 3 4 5 v:240"),
+        event_line(4, "0", "SWSTART", "0 3.2.0 v:240"),
+        "15/03/2024 08:00:05 UNIT 654321".to_string(),
+        event_line(6, "8", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(7, "8", "TRIP", "8 1 2 3 4 5 v:240"),
+    ].join("\n");
+    let parsed = parse_log(log.as_bytes()).unwrap();
+
+    // First found are still reported on their own.
+    assert_eq!(parsed.controller_id.as_deref(), Some("123456"));
+    assert_eq!(parsed.controller_fw.as_deref(), Some("3.1.4"));
+
+    let history: Vec<(usize, &ControllerDetail)> = parsed.controller_history.iter()
+        .map(|change| (change.line_num, &change.detail))
+        .collect();
+    assert_eq!(history, [
+        (1, &ControllerDetail::Id("123456".to_string())),
+        (2, &ControllerDetail::Firmware("3.1.4".to_string())),
+        (5, &ControllerDetail::Firmware("3.2.0".to_string())),
+        (6, &ControllerDetail::Id("654321".to_string())),
+    ]);
+    assert!(parsed.controller_history.iter().all(|change| change.timestamp.is_some()));
+
+    // Each trip has the firmware running at the time.
+    let trips: Vec<(&str, Option<&str>)> = parsed.scrapings.iter()
+        .filter(|s| s.event.kind == EventKind::SignOn)
+        .map(|s| (s.trip_num.as_str(), s.firmware.as_deref()))
+        .collect();
+    assert_eq!(trips, [("7", Some("3.1.4")), ("8", Some("3.2.0"))]);
+}
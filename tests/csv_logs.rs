@@ -0,0 +1,84 @@
+// Tests for reading CSV exports from the telematics portal.
+
+use scraper::{CsvMapping, EventKind, LogParser, ParsedLog};
+
+fn parse_csv(mapping: CsvMapping, lines: &[&str]) -> ParsedLog {
+    let mut parser = LogParser::new().with_csv(mapping);
+    for line in lines {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+#[test]
+fn header_columns_are_mapped() {
+    let log = parse_csv(CsvMapping::default(), &[
+        "Event,Date,Time,Trip,Unix Time,Latitude,Longitude,RSSI,Speed,Detail",
+        "SIGNON,15/03/2024,08:00:00,7,1710489600,-27.47,153.02,45,0,1234 abc operator 0 0 0 v:240",
+        "\"SERVICE\",15/03/2024,08:00:10,7,1710489610,-27.4701,153.0201,45,32,\"v:240\"",
+    ]);
+
+    let kinds: Vec<&EventKind> = log.scrapings.iter().map(|s| &s.event.kind).collect();
+    assert_eq!(kinds, [&EventKind::SignOn, &EventKind::Service]);
+
+    let service = &log.scrapings[1];
+    assert_eq!(service.trip_num, "7");
+    assert_eq!(service.unix_time, 1710489610);
+    assert_eq!((service.gps_locn.lat, service.gps_locn.lon), (-27.4701, 153.0201));
+    assert_eq!((service.gps_rssi, service.gps_speed), (45, 32));
+    assert_eq!(service.line_num, 3);
+    assert!(service.event.battery().is_some());
+}
+
+#[test]
+fn files_without_a_header_use_column_numbers() {
+    // Date and time in one column, and no unix time.
+    let mapping = CsvMapping {
+        delimiter: ';',
+        date: "1".to_string(),
+        time: None,
+        trip: "2".to_string(),
+        unix_time: None,
+        latitude: "3".to_string(),
+        longitude: "4".to_string(),
+        gps_aux: None,
+        rssi: None,
+        speed: None,
+        event_type: "5".to_string(),
+        detail: Some("6".to_string()),
+    };
+    let log = parse_csv(mapping, &["15/03/2024 08:00:10;7;-27.47;153.02;SERVICE;v:240"]);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].unix_time, 1710489610);
+    assert_eq!(log.scrapings[0].gps_speed, 0);
+}
+
+#[test]
+fn missing_columns_are_reported_once() {
+    let log = parse_csv(CsvMapping::default(), &[
+        "Date,Time,Trip,Latitude,Longitude,Event",
+        "15/03/2024,08:00:10,7,-27.47,153.02,SERVICE",
+        "15/03/2024,08:00:20,7,-27.47,153.02,SERVICE",
+    ]);
+
+    assert!(log.scrapings.is_empty());
+    let reasons: Vec<&str> = log.diagnostics.iter()
+        .filter(|d| d.line_num > 0)
+        .map(|d| d.reason.as_str())
+        .collect();
+    assert_eq!(reasons, ["CSV column 'Unix Time' not found."]);
+}
+
+#[test]
+fn bad_rows_are_reported() {
+    let log = parse_csv(CsvMapping::default(), &[
+        "Date,Time,Trip,Unix Time,Latitude,Longitude,RSSI,Speed,Event,Detail",
+        "15/03/2024,08:00:10,7,1710489610,north,153.02,45,32,SERVICE,v:240",
+    ]);
+
+    assert!(log.scrapings.is_empty());
+    let diagnostic = log.diagnostics.iter().find(|d| d.line_num == 2).unwrap();
+    assert_eq!(diagnostic.reason, "Invalid latitude 'north'.");
+    assert_eq!(diagnostic.event_type.as_deref(), Some("SERVICE"));
+}
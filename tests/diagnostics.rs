@@ -0,0 +1,67 @@
+// Tests for parse issues being collected as diagnostics,
+// with loading carrying on past the bad lines.
+
+mod common;
+use common::{parse, service_line};
+
+// Make an EVENT line with the given unix time and GPS data.
+fn gps_line(unix_time: &str, gps: &str) -> String {
+    format!("15/03/2024 08:00:00 EVENT 7 {} {} SERVICE v:240", unix_time, gps)
+}
+
+// Parse a bad line between two good ones,
+// returning the reasons given for the bad line.
+fn bad_line_reasons(line: String) -> Vec<String> {
+    let log = parse(&[service_line(0), line.clone(), service_line(2)]);
+
+    // Good lines either side are still scraped.
+    let good: Vec<usize> = log.scrapings.iter().map(|s| s.line_num).filter(|&n| n != 2).collect();
+    assert_eq!(good, [1, 3]);
+
+    log.diagnostics.iter()
+        .filter(|d| d.line_num == 2)
+        .map(|d| {
+            assert_eq!(d.raw, line);
+            d.reason.clone()
+        })
+        .collect()
+}
+
+#[test]
+fn bad_unix_times_are_reported() {
+    let reasons = bad_line_reasons(gps_line("99999999999999999999999", "-274700000/1530200000/0/45/32"));
+    assert_eq!(reasons, ["Invalid unix time '99999999999999999999999'."]);
+}
+
+#[test]
+fn bad_latitudes_are_reported() {
+    let reasons = bad_line_reasons(gps_line("1710489601", "-/1530200000/0/45/32"));
+    assert_eq!(reasons, ["Invalid latitude '-'."]);
+}
+
+#[test]
+fn bad_rssi_is_reported() {
+    let reasons = bad_line_reasons(gps_line("1710489601", "-274700000/1530200000/0/99999999999/32"));
+    assert_eq!(reasons, ["Invalid gps rssi '99999999999'."]);
+}
+
+#[test]
+fn malformed_event_lines_are_reported() {
+    let reasons = bad_line_reasons("15/03/2024 08:00:01 EVENT 7 1710489601 -274700000/1530200000 SERVICE v:240".to_string());
+    assert_eq!(reasons, ["Malformed EVENT line."]);
+}
+
+#[test]
+fn diagnostics_are_tagged_with_the_event_type() {
+    let log = parse(&[
+        gps_line("1710489601", "-/1530200000/0/45/32"),
+        service_line(2),
+        "15/03/2024 08:00:03 EVENT 7 1710489603 -274700000/1530200000/0/45/32 SIGNON garbled v:240".to_string(),
+    ]);
+
+    let issues: Vec<(usize, Option<&str>)> = log.diagnostics.iter()
+        .filter(|d| d.line_num > 0)
+        .map(|d| (d.line_num, d.event_type.as_deref()))
+        .collect();
+    assert_eq!(issues, [(1, Some("SERVICE")), (3, Some("SIGNON"))]);
+}
@@ -0,0 +1,80 @@
+// Tests for JSON and NDJSON dumps of a parsed log.
+
+use std::sync::Arc;
+
+use scraper::{is_dump_file, read_dump, write_json, write_ndjson, EventKind, LogParser, ParsedLog, RuleSchema, RuleSet, Value};
+
+// A log with controller details, trips, a custom rule event and a bad line.
+fn parsed_log() -> ParsedLog {
+    let rules = RuleSchema::from_yaml("rules:\n  - { name: MODEM, pattern: 'MODEM BER (?P<ber>[.0-9]+)' }\n").unwrap();
+    let mut parser = LogParser::new()
+        .with_rules(Arc::new(RuleSet::new(&rules)))
+        .with_source("scraper.log");
+    for line in [
+        "15/03/2024 08:00:00 UNIT 123456",
+        "15/03/2024 08:00:01 EVENT 0 1710489601 0/0/0/0/0 SWSTART 0 3.1.4 v:240",
+        "15/03/2024 08:00:02 EVENT 7 1710489602 -274700000/1530200000/90/45/32 SIGNON 1234 abc operator 0 0 0 v:240",
+        "15/03/2024 08:00:03 MODEM BER 0.25",
+        "15/03/2024 08:00:04 EVENT 7 1710489604 -274700000/1530200000/90/45/32 NEWEVENT something",
+        "15/03/2024 08:00:05 EVENT 7 xx -274700000/1530200000/90/45/32 SERVICE v:240",
+        "15/03/2024 08:00:06 EVENT 7 1710489606 -274700000/1530200000/90/45/32 TRIP 7 1 2 3 4 5 v:240",
+    ] {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+fn to_json(log: &ParsedLog) -> String {
+    let mut output = Vec::new();
+    write_json(&mut output, log).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn json_dumps_read_back_the_same() {
+    let log = parsed_log();
+    let json = to_json(&log);
+
+    let read = read_dump(json.as_bytes()).unwrap();
+    assert_eq!(to_json(&read), json);
+    assert_eq!(read.controller_id.as_deref(), Some("123456"));
+    assert_eq!(read.scrapings.len(), log.scrapings.len());
+    assert_eq!(read.diagnostics.len(), log.diagnostics.len());
+}
+
+#[test]
+fn ndjson_dumps_have_a_record_per_line() {
+    let log = parsed_log();
+    let mut output = Vec::new();
+    write_ndjson(&mut output, &log).unwrap();
+    let ndjson = String::from_utf8(output).unwrap();
+
+    let lines: Vec<&str> = ndjson.lines().collect();
+    let records = 1 + log.controller_history.len() + log.scrapings.len() + log.diagnostics.len();
+    assert_eq!(lines.len(), records);
+    assert!(lines[0].starts_with(r#"{"record":"controller","#));
+
+    // Nothing is lost.
+    let read = read_dump(ndjson.as_bytes()).unwrap();
+    assert_eq!(to_json(&read), to_json(&log));
+
+    let modem = read.scrapings.iter().find(|s| s.event.kind == EventKind::Custom("MODEM".to_string())).unwrap();
+    assert_eq!(modem.event.fields[0].value, Value::Number(0.25));
+    let unsupported = read.scrapings.iter().find(|s| s.event_type() == "NEWEVENT").unwrap();
+    assert!(!unsupported.ev_supported);
+    assert_eq!(unsupported.gps_aux, 90);
+}
+
+#[test]
+fn bad_dumps_are_errors() {
+    let error = read_dump("{\"record\":\"controller\"}\nnot json\n".as_bytes()).unwrap_err();
+    assert!(error.to_string().contains("line 2"));
+    assert!(read_dump("[1, 2]".as_bytes()).is_err());
+}
+
+#[test]
+fn dump_files_are_recognised_by_name() {
+    assert!(is_dump_file("scraper.json"));
+    assert!(is_dump_file("Scraper.NDJSON.gz"));
+    assert!(!is_dump_file("scraper.log"));
+}
@@ -0,0 +1,121 @@
+// Tests for event definitions and the decoder registry built from them.
+
+use std::sync::Arc;
+
+use scraper::{EventKind, EventRegistry, EventSchema, FieldKey, LogParser, ParsedLog, Volts};
+
+// Make an EVENT line with the given event type and sub-data.
+fn event_line(event_type: &str, sub_data: &str) -> String {
+    format!("15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/32 {} {}", event_type, sub_data)
+}
+
+fn parse_with_schema(yaml: &str, lines: &[String]) -> ParsedLog {
+    let schema = EventSchema::from_yaml(yaml).unwrap();
+    let mut parser = LogParser::with_registry(Arc::new(EventRegistry::new(&schema)));
+    for line in lines {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+#[test]
+fn registry_has_every_default_event() {
+    let registry = EventRegistry::default_registry();
+    let mut names: Vec<&str> = registry.event_names().collect();
+    names.sort();
+
+    let mut expected: Vec<String> = EventSchema::default().events.into_iter().map(|def| def.name).collect();
+    expected.sort();
+    assert_eq!(names, expected);
+    assert!(names.contains(&"OOS PM"));
+}
+
+#[test]
+fn unknown_events_are_unsupported() {
+    let log = parse_with_schema(
+        "events:\n  - { name: SERVICE, pattern: 'v:(.+?)$', fields: [{ key: battery, label: Battery voltage, group: 1, unit: volts, scale: 0.1 }] }\n",
+        &[event_line("SERVICE", "v:240"), event_line("SIGNON", "1234 abc operator 0 0 0 v:240")],
+    );
+
+    assert_eq!(log.scrapings.len(), 2);
+    assert!(log.scrapings[0].ev_supported);
+    assert_eq!(log.scrapings[0].event.fields.len(), 1);
+
+    // Still scraped, but not decoded.
+    let signon = &log.scrapings[1];
+    assert_eq!(signon.event.kind, EventKind::SignOn);
+    assert!(!signon.ev_supported);
+    assert!(signon.event.fields.is_empty());
+    assert!(log.diagnostics.iter().all(|d| d.line_num == 0));
+}
+
+#[test]
+fn invalid_patterns_are_reported_per_event() {
+    let log = parse_with_schema(
+        "events:\n  - { name: SERVICE, pattern: 'v:(.+?$' }\n",
+        &[event_line("SERVICE", "v:240"), event_line("SERVICE", "v:241")],
+    );
+
+    assert_eq!(log.scrapings.len(), 2);
+    let issues: Vec<(usize, &str)> = log.diagnostics.iter()
+        .filter(|d| d.line_num > 0)
+        .map(|d| (d.line_num, d.reason.as_str()))
+        .collect();
+    assert_eq!(issues, [(1, "Invalid SERVICE sub-data pattern."), (2, "Invalid SERVICE sub-data pattern.")]);
+}
+
+const NEW_EVENTS: &str = r#"
+events:
+  - name: PUMP
+    pattern: '([0-9]+) ([0-9]+) v:(.+?)$'
+    on_trip: false
+    fields:
+      - { label: Pump id, group: 1 }
+      - { key: duration, label: Run time, group: 2, unit: seconds }
+      - { key: battery, label: Battery voltage, group: 3, unit: volts, scale: 0.1, decimals: 1 }
+  - name: HEARTBEAT
+"#;
+
+#[test]
+fn new_events_are_loaded_from_yaml() {
+    let schema = EventSchema::from_yaml(NEW_EVENTS).unwrap();
+    assert_eq!(schema.events.len(), 2);
+    let heartbeat = &schema.events[1];
+    assert_eq!(heartbeat.pattern, None);
+    assert!(heartbeat.on_trip);
+    assert!(heartbeat.fields.is_empty());
+
+    let log = parse_with_schema(NEW_EVENTS, &[event_line("PUMP", "3 125 v:243"), event_line("HEARTBEAT", "ok")]);
+    assert_eq!(log.scrapings.len(), 2);
+
+    let pump = &log.scrapings[0];
+    assert_eq!(pump.event.kind, EventKind::Other("PUMP".to_string()));
+    assert!(pump.ev_supported);
+    assert!(!pump.on_trip);
+    assert_eq!(pump.event.detail(), [
+        ("Pump id".to_string(), "3".to_string()),
+        ("Run time".to_string(), "125".to_string()),
+        ("Battery voltage".to_string(), "24.3".to_string()),
+    ]);
+    assert!(log.scrapings[1].ev_supported);
+}
+
+#[test]
+fn bad_schemas_are_errors() {
+    assert!(EventSchema::from_yaml("events:\n  - { pattern: 'v:(.+?)$' }\n").is_err());
+    assert!(EventSchema::from_yaml("events:\n  - { name: PUMP, fields: [{ label: Pump id, group: 1, unit: litres }] }\n").is_err());
+}
+
+#[test]
+fn unknown_field_keys_are_ignored() {
+    let yaml = "events:\n  - { name: PUMP, pattern: '([0-9]+) v:(.+?)$', fields: [{ key: pump_id, label: Pump id, group: 1 }, { key: battery, label: Battery voltage, group: 2, unit: volts, scale: 0.1 }] }\n";
+    let schema = EventSchema::from_yaml(yaml).unwrap();
+    let fields = &schema.events[0].fields;
+    assert_eq!(fields[0].key, None);
+    assert_eq!(fields[1].key, Some(FieldKey::Battery));
+
+    // The field is still decoded, just can't be looked up by key.
+    let log = parse_with_schema(yaml, &[event_line("PUMP", "3 v:243")]);
+    assert_eq!(log.scrapings[0].event.detail()[0], ("Pump id".to_string(), "3".to_string()));
+    assert_eq!(log.scrapings[0].event.battery(), Some(Volts(24.3)));
+}
@@ -0,0 +1,118 @@
+// Regression tests for event type recognition and typed event fields,
+// including multi-word event types like OOS PM and OOS UPM.
+
+use regex::Regex;
+use scraper::{parse_log, EventKind, EventSchema, FieldKey, Kph, Seconds, Value, Volts};
+
+// Make an EVENT line with the given event type and sub-data.
+fn event_line(event_type: &str, sub_data: &str) -> String {
+    format!("15/03/2024 08:00:00 EVENT 1234 1710489600 -274700000/1530200000/0/45/32 {} {}", event_type, sub_data)
+}
+
+#[test]
+fn oos_pm_event_is_recognised() {
+    let log = event_line("OOS PM", "v:245");
+    let parsed = parse_log(log.as_bytes()).unwrap();
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    let event = &parsed.scrapings[0];
+    assert_eq!(event.event.kind, EventKind::OosPm);
+    assert_eq!(event.event_type(), "OOS PM");
+    assert!(event.ev_supported);
+    assert_eq!(event.event.battery(), Some(Volts(24.5)));
+    assert!(parsed.diagnostics.iter().all(|d| d.line_num == 0));
+}
+
+#[test]
+fn oos_upm_event_is_recognised() {
+    let log = event_line("OOS UPM", "1234 3 v:241");
+    let parsed = parse_log(log.as_bytes()).unwrap();
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    let event = &parsed.scrapings[0];
+    assert_eq!(event.event.kind, EventKind::OosUpm);
+    assert_eq!(event.event_type(), "OOS UPM");
+    assert!(event.ev_supported);
+    assert_eq!(event.event.text(FieldKey::TripId), Some("1234"));
+    assert_eq!(event.event.value(FieldKey::Reason), Some(&Value::Count(3)));
+    assert_eq!(event.event.battery(), Some(Volts(24.1)));
+    assert!(parsed.diagnostics.iter().all(|d| d.line_num == 0));
+}
+
+#[test]
+fn single_word_events_still_recognised() {
+    let log = [
+        event_line("OOS UPM", "1234 3 v:241"),
+        event_line("SERVICE", "v:240"),
+        event_line("NEWEVENT", "1 2 3"),
+    ].join("\n");
+    let parsed = parse_log(log.as_bytes()).unwrap();
+
+    let kinds: Vec<&EventKind> = parsed.scrapings.iter().map(|s| &s.event.kind).collect();
+    assert_eq!(kinds, [&EventKind::OosUpm, &EventKind::Service, &EventKind::Other("NEWEVENT".to_string())]);
+    assert_eq!(parsed.scrapings[1].event.battery(), Some(Volts(24.0)));
+    assert!(!parsed.scrapings[2].ev_supported);
+}
+
+#[test]
+fn default_event_patterns_compile() {
+    for def in EventSchema::default().events {
+        if let Some(pattern) = &def.pattern {
+            assert!(Regex::new(pattern).is_ok(), "Invalid {} pattern: {}", def.name, pattern);
+        }
+    }
+}
+
+#[test]
+fn event_fields_carry_their_units() {
+    let log = [
+        event_line("ZONEOVERSPEED", "1234 42 27 1 v:239"),
+        event_line("IMPACT", "1234 1 2 3 4 5 6 high v:238"),
+        event_line("XSIDLE", "1234 300 2 v:bad"),
+    ].join("\n");
+    let parsed = parse_log(log.as_bytes()).unwrap();
+    let [overspeed, impact, idle] = &parsed.scrapings[..] else {
+        panic!("Expected 3 events, got {}", parsed.scrapings.len());
+    };
+
+    assert_eq!(overspeed.event.duration(), Some(Seconds(42)));
+    assert_eq!(overspeed.event.value(FieldKey::MaxSpeed), Some(&Value::Kph(Kph(27))));
+    assert_eq!(overspeed.event.count(FieldKey::ZoneOutput), Some(1));
+    assert_eq!(overspeed.event.value(FieldKey::MaxSpeed).and_then(Value::as_f64), Some(27.0));
+
+    assert_eq!(impact.event.count(FieldKey::MaxG2), Some(6));
+    assert_eq!(impact.event.text(FieldKey::Severity), Some("high"));
+    // Looked up by key, so the wrong type of value isn't found.
+    assert_eq!(impact.event.text(FieldKey::MaxG2), None);
+    assert_eq!(impact.event.duration(), None);
+
+    // Fallbacks for values that don't parse.
+    assert_eq!(idle.event.seconds(FieldKey::MaxIdle), Some(Seconds(300)));
+    assert_eq!(idle.event.battery(), None);
+    assert_eq!(idle.event.text(FieldKey::Battery), Some("?"));
+}
+
+#[test]
+fn display_strings_come_from_the_values() {
+    let parsed = parse_log(event_line("ZONEOVERSPEED", "1234 42 27 1 v:240").as_bytes()).unwrap();
+
+    assert_eq!(parsed.scrapings[0].event.detail(), [
+        ("Trip id".to_string(), "1234".to_string()),
+        ("Duration".to_string(), "42".to_string()),
+        ("Maximum speed".to_string(), "27".to_string()),
+        ("Zone output".to_string(), "1".to_string()),
+        ("Battery voltage".to_string(), "24.0".to_string()),
+    ]);
+}
+
+#[test]
+fn duration_events_have_a_battery_voltage() {
+    for event_type in ["LOWCOOLANT", "OFFSEAT", "OILPRESSURE", "OVERLOAD"] {
+        let parsed = parse_log(event_line(event_type, "1234 15 v:243").as_bytes()).unwrap();
+
+        let event = &parsed.scrapings[0].event;
+        assert_eq!(event.duration(), Some(Seconds(15)), "{}", event_type);
+        assert_eq!(event.battery(), Some(Volts(24.3)), "{}", event_type);
+        assert!(parsed.diagnostics.iter().all(|d| d.line_num == 0), "{}", event_type);
+    }
+}
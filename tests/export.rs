@@ -0,0 +1,144 @@
+// Tests for exporting events and trips.
+
+use scraper::{write_events_csv, write_geojson, write_gpx, write_kml, write_trips_csv, xml_escape, ParsedLog, ScrapedData};
+
+mod common;
+use common::{event_line, parse};
+
+fn export_lines(write: impl Fn(&mut Vec<u8>, &[&ScrapedData]) -> std::io::Result<()>, log: &ParsedLog) -> Vec<String> {
+    let events: Vec<&ScrapedData> = log.scrapings.iter().collect();
+    let mut output = Vec::new();
+    write(&mut output, &events).unwrap();
+    String::from_utf8(output).unwrap().lines().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn events_have_a_column_per_detail() {
+    let log = parse(&[
+        event_line(0, "7", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(10, "7", "SERVICE", "v:240"),
+    ]);
+    let lines = export_lines(write_events_csv, &log);
+
+    assert_eq!(lines.len(), 3);
+    let header: Vec<&str> = lines[0].split(',').collect();
+    assert_eq!(&header[..6], ["Date", "Time", "Unix Time", "Trip", "Event", "Operator id"]);
+    assert!(header.contains(&"Battery voltage"));
+    assert!(lines[1].starts_with("15/03/2024,08:00:00,1710489600,7,SIGNON,1234,"));
+
+    // Columns line up, with details the event doesn't have left empty.
+    let service: Vec<&str> = lines[2].split(',').collect();
+    assert_eq!(service.len(), header.len());
+    assert_eq!(service[header.iter().position(|h| *h == "Operator id").unwrap()], "");
+    assert_eq!(service[header.iter().position(|h| *h == "Speed").unwrap()], "10");
+}
+
+#[test]
+fn cells_with_commas_are_quoted() {
+    let log = parse(&[event_line(0, "7", "SIGNON", "1234 abc \"card\", ok 0 0 0 v:240")]);
+    let lines = export_lines(write_events_csv, &log);
+
+    assert!(lines[1].contains(",1234,abc,\"\"\"card\"\", ok\",0,"));
+}
+
+#[test]
+fn trips_have_one_row_per_signon() {
+    // Both trips are numbered 1, after a power cycle.
+    let log = parse(&[
+        event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(10, "1", "SERVICE", "v:240"),
+        event_line(20, "1", "TRIP", "1 1 2 3 4 5 v:240"),
+        event_line(25, "1", "SERVICE", "v:240"),
+        event_line(30, "1", "SIGNON", "5678 abc operator 0 0 0 v:240"),
+        event_line(45, "1", "SERVICE", "v:240"),
+    ]);
+    let lines = export_lines(write_trips_csv, &log);
+
+    assert_eq!(lines, [
+        "Trip,Start,End,Start Unix Time,End Unix Time,Duration (s),Events,Max Speed,Operator,Firmware,Source",
+        "1,15/03/2024 08:00:00,15/03/2024 08:00:20,1710489600,1710489620,20,3,20,1234,,",
+        "1,15/03/2024 08:00:30,15/03/2024 08:00:45,1710489630,1710489645,15,2,45,5678,,",
+    ]);
+}
+
+#[test]
+fn gpx_has_a_track_per_trip_and_events_as_waypoints() {
+    let log = parse(&[
+        event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(36, "1", "SERVICE", "v:240"),
+        event_line(40, "1", "TRIP", "1 1 2 3 4 5 v:240"),
+        event_line(50, "1", "SIGNON", "5678 abc operator 0 0 0 v:240"),
+    ]);
+    let gpx = export_lines(|w, e| write_gpx(w, e, false), &log).join("\n");
+
+    assert!(gpx.starts_with("<?xml"));
+    assert!(gpx.contains(r#"<gpx version="1.1""#));
+    assert_eq!(gpx.matches("<trk>").count(), 2);
+    assert_eq!(gpx.matches("<trkpt ").count(), 4);
+    assert_eq!(gpx.matches("<wpt ").count(), 4);
+    assert!(gpx.contains(r#"<wpt lat="-27.47" lon="153.02">"#));
+    assert!(gpx.contains("<name>SERVICE</name>"));
+    assert!(gpx.contains("<time>2024-03-15T08:00:36Z</time>"));
+    // 36 kph is 10 m/s.
+    assert!(gpx.contains("<gpxtpx:speed>10.00</gpxtpx:speed>"));
+    assert!(gpx.find("<wpt ").unwrap() < gpx.find("<trk>").unwrap());
+}
+
+#[test]
+fn gpx_leaves_out_events_without_a_fix() {
+    let mut lines = vec![event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240")];
+    lines.push(event_line(10, "1", "SERVICE", "v:240").replace("-274700000/1530200000", "0/0"));
+    let log = parse(&lines);
+    let gpx = export_lines(|w, e| write_gpx(w, e, false), &log).join("\n");
+
+    assert_eq!(gpx.matches("<trkpt ").count(), 1);
+    assert!(!gpx.contains("<name>SERVICE</name>"));
+}
+
+#[test]
+fn geojson_has_a_line_per_trip_and_a_point_per_event() {
+    let log = parse(&[
+        event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(90, "1", "SERVICE", "v:240"),
+        event_line(100, "1", "TRIP", "1 1 2 3 4 5 v:240"),
+    ]);
+    let geojson = export_lines(|w, e| write_geojson(w, e, false), &log).join("\n");
+    let collection: serde_json::Value = serde_json::from_str(&geojson).unwrap();
+
+    let features = collection["features"].as_array().unwrap();
+    assert_eq!(collection["type"], "FeatureCollection");
+    assert_eq!(features.len(), 4);
+    assert_eq!(features[0]["geometry"]["type"], "LineString");
+    assert_eq!(features[0]["geometry"]["coordinates"][0], serde_json::json!([153.02, -27.47]));
+
+    // Event details are properties, and points are coloured by speed.
+    let signon = &features[1]["properties"];
+    assert_eq!(signon["event_type"], "SIGNON");
+    assert_eq!(signon["Operator id"], "1234");
+    assert_eq!(signon["marker-color"], "#00ff00");
+    assert_eq!(features[2]["properties"]["marker-color"], "#ffa500");
+}
+
+#[test]
+fn kml_has_a_placemark_per_event() {
+    let log = parse(&[
+        event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(70, "1", "SERVICE", "v:240"),
+    ]);
+    let kml = export_lines(|w, e| write_kml(w, e, false), &log).join("\n");
+
+    assert!(kml.contains(r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#));
+    assert_eq!(kml.matches("<Folder>").count(), 1);
+    assert_eq!(kml.matches("<Placemark>").count(), 3);
+    assert!(kml.contains("<LineString><coordinates>153.02,-27.47 153.02,-27.47</coordinates></LineString>"));
+    assert!(kml.contains(r#"<Data name="Operator id"><value>1234</value></Data>"#));
+    assert!(kml.contains("<TimeStamp><when>2024-03-15T08:01:10Z</when></TimeStamp>"));
+    // 70 kph is blue, in KML's blue, green, red order.
+    assert!(kml.contains(r#"<Style id="point-0000ff"><IconStyle><color>ffff0000</color></IconStyle></Style>"#));
+    assert!(kml.contains("<styleUrl>#point-0000ff</styleUrl>"));
+}
+
+#[test]
+fn xml_text_is_escaped() {
+    assert_eq!(xml_escape(r#"<Tom & Jerry's "cart">"#), "&lt;Tom &amp; Jerry&apos;s &quot;cart&quot;&gt;");
+}
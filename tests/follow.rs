@@ -0,0 +1,132 @@
+// Tests for following a log that is still being written.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use scraper::{
+    ControllerFormat, CsvFormat, CsvMapping, EventRegistry, LogFollower, LogFormat, LogParser, RuleSchema, RuleSet,
+};
+
+mod common;
+use common::{service_line, test_path};
+
+// A line of the log, as written.
+fn log_line(seconds: u64) -> String {
+    format!("{}\n", service_line(seconds))
+}
+
+fn append(path: &PathBuf, text: &str) {
+    let mut file = OpenOptions::new().append(true).open(path).unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+#[test]
+fn appended_lines_are_parsed() {
+    let path = test_path("appended.log");
+    fs::write(&path, log_line(0)).unwrap();
+    let mut follower = LogFollower::new(&path, LogParser::new());
+
+    let first = follower.poll().unwrap().unwrap();
+    assert!(first.restarted);
+    assert_eq!(first.log.scrapings.len(), 1);
+
+    // Nothing new.
+    assert!(follower.poll().unwrap().is_none());
+
+    // Half written lines wait until they are finished.
+    let line = log_line(1);
+    let (start, end) = line.split_at(30);
+    append(&path, start);
+    assert!(follower.poll().unwrap().is_none());
+    append(&path, end);
+
+    let update = follower.poll().unwrap().unwrap();
+    assert!(!update.restarted);
+    assert_eq!(update.log.scrapings.len(), 1);
+    assert_eq!(update.log.scrapings[0].unix_time, 1710489601);
+}
+
+#[test]
+fn truncated_logs_are_read_again() {
+    let path = test_path("truncated.log");
+    fs::write(&path, [log_line(0), log_line(1)].concat()).unwrap();
+    let mut follower = LogFollower::new(&path, LogParser::new());
+    assert_eq!(follower.poll().unwrap().unwrap().log.scrapings.len(), 2);
+
+    fs::write(&path, log_line(5)).unwrap();
+    let update = follower.poll().unwrap().unwrap();
+    assert!(update.restarted);
+    assert_eq!(update.log.scrapings.len(), 1);
+    assert_eq!(update.log.scrapings[0].unix_time, 1710489605);
+}
+
+#[test]
+fn restarted_logs_drop_the_old_controller() {
+    let path = test_path("restarted_controller.log");
+    fs::write(&path, ["15/03/2024 08:00:00 UNIT 123456\n".to_string(), log_line(1)].concat()).unwrap();
+    let mut follower = LogFollower::new(&path, LogParser::new());
+    let first = follower.poll().unwrap().unwrap();
+    assert_eq!(first.log.controller_id.as_deref(), Some("123456"));
+
+    fs::write(&path, log_line(5)).unwrap();
+    let update = follower.poll().unwrap().unwrap();
+    assert!(update.restarted);
+    assert_eq!(update.log.controller_id, None);
+    assert_eq!(update.log.scrapings.len(), 1);
+}
+
+#[test]
+fn invalid_utf8_lines_are_still_parsed() {
+    let path = test_path("invalid_utf8.log");
+    let mut text = log_line(0).into_bytes();
+    text.extend_from_slice(b"Garbled \xff\xfe line\n");
+    text.extend_from_slice(log_line(1).as_bytes());
+    fs::write(&path, text).unwrap();
+    let mut follower = LogFollower::new(&path, LogParser::new());
+
+    let update = follower.poll().unwrap().unwrap();
+    assert_eq!(update.log.scrapings.len(), 2);
+}
+
+#[test]
+fn short_logs_are_shown_while_detecting_the_format() {
+    const RULES: &str = r#"
+rules:
+  - name: MODEM
+    pattern: '(?P<date>\S+) (?P<time>\S+) MODEM RSSI (?P<rssi>[-0-9]+)'
+"#;
+    let rules = RuleSet::new(&RuleSchema::from_yaml(RULES).unwrap());
+    let formats: Vec<Box<dyn LogFormat>> = vec![
+        Box::new(ControllerFormat::new(&EventRegistry::default_registry())),
+        Box::new(CsvFormat::new(CsvMapping::default())),
+    ];
+    let parser = LogParser::new().with_formats(formats).with_rules(Arc::new(rules));
+
+    // Too few lines to detect the format from.
+    let path = test_path("detecting.log");
+    fs::write(&path, "15/03/2024 08:00:00 MODEM RSSI -71\n").unwrap();
+    let mut follower = LogFollower::new(&path, parser);
+    assert_eq!(follower.poll().unwrap().unwrap().log.scrapings.len(), 1);
+
+    append(&path, "15/03/2024 08:00:05 MODEM RSSI -70\n");
+    assert_eq!(follower.poll().unwrap().unwrap().log.scrapings.len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn rolled_over_logs_are_read_again() {
+    let path = test_path("rolled.log");
+    fs::write(&path, log_line(0)).unwrap();
+    let mut follower = LogFollower::new(&path, LogParser::new());
+    assert_eq!(follower.poll().unwrap().unwrap().log.scrapings.len(), 1);
+
+    // New file, at least as long as the old one.
+    fs::rename(&path, test_path("rolled.bak")).unwrap();
+    fs::write(&path, [log_line(10), log_line(11)].concat()).unwrap();
+
+    let update = follower.poll().unwrap().unwrap();
+    assert!(update.restarted);
+    assert_eq!(update.log.scrapings.len(), 2);
+}
@@ -0,0 +1,69 @@
+// Tests for the GPS aux field and GPS fix quality.
+
+use scraper::{parse_log, GpsFix, ParsedLog};
+
+// Make a SERVICE line at the given seconds past 08:00:00 with the given GPS data.
+fn gps_line(seconds: u64, gps: &str) -> String {
+    format!(
+        "15/03/2024 08:{:02}:{:02} EVENT 7 {} {} SERVICE v:240",
+        seconds / 60, seconds % 60, 1710489600 + seconds, gps
+    )
+}
+
+fn parse(lines: &[String]) -> ParsedLog {
+    parse_log(lines.join("\n").as_bytes()).unwrap()
+}
+
+fn fixes(log: &ParsedLog) -> Vec<GpsFix> {
+    log.scrapings.iter().map(|s| s.gps_fix).collect()
+}
+
+#[test]
+fn gps_aux_field_is_decoded() {
+    let log = parse(&[gps_line(0, "-274700000/1530200000/9/45/32")]);
+
+    let event = &log.scrapings[0];
+    assert_eq!(event.gps_aux, 9);
+    assert_eq!(event.gps_rssi, 45);
+    assert_eq!(event.gps_speed, 32);
+}
+
+#[test]
+fn missing_and_weak_fixes_are_flagged() {
+    let log = parse(&[
+        gps_line(0, "-274700000/1530200000/9/45/32"),
+        gps_line(10, "0/0/0/0/0"),
+        gps_line(20, "-274701000/1530201000/9/5/32"),
+    ]);
+
+    assert_eq!(fixes(&log), [GpsFix::Good, GpsFix::NoFix, GpsFix::WeakSignal]);
+}
+
+#[test]
+fn implausible_jumps_are_flagged() {
+    // About 100 km north in 10 seconds, then back again.
+    let log = parse(&[
+        gps_line(0, "-274700000/1530200000/9/45/32"),
+        gps_line(10, "-265700000/1530200000/9/45/32"),
+        gps_line(20, "-274701000/1530201000/9/45/32"),
+    ]);
+
+    assert_eq!(fixes(&log), [GpsFix::Good, GpsFix::Jump, GpsFix::Good]);
+}
+
+#[test]
+fn repeated_jumps_are_accepted_as_the_new_location() {
+    // The first fix was the wrong one, so the later fixes keep jumping from it.
+    let log = parse(&[
+        gps_line(0, "-265700000/1530200000/9/45/32"),
+        gps_line(10, "-274700000/1530200000/9/45/32"),
+        gps_line(20, "-274701000/1530200000/9/45/32"),
+        gps_line(30, "-274702000/1530200000/9/45/32"),
+        gps_line(40, "-274703000/1530200000/9/45/32"),
+    ]);
+
+    assert_eq!(
+        fixes(&log),
+        [GpsFix::Good, GpsFix::Jump, GpsFix::Jump, GpsFix::Jump, GpsFix::Good]
+    );
+}
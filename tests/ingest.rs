@@ -0,0 +1,137 @@
+// Tests for live ingest, using a local TCP socket as a stand-in controller.
+
+use std::fs;
+use std::io::Write;
+use std::net::TcpListener;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+use std::time::Duration;
+
+use scraper::{run_ingest, IngestEvent, IngestOptions, IngestSource, LogParser, ParsedLog};
+
+mod common;
+use common::service_line;
+
+// A line as sent by the controller.
+fn sent_line(seconds: u64) -> String {
+    format!("{}\r\n", service_line(seconds))
+}
+
+#[test]
+fn source_is_tcp_or_serial() {
+    assert_eq!(IngestSource::parse("192.168.1.10:5000", 115200), IngestSource::Tcp { address: "192.168.1.10:5000".to_string() });
+    assert_eq!(IngestSource::parse("/dev/ttyUSB0", 115200), IngestSource::Serial { path: "/dev/ttyUSB0".to_string(), baud_rate: 115200 });
+    assert_eq!(IngestSource::parse("COM3", 9600), IngestSource::Serial { path: "COM3".to_string(), baud_rate: 9600 });
+}
+
+#[test]
+fn serial_devices_are_not_mistaken_for_sockets() {
+    // Needs a host and a valid port to be TCP.
+    let serial = |spec: &str| IngestSource::Serial { path: spec.to_string(), baud_rate: 115200 };
+    assert_eq!(IngestSource::parse(" /dev/ttyUSB0\n", 115200), serial("/dev/ttyUSB0"));
+    assert_eq!(IngestSource::parse(":5000", 115200), serial(":5000"));
+    assert_eq!(IngestSource::parse("COM3:", 115200), serial("COM3:"));
+    assert_eq!(IngestSource::parse("bench:99999", 115200), serial("bench:99999"));
+    assert_eq!(IngestSource::parse("/dev/ttyUSB0", 115200).to_string(), "/dev/ttyUSB0 at 115200 baud");
+}
+
+#[test]
+fn lines_are_parsed_across_reconnects() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+
+    // Controller that drops the connection after the first line,
+    // and sends a line in two parts after reconnecting.
+    let controller = thread::spawn(move || {
+        let (mut first, _) = listener.accept().unwrap();
+        first.write_all(sent_line(0).as_bytes()).unwrap();
+        drop(first);
+
+        let (mut second, _) = listener.accept().unwrap();
+        let line = sent_line(1);
+        let (start, end) = line.split_at(20);
+        second.write_all(start.as_bytes()).unwrap();
+        second.flush().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        second.write_all(end.as_bytes()).unwrap();
+    });
+
+    let capture = std::env::temp_dir().join(format!("scraper_ingest_{}.log", std::process::id()));
+    let _ = fs::remove_file(&capture);
+    let mut options = IngestOptions::new(IngestSource::parse(&address, 0));
+    options.capture = Some(capture.clone());
+    options.reconnect_delay = Duration::from_millis(50);
+
+    let cancel = AtomicBool::new(false);
+    let mut connects = 0;
+    let mut unix_times = Vec::new();
+    run_ingest(&options, LogParser::new(), &cancel, |event| {
+        match event {
+            IngestEvent::Connected => connects += 1,
+            IngestEvent::Parsed(log) => unix_times.extend(log.scrapings.iter().map(|s| s.unix_time)),
+            IngestEvent::Disconnected(_) => {}
+        }
+        unix_times.len() < 2
+    }).unwrap();
+    controller.join().unwrap();
+
+    assert_eq!(connects, 2);
+    assert_eq!(unix_times, [1710489600, 1710489601]);
+    assert_eq!(fs::read_to_string(&capture).unwrap(), [sent_line(0), sent_line(1)].concat());
+}
+
+// Ingest from a stand-in controller, run by the given function,
+// until the given number of events have been parsed.
+fn ingest_events(events: usize, controller: impl FnOnce(TcpListener) + Send + 'static) -> ParsedLog {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let controller = thread::spawn(move || controller(listener));
+
+    let mut options = IngestOptions::new(IngestSource::parse(&address, 0));
+    options.reconnect_delay = Duration::from_millis(50);
+    let cancel = AtomicBool::new(false);
+    let mut ingested = ParsedLog::default();
+    run_ingest(&options, LogParser::new(), &cancel, |event| {
+        if let IngestEvent::Parsed(log) = event {
+            ingested.scrapings.extend(log.scrapings);
+            ingested.diagnostics.extend(log.diagnostics);
+        }
+        ingested.scrapings.len() < events
+    }).unwrap();
+    controller.join().unwrap();
+    ingested
+}
+
+#[test]
+fn lines_cut_off_by_a_reconnect_are_dropped() {
+    let log = ingest_events(2, |listener| {
+        let (mut first, _) = listener.accept().unwrap();
+        first.write_all(sent_line(0).as_bytes()).unwrap();
+        first.write_all(&sent_line(1).as_bytes()[..30]).unwrap();
+        drop(first);
+
+        let (mut second, _) = listener.accept().unwrap();
+        second.write_all(sent_line(2).as_bytes()).unwrap();
+    });
+
+    let unix_times: Vec<u64> = log.scrapings.iter().map(|s| s.unix_time).collect();
+    assert_eq!(unix_times, [1710489600, 1710489602]);
+    assert!(log.diagnostics.is_empty());
+}
+
+#[test]
+fn over_long_lines_are_dropped() {
+    let log = ingest_events(1, |listener| {
+        let (mut stream, _) = listener.accept().unwrap();
+        // The end of the long line looks like an event, but isn't one.
+        stream.write_all(&[b'x'; 100_000]).unwrap();
+        stream.write_all(sent_line(0).as_bytes()).unwrap();
+        stream.write_all(sent_line(1).as_bytes()).unwrap();
+    });
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].unix_time, 1710489601);
+    let reasons: Vec<&str> = log.diagnostics.iter().map(|d| d.reason.as_str()).collect();
+    assert_eq!(reasons, ["Line longer than 65536 bytes dropped."]);
+    assert!(log.diagnostics[0].raw.len() > 65536);
+}
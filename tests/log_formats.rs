@@ -0,0 +1,107 @@
+// Tests for log formats and detecting them.
+
+use scraper::{
+    ControllerFormat, CsvFormat, CsvMapping, EventKind, EventRecord, EventRegistry, GpsLocation,
+    LineEvent, LineMetadata, LogFormat, LogParser, ParsedLog, TimestampFormat,
+};
+
+// Format with events as 'GEN2|date time|trip|unix|lat,lon|type|detail'.
+#[derive(Debug, Clone)]
+struct Gen2Format;
+
+impl LogFormat for Gen2Format {
+    fn name(&self) -> &'static str {
+        "Gen 2"
+    }
+
+    fn detect(&self, lines: &[&str]) -> bool {
+        lines.iter().any(|line| line.starts_with("GEN2|"))
+    }
+
+    fn parse_line(&mut self, line: &str, _timestamp_format: &TimestampFormat) -> LineEvent {
+        let fields: Vec<&str> = line.split('|').collect();
+        let [_, date_time, trip, unix, locn, event_type, detail] = fields[..] else {
+            return LineEvent::None;
+        };
+        let (date, time) = date_time.split_once(' ').unwrap();
+        let (lat, lon) = locn.split_once(',').unwrap();
+        LineEvent::Event(EventRecord {
+            date: date.to_string(),
+            time: time.to_string(),
+            trip_id: trip.to_string(),
+            unix_time: unix.parse().unwrap(),
+            gps_locn: GpsLocation { lat: lat.parse().unwrap(), lon: lon.parse().unwrap() },
+            gps_aux: 0,
+            gps_rssi: 0,
+            gps_speed: 0,
+            event_type: event_type.to_string(),
+            detail: detail.to_string(),
+        })
+    }
+
+    fn metadata(&self, _line: &str) -> Option<LineMetadata> {
+        None
+    }
+
+    fn boxed_clone(&self) -> Box<dyn LogFormat> {
+        Box::new(self.clone())
+    }
+}
+
+fn formats() -> Vec<Box<dyn LogFormat>> {
+    vec![
+        Box::new(ControllerFormat::new(&EventRegistry::default_registry())),
+        Box::new(CsvFormat::new(CsvMapping::default())),
+        Box::new(Gen2Format),
+    ]
+}
+
+fn parse(lines: &[&str]) -> ParsedLog {
+    let mut parser = LogParser::new().with_formats(formats());
+    for line in lines {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+#[test]
+fn controller_logs_are_detected() {
+    let log = parse(&[
+        "Controller log started",
+        "15/03/2024 08:00:00 UNIT 123456",
+        "15/03/2024 08:00:10 EVENT 7 1710489610 -274700000/1530200000/0/45/32 SERVICE v:240",
+    ]);
+
+    assert_eq!(log.controller_id.as_deref(), Some("123456"));
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].line_num, 3);
+}
+
+#[test]
+fn csv_logs_are_detected() {
+    let log = parse(&[
+        "Date,Time,Trip,Unix Time,Latitude,Longitude,RSSI,Speed,Event,Detail",
+        "15/03/2024,08:00:10,7,1710489610,-27.47,153.02,45,32,SERVICE,v:240",
+    ]);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].event.kind, EventKind::Service);
+}
+
+#[test]
+fn other_formats_can_be_added() {
+    let log = parse(&["GEN2|15/03/2024 08:00:10|7|1710489610|-27.47,153.02|SERVICE|v:240"]);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].gps_locn.lat, -27.47);
+}
+
+#[test]
+fn undetected_logs_fall_back_to_the_first_format() {
+    let mut lines = vec!["Nothing to see here"; 25];
+    lines.push("15/03/2024 08:00:10 EVENT 7 1710489610 -274700000/1530200000/0/45/32 SERVICE v:240");
+    let log = parse(&lines);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].line_num, 26);
+}
@@ -0,0 +1,108 @@
+// Tests for merging rolled over logs into one timeline.
+
+use scraper::{merge_logs, ControllerDetail, EventKind};
+
+mod common;
+use common::{event_line, parse};
+
+#[test]
+fn trips_split_across_logs_are_joined() {
+    let signon = event_line(0, "7", "SIGNON", "1234 abc operator 0 0 0 v:240");
+    let input = event_line(10, "7", "INPUT", "1 1 5 v:240");
+    let service = event_line(20, "7", "SERVICE", "v:240");
+    let trip = event_line(30, "7", "TRIP", "7 1 2 3 4 5 v:240");
+
+    // The last line of the .bak is also the first line of the .log.
+    let bak = parse(&[signon, input.clone()]);
+    let log = parse(&[input, service, trip]);
+    assert_eq!(log.scrapings[0].trip_num, "");
+
+    // Order of the logs doesn't matter.
+    let merged = merge_logs(vec![("scraper.log".to_string(), log), ("scraper.bak".to_string(), bak)]);
+
+    let kinds: Vec<&EventKind> = merged.scrapings.iter().map(|s| &s.event.kind).collect();
+    assert_eq!(kinds, [&EventKind::SignOn, &EventKind::Input, &EventKind::Service, &EventKind::Trip]);
+    assert!(merged.scrapings.iter().all(|s| s.trip_num == "7"));
+}
+
+#[test]
+fn repeated_events_in_one_log_are_kept() {
+    let input = event_line(10, "7", "INPUT", "1 1 5 v:240");
+    let log = parse(&[input.clone(), input.clone()]);
+    let other = parse(&[input]);
+
+    let merged = merge_logs(vec![("a.log".to_string(), log), ("b.log".to_string(), other)]);
+    assert_eq!(merged.scrapings.len(), 2);
+}
+
+#[test]
+fn diagnostics_name_their_log() {
+    let bad = "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/32 SERVICE".to_string();
+    let merged = merge_logs(vec![("a.log".to_string(), parse(&[bad]))]);
+
+    let line_issues: Vec<_> = merged.diagnostics.iter().filter(|d| d.line_num == 1).collect();
+    assert!(!line_issues.is_empty());
+    assert!(line_issues.iter().all(|d| d.source.as_deref() == Some("a.log")));
+}
+
+#[test]
+fn events_keep_their_log_line() {
+    let signon = event_line(0, "7", "SIGNON", "1234 abc operator 0 0 0 v:240");
+    let input = event_line(10, "7", "INPUT", "1 1 5 v:240");
+    let bak = parse(std::slice::from_ref(&signon));
+    let log = parse(&["15/03/2024 08:00:05 UNIT 123456".to_string(), input.clone()]);
+    assert_eq!((log.scrapings[0].line_num, log.scrapings[0].raw.as_str()), (2, input.as_str()));
+
+    let merged = merge_logs(vec![("scraper.log".to_string(), log), ("scraper.bak".to_string(), bak)]);
+
+    let lines: Vec<(Option<&str>, usize, &str)> = merged.scrapings.iter()
+        .map(|s| (s.source.as_deref(), s.line_num, s.raw.as_str()))
+        .collect();
+    assert_eq!(lines, [(Some("scraper.bak"), 1, signon.as_str()), (Some("scraper.log"), 2, input.as_str())]);
+}
+
+#[test]
+fn repeated_trip_numbers_have_their_own_keys() {
+    // The controller was power cycled between trips, so both are trip 1.
+    let lines = [
+        event_line(0, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(10, "1", "SERVICE", "v:240"),
+        event_line(20, "1", "TRIP", "1 1 2 3 4 5 v:240"),
+        event_line(30, "1", "SIGNON", "1234 abc operator 0 0 0 v:240"),
+        event_line(40, "1", "TRIP", "1 1 2 3 4 5 v:240"),
+    ];
+    let log = parse(&lines);
+
+    let keys: Vec<&str> = log.scrapings.iter().map(|s| s.trip_key.as_str()).collect();
+    assert!(log.scrapings.iter().all(|s| s.trip_num == "1"));
+    assert_eq!(keys[0], keys[1]);
+    assert_eq!(keys[1], keys[2]);
+    assert_eq!(keys[3], keys[4]);
+    assert_ne!(keys[0], keys[3]);
+
+    // Keys are the same when the trips are merged from logs.
+    let merged = merge_logs(vec![("scraper.log".to_string(), parse(&lines))]);
+    let merged_keys: Vec<&str> = merged.scrapings.iter().map(|s| s.trip_key.as_str()).collect();
+    assert_eq!(merged_keys, keys);
+}
+
+#[test]
+fn controller_changes_without_a_timestamp_keep_their_place() {
+    let newer = parse(&["15/03/2024 09:00:00 UNIT 300".to_string()]);
+    let older = parse(&[
+        "15/03/2024 08:00:00 UNIT 100".to_string(),
+        "99/99/2024 08:10:00 UNIT 150".to_string(),
+        "15/03/2024 08:30:00 UNIT 200".to_string(),
+    ]);
+
+    let merged = merge_logs(vec![("scraper.log".to_string(), newer), ("scraper.bak".to_string(), older)]);
+
+    let ids: Vec<&ControllerDetail> = merged.controller_history.iter().map(|change| &change.detail).collect();
+    assert_eq!(ids, [
+        &ControllerDetail::Id("100".to_string()),
+        &ControllerDetail::Id("150".to_string()),
+        &ControllerDetail::Id("200".to_string()),
+        &ControllerDetail::Id("300".to_string()),
+    ]);
+    assert_eq!(merged.controller_history[1].timestamp, None);
+}
@@ -0,0 +1,111 @@
+// Tests for parsing a whole log through the library entry point.
+
+use std::io::BufReader;
+use std::sync::mpsc;
+use std::thread;
+
+use scraper::{parse_log, EventKind, LogParser, ParsedLog};
+
+const LOG: &str = "\
+Controller log started
+15/03/2024 08:00:00 UNIT 123456
+15/03/2024 08:00:01 EVENT 0 1710489601 0/0/0/0/0 SWSTART 0 3.1.4 v:240
+15/03/2024 08:00:02 EVENT 7 1710489602 -274700000/1530200000/90/45/32 SIGNON 1234 abc operator 0 0 0 v:240
+15/03/2024 08:00:03 EVENT 7 1710489603 -274700000/1530200000/90/45/40 SERVICE v:240
+15/03/2024 08:00:04 EVENT 7 1710489604 -274700000/1530200000/90/45/0 TRIP 7 1 2 3 4 5 v:240
+";
+
+#[test]
+fn logs_are_parsed_from_a_reader() {
+    let log = parse_log(BufReader::new(LOG.as_bytes())).unwrap();
+
+    assert_eq!(log.controller_id.as_deref(), Some("123456"));
+    assert_eq!(log.controller_fw.as_deref(), Some("3.1.4"));
+    assert!(log.diagnostics.is_empty());
+
+    let kinds: Vec<&EventKind> = log.scrapings.iter().map(|s| &s.event.kind).collect();
+    assert_eq!(kinds, [&EventKind::SwStart, &EventKind::SignOn, &EventKind::Service, &EventKind::Trip]);
+    assert_eq!(log.scrapings[2].unix_time, 1710489603);
+    assert_eq!(log.scrapings[2].gps_speed, 40);
+    assert_eq!(log.scrapings[2].line_num, 5);
+}
+
+#[test]
+fn missing_controller_details_are_reported() {
+    let log = parse_log(&b"15/03/2024 08:00:03 EVENT 7 1710489603 -274700000/1530200000/90/45/40 SERVICE v:240\n"[..]).unwrap();
+
+    assert_eq!(log.controller_id, None);
+    assert_eq!(log.controller_fw, None);
+    assert_eq!(log.scrapings.len(), 1);
+    let reasons: Vec<&str> = log.diagnostics.iter().map(|d| d.reason.as_str()).collect();
+    assert_eq!(reasons, ["Controller serial number not found.", "Controller firmware version not found."]);
+}
+
+#[test]
+fn loading_carries_on_past_lines_that_are_not_utf8() {
+    let mut text = LOG.as_bytes().to_vec();
+    text.extend_from_slice(b"15/03/2024 08:00:05 Garbled \xff\xfe line\r\n");
+    text.extend_from_slice(b"15/03/2024 08:00:06 EVENT 7 1710489606 -274700000/1530200000/90/45/0 SERVICE v:240\n");
+    let log = parse_log(&text[..]).unwrap();
+
+    assert_eq!(log.scrapings.len(), 5);
+    assert_eq!(log.scrapings[4].line_num, 8);
+    let [diagnostic] = &log.diagnostics[..] else {
+        panic!("Expected 1 diagnostic, got {:?}", log.diagnostics);
+    };
+    assert_eq!(diagnostic.line_num, 7);
+    assert_eq!(diagnostic.raw, "15/03/2024 08:00:05 Garbled \u{fffd}\u{fffd} line");
+    assert_eq!(diagnostic.reason, "Invalid characters replaced.");
+}
+
+#[test]
+fn line_at_a_time_parsing_matches_whole_log_parsing() {
+    let mut parser = LogParser::new();
+    for (line_num, line) in LOG.lines().enumerate() {
+        parser.parse_line(line);
+        // Events are counted as they are found.
+        assert_eq!(parser.event_count(), line_num.saturating_sub(1));
+    }
+    let streamed = parser.finish();
+    let whole = parse_log(LOG.as_bytes()).unwrap();
+
+    assert_eq!(streamed.controller_id, whole.controller_id);
+    assert_eq!(streamed.controller_fw, whole.controller_fw);
+    let raw = |log: &ParsedLog| log.scrapings.iter().map(|s| (s.line_num, s.raw.clone())).collect::<Vec<_>>();
+    assert_eq!(raw(&streamed), raw(&whole));
+}
+
+#[test]
+fn controller_details_are_found_anywhere_in_the_log() {
+    let log = parse_log(&b"\
+15/03/2024 08:00:03 EVENT 7 1710489603 -274700000/1530200000/90/45/40 SERVICE v:240
+15/03/2024 08:00:04 EVENT 0 1710489604 0/0/0/0/0 SWSTART 0 3.1.4 v:240
+15/03/2024 08:00:05 UNIT 123456
+"[..]).unwrap();
+
+    assert_eq!(log.controller_id.as_deref(), Some("123456"));
+    assert_eq!(log.controller_fw.as_deref(), Some("3.1.4"));
+    // The SWSTART line is an event too, tagged with the new firmware.
+    assert_eq!(log.scrapings.len(), 2);
+    assert_eq!(log.scrapings[0].firmware, None);
+    assert_eq!(log.scrapings[1].firmware.as_deref(), Some("3.1.4"));
+}
+
+#[test]
+fn logs_can_be_parsed_on_a_worker_thread() {
+    let (sender, receiver) = mpsc::channel();
+
+    // Send the events found after each line, as the load worker does for progress.
+    let worker = thread::spawn(move || {
+        let mut parser = LogParser::new();
+        for line in LOG.lines() {
+            parser.parse_line(line);
+            sender.send(parser.event_count()).unwrap();
+        }
+        parser.finish()
+    });
+
+    let progress: Vec<usize> = receiver.iter().collect();
+    assert_eq!(progress, [0, 0, 1, 2, 3, 4]);
+    assert_eq!(worker.join().unwrap().scrapings.len(), 4);
+}
@@ -0,0 +1,109 @@
+// Tests for custom scrape rules.
+
+use std::sync::Arc;
+
+use scraper::{EventKind, GpsFix, LogParser, ParsedLog, RuleSchema, RuleSet, Value};
+
+const RULES: &str = r#"
+rules:
+  - name: MODEM
+    pattern: '(?P<date>\S+) (?P<time>\S+) MODEM RSSI (?P<rssi>[-0-9]+) BER (?P<ber>[.0-9]+) (?P<state>\w+)'
+  - name: CAN
+    pattern: 'CAN FAULT (?P<code>\w+)'
+"#;
+
+fn parse_with_rules(lines: &[&str]) -> ParsedLog {
+    let rules = RuleSet::new(&RuleSchema::from_yaml(RULES).unwrap());
+    let mut parser = LogParser::new().with_rules(Arc::new(rules));
+    for line in lines {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+#[test]
+fn matching_lines_become_custom_events() {
+    let log = parse_with_rules(&[
+        "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/0 SIGNON 1234 abc operator 0 0 0 v:240",
+        "15/03/2024 08:00:05 MODEM RSSI -71 BER 0.5 registered",
+        "15/03/2024 08:00:10 EVENT 7 1710489610 -274700000/1530200000/0/45/32 SERVICE v:240",
+    ]);
+
+    let kinds: Vec<&EventKind> = log.scrapings.iter().map(|s| &s.event.kind).collect();
+    assert_eq!(kinds, [&EventKind::SignOn, &EventKind::Custom("MODEM".to_string()), &EventKind::Service]);
+
+    let modem = &log.scrapings[1];
+    assert_eq!(modem.event_type(), "MODEM");
+    assert_eq!(modem.trip_num, "7");
+    assert_eq!(modem.unix_time, 1710489605);
+    assert_eq!(modem.line_num, 2);
+    assert_eq!(modem.gps_fix, GpsFix::NoFix);
+
+    let values: Vec<(&str, &Value)> = modem.event.fields.iter()
+        .map(|field| (field.label.as_str(), &field.value))
+        .collect();
+    assert_eq!(values, [
+        ("rssi", &Value::Count(-71)),
+        ("ber", &Value::Number(0.5)),
+        ("state", &Value::Text("registered".to_string())),
+    ]);
+}
+
+#[test]
+fn lines_without_a_time_take_the_previous_event_time() {
+    let log = parse_with_rules(&[
+        "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/0 SIGNON 1234 abc operator 0 0 0 v:240",
+        "can0: CAN FAULT E42",
+    ]);
+
+    let can = &log.scrapings[1];
+    assert_eq!(can.event.kind, EventKind::Custom("CAN".to_string()));
+    assert_eq!(can.unix_time, 1710489600);
+    assert_eq!(can.date_time, log.scrapings[0].date_time);
+}
+
+#[test]
+fn lines_after_a_trip_are_out_of_trip() {
+    let log = parse_with_rules(&[
+        "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/0 SIGNON 1234 abc operator 0 0 0 v:240",
+        "can0: CAN FAULT E41",
+        "15/03/2024 08:00:10 EVENT 7 1710489610 -274700000/1530200000/0/45/0 TRIP 7 1 2 3 4 5 v:240",
+        "can0: CAN FAULT E42",
+    ]);
+
+    let on_trip: Vec<(&str, bool)> = log.scrapings.iter().map(|s| (s.event_type(), s.on_trip)).collect();
+    assert_eq!(on_trip, [("SIGNON", true), ("CAN", true), ("TRIP", true), ("CAN", false)]);
+    assert_eq!(log.scrapings[3].trip_num, "");
+}
+
+#[test]
+fn lines_before_any_event_time_are_reported() {
+    let log = parse_with_rules(&[
+        "can0: CAN FAULT E42",
+        "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/0 SIGNON 1234 abc operator 0 0 0 v:240",
+    ]);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert!(log.diagnostics.iter().any(|d| d.line_num == 1 && d.reason == "Rule match before any event time."));
+}
+
+#[test]
+fn event_lines_and_unmatched_lines_are_not_scraped_by_rules() {
+    let log = parse_with_rules(&[
+        "15/03/2024 08:00:00 EVENT 7 1710489600 -274700000/1530200000/0/45/0 SERVICE CAN FAULT E42",
+        "15/03/2024 08:00:05 MODEM idle",
+    ]);
+
+    assert_eq!(log.scrapings.len(), 1);
+    assert_eq!(log.scrapings[0].event.kind, EventKind::Service);
+}
+
+#[test]
+fn invalid_rule_patterns_are_left_out() {
+    let schema = RuleSchema::from_yaml("rules:\n  - { name: BAD, pattern: '(unclosed' }\n  - { name: OK, pattern: 'OK' }\n").unwrap();
+    let rules = RuleSet::new(&schema);
+
+    assert!(!rules.is_empty());
+    assert_eq!(rules.scrape("all OK").map(|m| m.name), Some("OK".to_string()));
+    assert!(RuleSet::new(&RuleSchema::from_yaml(scraper::rules::DEFAULT_RULES).unwrap()).is_empty());
+}
@@ -0,0 +1,106 @@
+// Tests for reading log timestamps in different date orders,
+// 12 hour clocks and time zones.
+
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use scraper::{CsvMapping, DateOrder, LogParser, TimestampFormat};
+
+// Make a SERVICE EVENT line logged at the given date and time.
+fn event_line(date_time: &str) -> String {
+    format!("{} EVENT 1234 1710489600 -274700000/1530200000/0/45/32 SERVICE v:240", date_time)
+}
+
+// Parse lines with the given timestamp format.
+fn parse(lines: &[String], timestamp_format: TimestampFormat) -> scraper::ParsedLog {
+    let mut parser = LogParser::new().with_timestamp_format(timestamp_format);
+    for line in lines {
+        parser.parse_line(line);
+    }
+    parser.finish()
+}
+
+#[test]
+fn day_first_utc_is_the_default() {
+    let parsed = parse(&[event_line("15/03/2024 08:00:00")], TimestampFormat::default());
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    assert_eq!(parsed.scrapings[0].timestamp, Some(Utc.with_ymd_and_hms(2024, 3, 15, 8, 0, 0).unwrap()));
+}
+
+#[test]
+fn us_twelve_hour_lines_are_not_dropped() {
+    let lines = [
+        event_line("3/5/2024 12:15:30 AM"),
+        event_line("03/05/2024 1:02:03.250 PM"),
+        event_line("12/31/2024 12:00:00 PM"),
+    ];
+    let timestamp_format = TimestampFormat {
+        date_order: DateOrder::Mdy,
+        source_tz: Tz::UTC,
+    };
+    let parsed = parse(&lines, timestamp_format);
+
+    assert_eq!(parsed.scrapings.len(), 3);
+    let times: Vec<_> = parsed.scrapings.iter().map(|s| s.timestamp.unwrap()).collect();
+    assert_eq!(times[0], Utc.with_ymd_and_hms(2024, 3, 5, 0, 15, 30).unwrap());
+    assert_eq!(times[1].timestamp_millis(), Utc.with_ymd_and_hms(2024, 3, 5, 13, 2, 3).unwrap().timestamp_millis() + 250);
+    assert_eq!(times[2], Utc.with_ymd_and_hms(2024, 12, 31, 12, 0, 0).unwrap());
+    assert_eq!(parsed.scrapings[0].date_time, "3/5/2024 12:15:30 AM");
+}
+
+#[test]
+fn western_hemisphere_lines_are_not_dropped() {
+    let line = "03/15/2024 1:05:00 PM EVENT 7 1710489600 417000000/-876000000/0/45/32 SERVICE v:245".to_string();
+    let timestamp_format = TimestampFormat {
+        date_order: DateOrder::Mdy,
+        source_tz: Tz::UTC,
+    };
+    let parsed = parse(&[line], timestamp_format);
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    assert_eq!(parsed.scrapings[0].gps_locn.lat, 41.7);
+    assert_eq!(parsed.scrapings[0].gps_locn.lon, -87.6);
+    assert_eq!(parsed.scrapings[0].timestamp, Some(Utc.with_ymd_and_hms(2024, 3, 15, 13, 5, 0).unwrap()));
+    assert!(parsed.diagnostics.iter().all(|d| d.line_num == 0));
+}
+
+#[test]
+fn source_time_zone_is_converted_to_utc() {
+    let timestamp_format = TimestampFormat {
+        date_order: DateOrder::Mdy,
+        source_tz: Tz::America__Chicago,
+    };
+
+    // Central Standard Time in winter, Central Daylight Time in summer.
+    let parsed = parse(&[event_line("01/15/2024 9:00:00 AM"), event_line("07/15/2024 9:00:00 AM")], timestamp_format);
+
+    assert_eq!(parsed.scrapings[0].timestamp, Some(Utc.with_ymd_and_hms(2024, 1, 15, 15, 0, 0).unwrap()));
+    assert_eq!(parsed.scrapings[1].timestamp, Some(Utc.with_ymd_and_hms(2024, 7, 15, 14, 0, 0).unwrap()));
+}
+
+#[test]
+fn unreadable_dates_are_kept_and_reported() {
+    // Month 15 doesn't exist when the month comes first.
+    let timestamp_format = TimestampFormat {
+        date_order: DateOrder::Mdy,
+        source_tz: Tz::UTC,
+    };
+    let parsed = parse(&[event_line("15/03/2024 08:00:00")], timestamp_format);
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    assert_eq!(parsed.scrapings[0].timestamp, None);
+    assert!(parsed.diagnostics.iter().any(|d| d.line_num == 1 && d.reason.starts_with("Invalid date time")));
+}
+
+#[test]
+fn non_digit_fractions_are_reported() {
+    // Fractional seconds with a non-ASCII character used to panic.
+    let mut parser = LogParser::new().with_csv(CsvMapping::default());
+    parser.parse_line("Date,Time,Trip,Unix Time,Event,Latitude,Longitude,RSSI,Speed,Detail");
+    parser.parse_line("15/03/2024,08:00:00.12345678é,1,1710489600,SERVICE,-27.47,153.02,45,0,v:240");
+    let parsed = parser.finish();
+
+    assert_eq!(parsed.scrapings.len(), 1);
+    assert_eq!(parsed.scrapings[0].timestamp, None);
+    assert!(parsed.diagnostics.iter().any(|d| d.line_num == 2 && d.reason.starts_with("Invalid date time")));
+}